@@ -9,7 +9,7 @@ pub mod state;
 mod contract_tests;
 
 pub use crate::error::ContractError;
-pub use crate::msg::{ExecuteMsg, InstantiateMsg, MinterResponse, QueryMsg};
+pub use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, MinterResponse, QueryMsg};
 pub use crate::state::Cw721Contract;
 
 // These types are re-exported so that contracts interacting with this
@@ -33,7 +33,8 @@ pub mod entry {
 
     #[cfg(not(feature = "library"))]
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+    use semver::Version;
 
     // This makes a conscious choice on the various generics used by the contract
     #[cfg_attr(not(feature = "library"), entry_point)]
@@ -65,6 +66,31 @@ pub mod entry {
         let tract = Cw721Contract::<Extension, Empty, Empty, Empty>::default();
         tract.query(deps, env, msg)
     }
+
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+        let previous = ensure_from_older_version(deps.storage)?;
+        //Nothing in the storage layout changed since 0.1.0
+        cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "migrate")
+            .add_attribute("from_version", previous.to_string())
+            .add_attribute("to_version", CONTRACT_VERSION))
+    }
+
+    //Only this contract can be migrated, and never to an older version
+    fn ensure_from_older_version(storage: &dyn Storage) -> Result<Version, ContractError> {
+        let stored = cw2::get_contract_version(storage)?;
+        if stored.contract != CONTRACT_NAME {
+            return Err(ContractError::WrongMigrateContract(stored.contract));
+        }
+        let previous: Version = stored.version.parse()?;
+        if previous > CONTRACT_VERSION.parse()? {
+            return Err(ContractError::WrongMigrateVersion(stored.version));
+        }
+        Ok(previous)
+    }
 }
 
 #[cfg(test)]
@@ -110,4 +136,27 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn migrate_from_older_version() {
+        let mut deps = mock_dependencies();
+
+        //Layout stored by 0.1.0
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        entry::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        //Downgrades are refused
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "9.0.0").unwrap();
+        let err = entry::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(err, ContractError::WrongMigrateVersion("9.0.0".into()));
+
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "0.1.0").unwrap();
+        let err = entry::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WrongMigrateContract("crates.io:cw20-base".into())
+        );
+    }
 }
@@ -20,6 +20,8 @@ pub struct InstantiateMsg {
     pub collection_info: CollectionInfo<RoyaltyInfoResponse>,
 }
 
+#[cw_serde]
+pub struct MigrateMsg {}
 
 #[cw_serde]
 pub struct CollectionInfo<T> {
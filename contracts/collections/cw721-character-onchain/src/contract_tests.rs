@@ -2,7 +2,7 @@
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
 
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, CosmosMsg, DepsMut, Empty, Response, StdError, WasmMsg,
+    from_binary, to_binary, Addr, CosmosMsg, Deps, DepsMut, Empty, Response, StdError, WasmMsg,
 };
 
 use cw721::{
@@ -15,7 +15,7 @@ use cw721_base::msg::{
 use cw_ownable::{Expiration, OwnershipError};
 
 use crate::error::ContractError;
-use crate::msg::Metadata;
+use crate::msg::{Metadata, MetadataAttribute, PauseStatusResponse, TokenMetadataResponse};
 use crate::{
     Cw721CharacterContract, Cw721Contract, ExecuteMsg, Extension, InstantiateMsg, MinterResponse,
     QueryMsg,
@@ -39,6 +39,7 @@ fn setup_contract(deps: DepsMut<'_>) -> Cw721Contract<'static, Extension, Empty,
             explicit_content: None,
             royalty_info: None,
         },
+        image_template: None,
     };
     let info = mock_info("creator", &[]);
     let res = contract.instantiate(deps, mock_env(), info, msg).unwrap();
@@ -63,6 +64,7 @@ fn proper_instantiation() {
             explicit_content: None,
             royalty_info: None,
         },
+        image_template: None,
     };
     let info = mock_info("creator", &[]);
 
@@ -105,12 +107,14 @@ fn minting() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: false,
@@ -147,12 +151,14 @@ fn minting() {
             token_uri: None,
             extension: Metadata {
                 name: Some(String::from("Cat1")),
-                ears: Some(String::from("Stiff")),
-                eyes: Some(String::from("Aviator")),
-                mouth: Some(String::from("Cool")),
-                fur_type: Some(String::from("Stripes")),
-                fur_color: Some(String::from("Red")),
-                tail_shape: Some(String::from("Heart")),
+                traits: vec![
+                    (String::from("ears"), String::from("Stiff")),
+                    (String::from("eyes"), String::from("Aviator")),
+                    (String::from("mouth"), String::from("Cool")),
+                    (String::from("fur_type"), String::from("Stripes")),
+                    (String::from("fur_color"), String::from("Red")),
+                    (String::from("tail_shape"), String::from("Heart")),
+                ],
                 rarity: None,
                 traits_equipped: None,
                 locked: false,
@@ -179,12 +185,14 @@ fn minting() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: false,
@@ -216,12 +224,14 @@ fn test_update_minter() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: false,
@@ -300,12 +310,14 @@ fn test_update_minter() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: false,
@@ -337,12 +349,14 @@ fn burning() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: false,
@@ -398,12 +412,14 @@ fn transferring_nft() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: true,
@@ -448,6 +464,87 @@ fn transferring_nft() {
     );
 }
 
+#[test]
+fn pausing_transfers() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let token_id = "grow1".to_string();
+    let mint_msg = ExecuteMsg::Mint {
+        token_id: token_id.clone(),
+        owner: String::from("venus"),
+        token_uri: None,
+        extension: Metadata {
+            name: Some(String::from("Cat1")),
+            traits: vec![],
+            rarity: None,
+            traits_equipped: None,
+            locked: true,
+        },
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    // only the minter can pause
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            ExecuteMsg::PauseTransfers { paused: true },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::PauseTransfers { paused: true },
+        )
+        .unwrap();
+    let status: PauseStatusResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert!(status.transfers_paused);
+
+    let transfer_msg = ExecuteMsg::TransferNft {
+        recipient: String::from("random"),
+        token_id: token_id.clone(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::TransfersPaused {});
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::PauseTransfers { paused: false },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("venus", &[]),
+            transfer_msg,
+        )
+        .unwrap();
+}
+
 #[test]
 fn sending_nft() {
     let mut deps = mock_dependencies();
@@ -463,12 +560,14 @@ fn sending_nft() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: true,
@@ -536,12 +635,14 @@ fn sending_nft() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: false,
@@ -583,12 +684,14 @@ fn approving_revoking() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: true,
@@ -738,12 +841,14 @@ fn approving_all_revoking_all() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: true,
@@ -765,12 +870,14 @@ fn approving_all_revoking_all() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")) ,
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: true,
@@ -1040,12 +1147,14 @@ fn query_tokens_by_owner() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: false,
@@ -1061,12 +1170,14 @@ fn query_tokens_by_owner() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: false,
@@ -1082,12 +1193,14 @@ fn query_tokens_by_owner() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: false,
@@ -1269,12 +1382,14 @@ fn modify_a_character() {
         token_uri: None,
         extension: Metadata {
             name: Some(String::from("Cat1")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: false,
@@ -1289,12 +1404,14 @@ fn modify_a_character() {
 
     let new_extension = Metadata {
         name: Some(String::from("Cat2")),
-        ears: Some(String::from("Stiff")),
-        eyes: Some(String::from("Aviator")),
-        mouth: Some(String::from("Cool")),
-        fur_type: Some(String::from("Stripes")),
-        fur_color: Some(String::from("Red")),
-        tail_shape: Some(String::from("Heart")),
+        traits: vec![
+            (String::from("ears"), String::from("Stiff")),
+            (String::from("eyes"), String::from("Aviator")),
+            (String::from("mouth"), String::from("Cool")),
+            (String::from("fur_type"), String::from("Stripes")),
+            (String::from("fur_color"), String::from("Red")),
+            (String::from("tail_shape"), String::from("Heart")),
+        ],
         rarity: None,
         traits_equipped: None,
         locked: false,
@@ -1318,12 +1435,14 @@ fn modify_a_character() {
         token.extension,
         Metadata {
             name: Some(String::from("Cat2")),
-            ears: Some(String::from("Stiff")),
-            eyes: Some(String::from("Aviator")),
-            mouth: Some(String::from("Cool")),
-            fur_type: Some(String::from("Stripes")),
-            fur_color: Some(String::from("Red")),
-            tail_shape: Some(String::from("Heart")),
+            traits: vec![
+                (String::from("ears"), String::from("Stiff")),
+                (String::from("eyes"), String::from("Aviator")),
+                (String::from("mouth"), String::from("Cool")),
+                (String::from("fur_type"), String::from("Stripes")),
+                (String::from("fur_color"), String::from("Red")),
+                (String::from("tail_shape"), String::from("Heart")),
+            ],
             rarity: None,
             traits_equipped: None,
             locked: false,
@@ -1337,3 +1456,99 @@ fn modify_a_character() {
         .unwrap_err();
     assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
 }
+
+#[test]
+fn token_metadata() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint = |deps: DepsMut, token_id: &str, name: Option<&str>, rarity: Option<&str>| {
+        let mint_msg = ExecuteMsg::Mint {
+            token_id: token_id.to_string(),
+            owner: String::from("medusa"),
+            token_uri: None,
+            extension: Metadata {
+                name: name.map(String::from),
+                traits: vec![
+                    (String::from("ears"), String::from("Stiff")),
+                    (String::from("eyes"), String::from("Aviator")),
+                ],
+                rarity: rarity.map(String::from),
+                traits_equipped: None,
+                locked: false,
+            },
+        };
+        contract
+            .execute(deps, mock_env(), mock_info(MINTER, &[]), mint_msg)
+            .unwrap();
+    };
+    mint(deps.as_mut(), "1", Some("Cat1"), Some("rare"));
+    mint(deps.as_mut(), "2", None, None);
+
+    let query = |deps: Deps, token_id: &str| {
+        let msg = QueryMsg::TokenMetadata {
+            token_id: token_id.to_string(),
+        };
+        //Contracts using this one as a library get the same answer as the entry point
+        let res = contract.query(deps, mock_env(), msg.clone()).unwrap();
+        assert_eq!(crate::entry::query(deps, mock_env(), msg).unwrap(), res);
+        from_binary::<TokenMetadataResponse>(&res).unwrap()
+    };
+
+    //Without a template the collection image is used
+    assert_eq!(
+        query(deps.as_ref(), "1"),
+        TokenMetadataResponse {
+            name: "Cat1".to_string(),
+            description: "description".to_string(),
+            image: Some("https://example.com/image.png".to_string()),
+            attributes: vec![
+                MetadataAttribute {
+                    trait_type: "ears".to_string(),
+                    value: "Stiff".to_string(),
+                },
+                MetadataAttribute {
+                    trait_type: "eyes".to_string(),
+                    value: "Aviator".to_string(),
+                },
+                MetadataAttribute {
+                    trait_type: "rarity".to_string(),
+                    value: "rare".to_string(),
+                },
+                MetadataAttribute {
+                    trait_type: "locked".to_string(),
+                    value: "false".to_string(),
+                },
+            ],
+        }
+    );
+
+    let set_template = |template: &str| ExecuteMsg::SetImageTemplate {
+        image_template: Some(template.to_string()),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            set_template("https://img.example.com/{token_id}.png"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            set_template("https://img.example.com/{token_id}.png"),
+        )
+        .unwrap();
+
+    let metadata = query(deps.as_ref(), "2");
+    assert_eq!(metadata.name, format!("{} #2", CONTRACT_NAME));
+    assert_eq!(
+        metadata.image,
+        Some("https://img.example.com/2.png".to_string())
+    );
+    assert_eq!(metadata.attributes.len(), 3);
+}
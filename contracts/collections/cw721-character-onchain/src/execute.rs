@@ -5,19 +5,22 @@ use cw721_base::msg::{
 use cw_ownable::OwnershipError;
 
 use cosmwasm_std::{
-    Binary, Decimal, Deps, DepsMut, Empty, Env, Event, MessageInfo, Response, StdResult,
+    Binary, Decimal, Deps, DepsMut, Empty, Env, Event, MessageInfo, Order, Response, StdResult,
 };
+use cw_storage_plus::{Bound, Map};
 
 use cw721::{ContractInfoResponse, Cw721ReceiveMsg, Expiration};
 use url::Url;
 
 use crate::error::ContractError;
 use crate::msg::{ExecuteMsg, InstantiateMsg, Metadata};
-use crate::state::{Approval, TokenInfo};
+use crate::state::{Approval, StoredMetadata, TokenInfo};
 use crate::Cw721CharacterContract;
 use crate::{CONTRACT_NAME, CONTRACT_VERSION};
 
 const MAX_DESCRIPTION_LENGTH: u32 = 512;
+const DEFAULT_MIGRATE_LIMIT: u32 = 100;
+const MAX_MIGRATE_LIMIT: u32 = 500;
 
 impl Cw721CharacterContract<'_> {
     pub fn instantiate(
@@ -68,6 +71,11 @@ impl Cw721CharacterContract<'_> {
         self.collection_info.save(deps.storage, &collection_info)?;
         self.frozen_collection_info.save(deps.storage, &false)?;
 
+        if let Some(image_template) = msg.image_template {
+            Url::parse(&image_template.replace("{token_id}", "1"))?;
+            self.image_template.save(deps.storage, &image_template)?;
+        }
+
         Ok(Response::default()
             .add_attribute("action", "instantiate")
             .add_attribute("contract_name", CONTRACT_NAME)
@@ -123,6 +131,13 @@ impl Cw721CharacterContract<'_> {
                 token_id,
                 new_values,
             } => self.modify_character(deps, info, token_id, new_values),
+            ExecuteMsg::PauseTransfers { paused } => self.pause_transfers(deps, info, paused),
+            ExecuteMsg::SetImageTemplate { image_template } => {
+                self.set_image_template(deps, info, image_template)
+            }
+            ExecuteMsg::MigrateTokens { start_after, limit } => {
+                self.migrate_tokens(deps, start_after, limit)
+            }
             ExecuteMsg::UpdateOwnership(action) => Self::update_ownership(deps, env, info, action),
             ExecuteMsg::Extension { msg: _ } => Ok(Response::default()),
         }
@@ -286,6 +301,30 @@ impl Cw721CharacterContract<'_> {
         })
     }
 
+    fn set_image_template(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        image_template: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let collection = self.query_collection_info(deps.as_ref())?;
+        if collection.creator != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        match &image_template {
+            Some(image_template) => {
+                Url::parse(&image_template.replace("{token_id}", "1"))?;
+                self.image_template.save(deps.storage, image_template)?;
+            }
+            None => self.image_template.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_image_template")
+            .add_attribute("image_template", image_template.unwrap_or_default()))
+    }
+
     fn modify_character(
         &self,
         deps: DepsMut,
@@ -316,6 +355,63 @@ impl Cw721CharacterContract<'_> {
             .add_attribute("token_id", token_id))
     }
 
+    fn pause_transfers(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        paused: bool,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        self.transfers_paused.save(deps.storage, &paused)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "pause_transfers")
+            .add_attribute("paused", paused.to_string()))
+    }
+
+    //Only the layout changes, so the owner index entries are already right
+    fn migrate_tokens(
+        &self,
+        deps: DepsMut,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<Response, ContractError> {
+        let limit = limit
+            .unwrap_or(DEFAULT_MIGRATE_LIMIT)
+            .min(MAX_MIGRATE_LIMIT) as usize;
+        let start = start_after.as_deref().map(Bound::exclusive);
+
+        let stored_tokens: Map<&str, TokenInfo<StoredMetadata>> = Map::new("tokens");
+        let tokens = stored_tokens
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let mut migrated = 0u32;
+        for (token_id, token) in &tokens {
+            if let StoredMetadata::Legacy(legacy) = &token.extension {
+                stored_tokens.remove(deps.storage, token_id);
+                let token = TokenInfo {
+                    owner: token.owner.clone(),
+                    approvals: token.approvals.clone(),
+                    token_uri: token.token_uri.clone(),
+                    extension: Metadata::from(legacy.clone()),
+                };
+                self.tokens.save(deps.storage, token_id, &token)?;
+                migrated += 1;
+            }
+        }
+
+        let last_token_id = tokens
+            .last()
+            .map(|(token_id, _)| token_id.clone())
+            .unwrap_or_default();
+        Ok(Response::new()
+            .add_attribute("action", "migrate_tokens")
+            .add_attribute("migrated", migrated.to_string())
+            .add_attribute("last_token_id", last_token_id))
+    }
+
     fn transfer_nft(
         &self,
         deps: DepsMut,
@@ -479,6 +575,9 @@ impl Cw721CharacterContract<'_> {
         recipient: &str,
         token_id: &str,
     ) -> Result<TokenInfo<Metadata>, ContractError> {
+        if self.transfers_paused(deps.storage)? {
+            return Err(ContractError::TransfersPaused {});
+        }
         let mut token = self.tokens.load(deps.storage, token_id)?;
         // ensure we have permissions
         self.check_can_send(deps.as_ref(), env, info, &token)?;
@@ -501,7 +600,7 @@ impl Cw721CharacterContract<'_> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
         let mut token = self.tokens.load(deps.storage, token_id)?;
         //check if already locked
-        if token.extension.locked == true {
+        if token.extension.locked {
             return Err(ContractError::CharacterAlreadyLocked {});
         }
         // ensure we have permissions
@@ -588,7 +687,7 @@ impl Cw721CharacterContract<'_> {
         token: &TokenInfo<Metadata>,
     ) -> Result<(), ContractError> {
         //Ensure character is locked
-        if token.extension.locked == false {
+        if !token.extension.locked {
             return Err(ContractError::CharacterNotLocked {});
         }
 
@@ -8,7 +8,8 @@ pub mod state;
 #[cfg(test)]
 mod contract_tests;
 
-pub use crate::msg::{ExecuteMsg, InstantiateMsg, MinterResponse, QueryMsg};
+pub use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, MinterResponse, QueryMsg};
+pub use crate::query::TokenMetadataQuery;
 pub use crate::state::Cw721Contract;
 
 // These types are re-exported so that contracts interacting with this
@@ -33,7 +34,8 @@ pub mod entry {
     use super::*;
     #[cfg(not(feature = "library"))]
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+    use semver::Version;
 
     // This makes a conscious choice on the various generics used by the contract
     #[cfg_attr(not(feature = "library"), entry_point)]
@@ -65,13 +67,43 @@ pub mod entry {
         let tract = Cw721CharacterContract::default();
         tract.query(deps, env, msg)
     }
+
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+        let previous = ensure_from_older_version(deps.storage)?;
+        //The transfer pause added after 0.1.0 defaults to unpaused when it is not stored.
+        //Tokens stored before 0.3.0 are rewritten afterwards with `MigrateTokens`, a page at a time
+        cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "migrate")
+            .add_attribute("from_version", previous.to_string())
+            .add_attribute("to_version", CONTRACT_VERSION))
+    }
+
+    fn ensure_from_older_version(storage: &dyn Storage) -> Result<Version, ContractError> {
+        let stored = cw2::get_contract_version(storage)?;
+        if stored.contract != CONTRACT_NAME {
+            return Err(ContractError::WrongMigrateContract(stored.contract));
+        }
+        let previous: Version = stored.version.parse()?;
+        if previous > CONTRACT_VERSION.parse()? {
+            return Err(ContractError::WrongMigrateVersion(stored.version));
+        }
+        Ok(previous)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, Addr, DepsMut};
     use cw2::ContractVersion;
     use cw721_base::msg::CollectionInfo;
+    use cw_storage_plus::Map;
+
+    use crate::error::ContractError;
+    use crate::state::{LegacyMetadata, TokenInfo};
 
     use super::*;
 
@@ -96,6 +128,7 @@ mod tests {
                     explicit_content: None,
                     royalty_info: None,
                 },
+                image_template: None,
             },
         )
         .unwrap();
@@ -109,4 +142,121 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn migrate_from_older_version() {
+        let mut deps = mock_dependencies();
+
+        //Layout stored by 0.1.0
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        entry::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        let tract = Cw721CharacterContract::default();
+        assert!(!tract.transfers_paused(deps.as_ref().storage).unwrap());
+
+        //Tokens stored by 0.2.0 get their trait fields moved into the slot list
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.2.0").unwrap();
+        let legacy_tokens: Map<&str, TokenInfo<LegacyMetadata>> = Map::new("tokens");
+        let legacy = TokenInfo {
+            owner: Addr::unchecked("owner"),
+            approvals: vec![],
+            token_uri: None,
+            extension: LegacyMetadata {
+                name: Some("Tom".into()),
+                ears: Some("pointy".into()),
+                eyes: None,
+                mouth: None,
+                fur_type: Some("fluffy".into()),
+                fur_color: None,
+                tail_shape: Some("long".into()),
+                rarity: Some("common".into()),
+                traits_equipped: None,
+                locked: false,
+            },
+        };
+        for token_id in ["1", "2"] {
+            legacy_tokens
+                .save(deps.as_mut().storage, token_id, &legacy)
+                .unwrap();
+        }
+        entry::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        tract.tokens.load(deps.as_ref().storage, "1").unwrap_err();
+
+        //Anyone rewrites the tokens a page at a time, the tokens already rewritten are skipped
+        let migrate_tokens = |deps: DepsMut, start_after: Option<&str>| {
+            let res = entry::execute(
+                deps,
+                mock_env(),
+                mock_info("anyone", &[]),
+                ExecuteMsg::MigrateTokens {
+                    start_after: start_after.map(String::from),
+                    limit: Some(1),
+                },
+            )
+            .unwrap();
+            (
+                res.attributes[1].value.clone(),
+                res.attributes[2].value.clone(),
+            )
+        };
+        assert_eq!(
+            migrate_tokens(deps.as_mut(), None),
+            ("1".to_string(), "1".to_string())
+        );
+        tract.tokens.load(deps.as_ref().storage, "2").unwrap_err();
+        assert_eq!(
+            migrate_tokens(deps.as_mut(), None),
+            ("0".to_string(), "1".to_string())
+        );
+        assert_eq!(
+            migrate_tokens(deps.as_mut(), Some("1")),
+            ("1".to_string(), "2".to_string())
+        );
+        assert_eq!(
+            migrate_tokens(deps.as_mut(), Some("2")),
+            ("0".to_string(), "".to_string())
+        );
+
+        for token_id in ["1", "2"] {
+            let token = tract.tokens.load(deps.as_ref().storage, token_id).unwrap();
+            assert_eq!(
+                token.extension.traits,
+                vec![
+                    ("ears".to_string(), "pointy".to_string()),
+                    ("fur_type".to_string(), "fluffy".to_string()),
+                    ("tail_shape".to_string(), "long".to_string()),
+                ]
+            );
+            assert_eq!(token.extension.trait_value("fur_type").unwrap(), "fluffy");
+            assert_eq!(token.extension.trait_value("eyes"), None);
+        }
+        let tokens: cw721::TokensResponse = from_binary(
+            &entry::query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Tokens {
+                    owner: "owner".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(tokens.tokens, vec!["1".to_string(), "2".to_string()]);
+
+        //Downgrades are refused
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "9.0.0").unwrap();
+        let err = entry::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(err, ContractError::WrongMigrateVersion("9.0.0".into()));
+
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "0.1.0").unwrap();
+        let err = entry::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WrongMigrateContract("crates.io:cw20-base".into())
+        );
+    }
 }
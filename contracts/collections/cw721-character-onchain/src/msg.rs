@@ -18,17 +18,20 @@ pub struct InstantiateMsg {
 
     //Info of collection to be kept on chain
     pub collection_info: CollectionInfo<RoyaltyInfoResponse>,
+
+    //Url of the character images, `{token_id}` is replaced by the id of each character
+    #[serde(default)]
+    pub image_template: Option<String>,
 }
 
+#[cw_serde]
+pub struct MigrateMsg {}
+
 #[cw_serde]
 pub struct Metadata {
     pub name: Option<String>,
-    pub ears: Option<String>,
-    pub eyes: Option<String>,
-    pub mouth: Option<String>,
-    pub fur_type: Option<String>,
-    pub fur_color: Option<String>,
-    pub tail_shape: Option<String>,
+    //Slot and value of each filled trait slot, in the order of the slot registry kept in mintables
+    pub traits: Vec<(String, String)>,
     //If the character is a pre-made one sold in the store, we need to keep track of the rarity to know the mint price
     pub rarity: Option<String>,
     //Every time we modify the character with new traits, we keep the ID of the traits so that we can burn them if we lock the character
@@ -36,6 +39,15 @@ pub struct Metadata {
     pub locked: bool,
 }
 
+impl Metadata {
+    pub fn trait_value(&self, slot: &str) -> Option<&String> {
+        self.traits
+            .iter()
+            .find(|(trait_slot, _)| trait_slot == slot)
+            .map(|(_, value)| value)
+    }
+}
+
 pub type Extension = Metadata;
 
 /// This is like Cw721ExecuteMsg but we add a Mint command for an owner
@@ -118,6 +130,23 @@ pub enum ExecuteMsg<Metadata, E> {
         new_values: Metadata,
     },
 
+    //Stop or resume transfers and sends, only the minter can do it
+    PauseTransfers {
+        paused: bool,
+    },
+
+    //Set or remove the image url template, only the collection creator can do it
+    SetImageTemplate {
+        image_template: Option<String>,
+    },
+
+    //Rewrites a page of tokens stored up to 0.2.0 with the slot list, anyone can send it after migrating.
+    //Tokens already in the current layout are skipped, the `last_token_id` attribute is the next `start_after`
+    MigrateTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
     /// Extension msg
     Extension {
         msg: E,
@@ -215,6 +244,14 @@ pub enum QueryMsg<Q: JsonSchema> {
     #[returns(CharacterInfoResponse<Extension>)]
     CharacterInfo { token_id: String },
 
+    /// Return whether transfers are paused
+    #[returns(PauseStatusResponse)]
+    PauseStatus {},
+
+    /// Return the metadata of a character following the ERC721 Metadata JSON Schema
+    #[returns(TokenMetadataResponse)]
+    TokenMetadata { token_id: String },
+
     /// Extension query
     #[returns(())]
     Extension { msg: Q },
@@ -241,3 +278,23 @@ pub struct CharacterInfoResponse<Extension> {
     pub owner: String,
     pub token_info: Extension,
 }
+
+#[cw_serde]
+pub struct PauseStatusResponse {
+    pub transfers_paused: bool,
+}
+
+/// Token metadata in the shape of the ERC721 Metadata JSON Schema used by marketplaces and wallets
+#[cw_serde]
+pub struct TokenMetadataResponse {
+    pub name: String,
+    pub description: String,
+    pub image: Option<String>,
+    pub attributes: Vec<MetadataAttribute>,
+}
+
+#[cw_serde]
+pub struct MetadataAttribute {
+    pub trait_type: String,
+    pub value: String,
+}
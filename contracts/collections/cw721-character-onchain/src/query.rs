@@ -14,7 +14,10 @@ use cw721::{
 use cw_storage_plus::Bound;
 use cw_utils::maybe_addr;
 
-use crate::msg::{MinterResponse, QueryMsg, CharacterInfoResponse};
+use crate::msg::{
+    CharacterInfoResponse, Extension, MetadataAttribute, MinterResponse, PauseStatusResponse,
+    QueryMsg, TokenMetadataResponse,
+};
 use crate::state::{Approval, Cw721Contract, TokenInfo};
 
 const DEFAULT_LIMIT: u32 = 10;
@@ -249,7 +252,10 @@ where
     E: CustomMsg,
     Q: CustomMsg,
 {
-    pub fn query(&self, deps: Deps, env: Env, msg: QueryMsg<Q>) -> StdResult<Binary> {
+    pub fn query(&self, deps: Deps, env: Env, msg: QueryMsg<Q>) -> StdResult<Binary>
+    where
+        Self: TokenMetadataQuery,
+    {
         match msg {
             QueryMsg::Minter {} => to_binary(&self.minter(deps)?),
             QueryMsg::ContractInfo {} => to_binary(&self.contract_info(deps)?),
@@ -322,6 +328,12 @@ where
             QueryMsg::Ownership {} => to_binary(&Self::ownership(deps)?),
             QueryMsg::CollectionInfo {} => to_binary(&self.collection_info(deps)?),
             QueryMsg::CharacterInfo { token_id } => to_binary(&self.character_info(deps, token_id)?),
+            QueryMsg::PauseStatus {} => to_binary(&PauseStatusResponse {
+                transfers_paused: self.transfers_paused(deps.storage)?,
+            }),
+            QueryMsg::TokenMetadata { token_id } => {
+                to_binary(&self.token_metadata(deps, token_id)?)
+            }
             QueryMsg::Extension { msg: _ } => Ok(Binary::default()),
         }
     }
@@ -334,9 +346,16 @@ where
         Ok(MinterResponse { minter })
     }
 
-    pub fn character_info(&self, deps:Deps, token_id: String) -> StdResult<CharacterInfoResponse<T>> {
+    pub fn character_info(
+        &self,
+        deps: Deps,
+        token_id: String,
+    ) -> StdResult<CharacterInfoResponse<T>> {
         let info = self.tokens.load(deps.storage, &token_id)?;
-        Ok(CharacterInfoResponse { owner: info.owner.into_string(), token_info: info.extension })
+        Ok(CharacterInfoResponse {
+            owner: info.owner.into_string(),
+            token_info: info.extension,
+        })
     }
 
     pub fn ownership(deps: Deps) -> StdResult<cw_ownable::Ownership<Addr>> {
@@ -390,3 +409,56 @@ fn humanize_approval(approval: &Approval) -> cw721::Approval {
         expires: approval.expires,
     }
 }
+
+/// Describes tokens following the ERC721 Metadata JSON Schema, for the contracts whose extension it knows
+pub trait TokenMetadataQuery {
+    fn token_metadata(&self, deps: Deps, token_id: String) -> StdResult<TokenMetadataResponse>;
+}
+
+impl<'a, C, E, Q> TokenMetadataQuery for Cw721Contract<'a, Extension, C, E, Q>
+where
+    C: CustomMsg,
+    E: CustomMsg,
+    Q: CustomMsg,
+{
+    //Unnamed characters are named after the collection, the image falls back to the collection image
+    fn token_metadata(&self, deps: Deps, token_id: String) -> StdResult<TokenMetadataResponse> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        let contract_info = self.contract_info.load(deps.storage)?;
+        let collection_info = self.collection_info.load(deps.storage)?;
+        let character = token.extension;
+
+        let image = match self.image_template.may_load(deps.storage)? {
+            Some(template) => Some(template.replace("{token_id}", &token_id)),
+            None => collection_info.image,
+        };
+
+        let mut attributes: Vec<MetadataAttribute> = character
+            .traits
+            .into_iter()
+            .map(|(slot, value)| MetadataAttribute {
+                trait_type: slot,
+                value,
+            })
+            .collect();
+        if let Some(rarity) = character.rarity {
+            attributes.push(MetadataAttribute {
+                trait_type: "rarity".to_string(),
+                value: rarity,
+            });
+        }
+        attributes.push(MetadataAttribute {
+            trait_type: "locked".to_string(),
+            value: character.locked.to_string(),
+        });
+
+        Ok(TokenMetadataResponse {
+            name: character
+                .name
+                .unwrap_or_else(|| format!("{} #{}", contract_info.name, token_id)),
+            description: collection_info.description,
+            image,
+            attributes,
+        })
+    }
+}
@@ -1,3 +1,4 @@
+use cosmwasm_schema::cw_serde;
 use cw721_base::msg::{CollectionInfo, RoyaltyInfo};
 use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
@@ -7,6 +8,8 @@ use std::marker::PhantomData;
 use cosmwasm_std::{Addr, BlockInfo, CustomMsg, StdResult, Storage};
 
 use cw721::{ContractInfoResponse, Expiration};
+
+use crate::msg::Metadata;
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
 pub struct Cw721Contract<'a, T, C, E, Q>
@@ -22,6 +25,10 @@ where
     /// Stored as (granter, operator) giving operator full control over granter's account
     pub operators: Map<'a, (&'a Addr, &'a Addr), Expiration>,
     pub tokens: IndexedMap<'a, &'a str, TokenInfo<T>, TokenIndexes<'a, T>>,
+    /// Set by the minter to stop transfers and sends in an emergency
+    pub transfers_paused: Item<'a, bool>,
+    /// Url of the token images with a `{token_id}` placeholder
+    pub image_template: Item<'a, String>,
 
     pub(crate) _custom_response: PhantomData<C>,
     pub(crate) _custom_query: PhantomData<Q>,
@@ -43,6 +50,8 @@ where
             "operators",
             "tokens",
             "tokens__owner",
+            "transfers_paused",
+            "image_template",
         )
     }
 }
@@ -53,6 +62,7 @@ where
     E: CustomMsg,
     Q: CustomMsg,
 {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         contract_key: &'a str,
         token_count_key: &'a str,
@@ -61,6 +71,8 @@ where
         operator_key: &'a str,
         tokens_key: &'a str,
         tokens_owner_key: &'a str,
+        transfers_paused_key: &'a str,
+        image_template_key: &'a str,
     ) -> Self {
         let indexes = TokenIndexes {
             owner: MultiIndex::new(token_owner_idx, tokens_key, tokens_owner_key),
@@ -72,6 +84,8 @@ where
             frozen_collection_info: Item::new(frozen_key),
             operators: Map::new(operator_key),
             tokens: IndexedMap::new(tokens_key, indexes),
+            transfers_paused: Item::new(transfers_paused_key),
+            image_template: Item::new(image_template_key),
             _custom_response: PhantomData,
             _custom_execute: PhantomData,
             _custom_query: PhantomData,
@@ -88,6 +102,10 @@ where
         Ok(val)
     }
 
+    pub fn transfers_paused(&self, storage: &dyn Storage) -> StdResult<bool> {
+        Ok(self.transfers_paused.may_load(storage)?.unwrap_or_default())
+    }
+
     pub fn decrement_tokens(&self, storage: &mut dyn Storage) -> StdResult<u64> {
         let val = self.token_count(storage)? - 1;
         self.token_count.save(storage, &val)?;
@@ -142,6 +160,52 @@ where
     }
 }
 
+/// Character metadata stored up to 0.2.0, with one field per trait slot
+#[cw_serde]
+pub struct LegacyMetadata {
+    pub name: Option<String>,
+    pub ears: Option<String>,
+    pub eyes: Option<String>,
+    pub mouth: Option<String>,
+    pub fur_type: Option<String>,
+    pub fur_color: Option<String>,
+    pub tail_shape: Option<String>,
+    pub rarity: Option<String>,
+    pub traits_equipped: Option<Vec<String>>,
+    pub locked: bool,
+}
+
+impl From<LegacyMetadata> for Metadata {
+    fn from(legacy: LegacyMetadata) -> Self {
+        let slots = [
+            ("ears", legacy.ears),
+            ("eyes", legacy.eyes),
+            ("mouth", legacy.mouth),
+            ("fur_type", legacy.fur_type),
+            ("fur_color", legacy.fur_color),
+            ("tail_shape", legacy.tail_shape),
+        ];
+        Metadata {
+            name: legacy.name,
+            traits: slots
+                .into_iter()
+                .filter_map(|(slot, value)| value.map(|value| (slot.to_string(), value)))
+                .collect(),
+            rarity: legacy.rarity,
+            traits_equipped: legacy.traits_equipped,
+            locked: legacy.locked,
+        }
+    }
+}
+
+/// Token metadata in either layout, read while the tokens are migrated
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum StoredMetadata {
+    Current(Metadata),
+    Legacy(LegacyMetadata),
+}
+
 pub fn token_owner_idx<T>(_pk: &[u8], d: &TokenInfo<T>) -> Addr {
     d.owner.clone()
 }
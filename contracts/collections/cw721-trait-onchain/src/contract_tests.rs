@@ -1,16 +1,23 @@
 #![cfg(test)]
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
 
-use cosmwasm_std::{from_binary, Addr, DepsMut, Empty};
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, ContractResult, Deps, DepsMut, Empty, SystemError, SystemResult,
+    WasmQuery,
+};
 
 use cw721::{ContractInfoResponse, Cw721Query, NftInfoResponse, OwnerOfResponse};
 use cw721_base::msg::{
     CollectionInfo, CollectionInfoResponse, RoyaltyInfoResponse, UpdateCollectionInfoMsg,
 };
 use cw_ownable::OwnershipError;
+use mintables::msg::QueryMsg as MintablesQueryMsg;
+use mintables::state::TraitValueInfo;
 
 use crate::error::ContractError;
-use crate::msg::Metadata;
+use crate::msg::{
+    EscrowResponse, Metadata, MetadataAttribute, TokenMetadataResponse, TraitRegistryResponse,
+};
 use crate::{
     Cw721Contract, Cw721TraitContract, ExecuteMsg, Extension, InstantiateMsg, MinterResponse,
     QueryMsg,
@@ -34,6 +41,8 @@ fn setup_contract(deps: DepsMut<'_>) -> Cw721Contract<'static, Extension, Empty,
             explicit_content: None,
             royalty_info: None,
         },
+        trait_registry: None,
+        image_template: None,
     };
     let info = mock_info("creator", &[]);
     let res = contract.instantiate(deps, mock_env(), info, msg).unwrap();
@@ -58,6 +67,8 @@ fn proper_instantiation() {
             explicit_content: None,
             royalty_info: None,
         },
+        trait_registry: None,
+        image_template: None,
     };
     let info = mock_info("creator", &[]);
 
@@ -406,6 +417,274 @@ fn burning_multiple() {
     assert!(tokens.tokens.is_empty());
 }
 
+#[test]
+fn escrowing() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let token_id = "1".to_string();
+    let owner = String::from("demeter");
+    let manager = String::from("manager");
+
+    let mint_msg = ExecuteMsg::Mint {
+        token_id: token_id.clone(),
+        owner: owner.clone(),
+        token_uri: None,
+        extension: Metadata {
+            trait_type: String::from("hair"),
+            trait_value: String::from("red"),
+            trait_rarity: String::from("common"),
+        },
+    };
+
+    let minter = mock_info(MINTER, &[]);
+    contract
+        .execute(deps.as_mut(), mock_env(), minter, mint_msg)
+        .unwrap();
+
+    let escrow_msg = ExecuteMsg::Escrow {
+        token_ids: vec![token_id.clone()],
+    };
+
+    // only the escrow agents set by the minter can escrow, even with an approval
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&manager, &[]),
+            escrow_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotEscrowAgent {});
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            ExecuteMsg::UpdateEscrowAgents {
+                add: vec![owner.clone()],
+                remove: vec![],
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            ExecuteMsg::UpdateEscrowAgents {
+                add: vec![manager.clone()],
+                remove: vec![],
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        contract.escrow_agents(deps.as_ref()).unwrap(),
+        vec![manager.clone()]
+    );
+
+    // manager cannot escrow without approval
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&manager, &[]),
+            escrow_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Ownership(OwnershipError::NotOwner));
+
+    // owner approves the manager for all tokens
+    let approve_all_msg = ExecuteMsg::ApproveAll {
+        operator: manager.clone(),
+        expires: None,
+    };
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            approve_all_msg,
+        )
+        .unwrap();
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&manager, &[]),
+            escrow_msg.clone(),
+        )
+        .unwrap();
+
+    let res: EscrowResponse = from_binary(
+        &contract
+            .query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Escrow {
+                    token_id: token_id.clone(),
+                },
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.holder, Some(manager.clone()));
+
+    // cannot escrow the same token twice
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            escrow_msg,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::TraitInEscrow {});
+
+    // owner cannot burn an escrowed token
+    let burn_msg = ExecuteMsg::Burn {
+        token_id: token_id.clone(),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            burn_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::TraitInEscrow {});
+
+    // nor transfer it or approve someone else to take it
+    let err = contract
+        ._transfer_nft(
+            deps.as_mut(),
+            &mock_env(),
+            &mock_info(&owner, &[]),
+            "buyer",
+            &token_id,
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::TraitInEscrow {});
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            ExecuteMsg::Approve {
+                spender: "buyer".into(),
+                token_id: token_id.clone(),
+                expires: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::TraitInEscrow {});
+
+    // only the holder can release the token
+    let release_msg = ExecuteMsg::ReleaseEscrow {
+        token_ids: vec![token_id.clone()],
+        recipient: None,
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            release_msg.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::NotEscrowHolder {});
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&manager, &[]),
+            release_msg,
+        )
+        .unwrap();
+
+    let res = contract
+        .escrow_holder(deps.as_ref(), token_id.clone())
+        .unwrap();
+    assert_eq!(res.holder, None);
+
+    // the holder can give the token to someone else when releasing it
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&manager, &[]),
+            ExecuteMsg::Escrow {
+                token_ids: vec![token_id.clone()],
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&manager, &[]),
+            ExecuteMsg::ReleaseEscrow {
+                token_ids: vec![token_id.clone()],
+                recipient: Some("buyer".to_string()),
+            },
+        )
+        .unwrap();
+    let res = contract
+        .owner_of(deps.as_ref(), mock_env(), token_id.clone(), false)
+        .unwrap();
+    assert_eq!(res.owner, "buyer");
+    contract
+        ._transfer_nft(
+            deps.as_mut(),
+            &mock_env(),
+            &mock_info("buyer", &[]),
+            &owner,
+            &token_id,
+        )
+        .unwrap();
+
+    // escrow again and the holder can burn even after the approval is revoked
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&manager, &[]),
+            ExecuteMsg::Escrow {
+                token_ids: vec![token_id.clone()],
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            ExecuteMsg::RevokeAll {
+                operator: manager.clone(),
+            },
+        )
+        .unwrap();
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&manager, &[]),
+            ExecuteMsg::BurnMultiple {
+                token_ids: vec![token_id.clone()],
+            },
+        )
+        .unwrap();
+
+    let count = contract.num_tokens(deps.as_ref()).unwrap();
+    assert_eq!(0, count.count);
+    let res = contract.escrow_holder(deps.as_ref(), token_id).unwrap();
+    assert_eq!(res.holder, None);
+}
+
 #[test]
 fn query_tokens_by_owner() {
     let mut deps = mock_dependencies();
@@ -619,3 +898,200 @@ fn freeze_collection() {
 
     assert_eq!(err, ContractError::CollectionInfoFrozen {})
 }
+
+#[test]
+fn minting_checks_trait_registry() {
+    let mut deps = mock_dependencies();
+    let contract = Cw721TraitContract::default();
+
+    let msg = InstantiateMsg {
+        name: CONTRACT_NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        minter: String::from(MINTER),
+        collection_info: CollectionInfo {
+            creator: "creator".into(),
+            description: "description".into(),
+            image: Some("https://example.com/image.png".into()),
+            external_link: None,
+            explicit_content: None,
+            royalty_info: None,
+        },
+        trait_registry: Some("mintables".into()),
+        image_template: None,
+    };
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg)
+        .unwrap();
+
+    let registry: TraitRegistryResponse = from_binary(
+        &contract
+            .query(deps.as_ref(), mock_env(), QueryMsg::TraitRegistry {})
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(registry.registry, Some("mintables".to_string()));
+
+    //Only ears/pointy is registered in the mocked mintables contract
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { msg, .. } => match from_binary(msg).unwrap() {
+            MintablesQueryMsg::TraitValue {
+                trait_type,
+                trait_value,
+            } if trait_type == "ears" && trait_value == "pointy" => {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&TraitValueInfo {
+                        trait_type,
+                        trait_value,
+                        display_name: "Pointy".into(),
+                        image_key: "ears/pointy".into(),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => SystemResult::Ok(ContractResult::Err("not found".into())),
+        },
+        _ => SystemResult::Err(SystemError::Unknown {}),
+    });
+
+    let mint_msg = |trait_value: &str| ExecuteMsg::Mint {
+        token_id: trait_value.to_string(),
+        owner: "medusa".into(),
+        token_uri: None,
+        extension: Metadata {
+            trait_type: "ears".into(),
+            trait_value: trait_value.into(),
+            trait_rarity: "common".into(),
+        },
+    };
+
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            mint_msg("round"),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::UnregisteredTrait {});
+
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            mint_msg("pointy"),
+        )
+        .unwrap();
+    assert_eq!(contract.token_count(&deps.storage).unwrap(), 1);
+}
+
+#[test]
+fn token_metadata() {
+    let mut deps = mock_dependencies();
+    let contract = setup_contract(deps.as_mut());
+
+    let mint_msg = ExecuteMsg::Mint {
+        token_id: "1".to_string(),
+        owner: "medusa".into(),
+        token_uri: None,
+        extension: Metadata {
+            trait_type: "ears".into(),
+            trait_value: "pointy".into(),
+            trait_rarity: "common".into(),
+        },
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), mint_msg)
+        .unwrap();
+
+    let query = |deps: Deps| {
+        let msg = QueryMsg::TokenMetadata {
+            token_id: "1".to_string(),
+        };
+        //Contracts using this one as a library get the same answer as the entry point
+        let res = contract.query(deps, mock_env(), msg.clone()).unwrap();
+        assert_eq!(crate::entry::query(deps, mock_env(), msg).unwrap(), res);
+        from_binary::<TokenMetadataResponse>(&res).unwrap()
+    };
+
+    //Without registry nor template the trait value and the collection image are used
+    assert_eq!(
+        query(deps.as_ref()),
+        TokenMetadataResponse {
+            name: "pointy".to_string(),
+            description: "description".to_string(),
+            image: Some("https://example.com/image.png".to_string()),
+            attributes: vec![
+                MetadataAttribute {
+                    trait_type: "ears".to_string(),
+                    value: "pointy".to_string(),
+                },
+                MetadataAttribute {
+                    trait_type: "rarity".to_string(),
+                    value: "common".to_string(),
+                },
+                MetadataAttribute {
+                    trait_type: "equipped".to_string(),
+                    value: "false".to_string(),
+                },
+            ],
+        }
+    );
+
+    let set_template = ExecuteMsg::SetImageTemplate {
+        image_template: Some("https://img.example.com/{image_key}.png".to_string()),
+    };
+    let err = contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MINTER, &[]),
+            set_template.clone(),
+        )
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    contract
+        .execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("creator", &[]),
+            set_template,
+        )
+        .unwrap();
+
+    let res = query(deps.as_ref());
+    assert_eq!(
+        res.image,
+        Some("https://img.example.com/ears/pointy.png".to_string())
+    );
+
+    //Registered traits use the display name and image key of the registry
+    contract
+        .trait_registry
+        .save(deps.as_mut().storage, &Addr::unchecked("mintables"))
+        .unwrap();
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { msg, .. } => match from_binary(msg).unwrap() {
+            MintablesQueryMsg::TraitValue {
+                trait_type,
+                trait_value,
+            } => SystemResult::Ok(ContractResult::Ok(
+                to_binary(&TraitValueInfo {
+                    trait_type,
+                    trait_value,
+                    display_name: "Pointy".into(),
+                    image_key: "ears-pointy-v2".into(),
+                })
+                .unwrap(),
+            )),
+            _ => SystemResult::Ok(ContractResult::Err("not found".into())),
+        },
+        _ => SystemResult::Err(SystemError::Unknown {}),
+    });
+    let res = query(deps.as_ref());
+    assert_eq!(res.name, "Pointy");
+    assert_eq!(
+        res.image,
+        Some("https://img.example.com/ears-pointy-v2.png".to_string())
+    );
+}
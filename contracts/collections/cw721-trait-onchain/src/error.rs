@@ -23,9 +23,15 @@ pub enum ContractError {
     #[error("Approval not found for: {spender}")]
     ApprovalNotFound { spender: String },
 
-    #[error("found version ({0}) while attempting to migrate from 0.16.0")]
+    #[error("found version ({0}) which is newer than the code being migrated to")]
     WrongMigrateVersion(String),
 
+    #[error("found contract ({0}) while attempting to migrate")]
+    WrongMigrateContract(String),
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+
     #[error("Description of collection is too long")]
     DescriptionTooLong {},
 
@@ -40,4 +46,22 @@ pub enum ContractError {
 
     #[error("RoyaltyShareIncreased")]
     RoyaltyShareIncreased {},
+
+    #[error("Trait is held in escrow")]
+    TraitInEscrow {},
+
+    #[error("Trait is not held in escrow by the sender")]
+    NotEscrowHolder {},
+
+    #[error("Only the minter and the escrow agents can escrow traits")]
+    NotEscrowAgent {},
+
+    #[error("Trait type or value is not in the trait registry")]
+    UnregisteredTrait {},
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
 }
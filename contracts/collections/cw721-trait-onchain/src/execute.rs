@@ -4,6 +4,8 @@ use cw721_base::msg::{
     UpdateCollectionInfoMsg,
 };
 use cw_ownable::OwnershipError;
+use mintables::msg::QueryMsg as MintablesQueryMsg;
+use mintables::state::TraitValueInfo;
 
 use cw721::{ContractInfoResponse, Expiration};
 use url::Url;
@@ -65,6 +67,16 @@ impl Cw721TraitContract<'_> {
         self.collection_info.save(deps.storage, &collection_info)?;
         self.frozen_collection_info.save(deps.storage, &false)?;
 
+        if let Some(registry) = msg.trait_registry {
+            let registry = deps.api.addr_validate(&registry)?;
+            self.trait_registry.save(deps.storage, &registry)?;
+        }
+
+        if let Some(image_template) = msg.image_template {
+            validate_image_template(&image_template)?;
+            self.image_template.save(deps.storage, &image_template)?;
+        }
+
         Ok(Response::default()
             .add_attribute("action", "instantiate")
             .add_attribute("contract_name", CONTRACT_NAME)
@@ -103,10 +115,21 @@ impl Cw721TraitContract<'_> {
             ExecuteMsg::BurnMultiple { token_ids } => {
                 self.burn_multiple(deps, env, info, token_ids)
             }
+            ExecuteMsg::Escrow { token_ids } => self.escrow(deps, env, info, token_ids),
+            ExecuteMsg::ReleaseEscrow {
+                token_ids,
+                recipient,
+            } => self.release_escrow(deps, info, token_ids, recipient),
+            ExecuteMsg::UpdateEscrowAgents { add, remove } => {
+                self.update_escrow_agents(deps, info, add, remove)
+            }
             ExecuteMsg::UpdateCollectionInfo { collection_info } => {
                 self.update_collection_info(deps, env, info, collection_info)
             }
             ExecuteMsg::FreezeCollectionInfo {} => self.freeze_collection_info(deps, env, info),
+            ExecuteMsg::SetImageTemplate { image_template } => {
+                self.set_image_template(deps, info, image_template)
+            }
             ExecuteMsg::UpdateOwnership(action) => Self::update_ownership(deps, env, info, action),
             ExecuteMsg::Extension { msg: _ } => Ok(Response::default()),
         }
@@ -122,6 +145,7 @@ impl Cw721TraitContract<'_> {
         extension: Metadata,
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
+        self.assert_registered(deps.as_ref(), &extension)?;
 
         // create the token
         let token = TokenInfo {
@@ -145,6 +169,26 @@ impl Cw721TraitContract<'_> {
             .add_attribute("token_id", token_id))
     }
 
+    //Checks the trait against the mintables registry, skipped when no registry is configured
+    fn assert_registered(&self, deps: Deps, trait_info: &Metadata) -> Result<(), ContractError> {
+        let registry = match self.trait_registry.may_load(deps.storage)? {
+            Some(registry) => registry,
+            None => return Ok(()),
+        };
+
+        deps.querier
+            .query_wasm_smart::<TraitValueInfo>(
+                registry,
+                &MintablesQueryMsg::TraitValue {
+                    trait_type: trait_info.trait_type.clone(),
+                    trait_value: trait_info.trait_value.clone(),
+                },
+            )
+            .map_err(|_| ContractError::UnregisteredTrait {})?;
+
+        Ok(())
+    }
+
     pub fn update_ownership(
         deps: DepsMut,
         env: Env,
@@ -249,6 +293,30 @@ impl Cw721TraitContract<'_> {
         Ok(Response::new().add_event(event))
     }
 
+    fn set_image_template(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        image_template: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let collection = self.query_collection_info(deps.as_ref())?;
+        if collection.creator != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        match &image_template {
+            Some(image_template) => {
+                validate_image_template(image_template)?;
+                self.image_template.save(deps.storage, image_template)?;
+            }
+            None => self.image_template.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_image_template")
+            .add_attribute("image_template", image_template.unwrap_or_default()))
+    }
+
     fn query_collection_info(&self, deps: Deps) -> StdResult<CollectionInfoResponse> {
         let info = self.collection_info.load(deps.storage)?;
 
@@ -398,9 +466,10 @@ impl Cw721TraitContract<'_> {
         token_id: String,
     ) -> Result<Response, ContractError> {
         let token = self.tokens.load(deps.storage, &token_id)?;
-        self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        self.check_can_burn(deps.as_ref(), &env, &info, &token_id, &token)?;
 
         self.tokens.remove(deps.storage, &token_id)?;
+        self.escrow.remove(deps.storage, &token_id);
         self.decrement_tokens(deps.storage)?;
 
         Ok(Response::new()
@@ -417,10 +486,11 @@ impl Cw721TraitContract<'_> {
         token_ids: Vec<String>,
     ) -> Result<Response, ContractError> {
         for token_id in token_ids.iter() {
-            let token = self.tokens.load(deps.storage, &token_id)?;
-            self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+            let token = self.tokens.load(deps.storage, token_id)?;
+            self.check_can_burn(deps.as_ref(), &env, &info, token_id, &token)?;
 
-            self.tokens.remove(deps.storage, &token_id)?;
+            self.tokens.remove(deps.storage, token_id)?;
+            self.escrow.remove(deps.storage, token_id);
             self.decrement_tokens(deps.storage)?;
         }
 
@@ -430,6 +500,103 @@ impl Cw721TraitContract<'_> {
             .add_attribute("tokens", token_ids.join(",")))
     }
 
+    fn escrow(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_ids: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        //Escrow holders can burn the tokens, so only contracts trusted by the minter can hold them
+        let agents = self
+            .escrow_agents
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        if !agents.contains(&info.sender) {
+            cw_ownable::assert_owner(deps.storage, &info.sender)
+                .map_err(|_| ContractError::NotEscrowAgent {})?;
+        }
+
+        for token_id in token_ids.iter() {
+            let token = self.tokens.load(deps.storage, token_id)?;
+            //A token can only be held by one escrow at a time
+            if self.escrow.has(deps.storage, token_id) {
+                return Err(ContractError::TraitInEscrow {});
+            }
+            self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+
+            self.escrow.save(deps.storage, token_id, &info.sender)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "escrow")
+            .add_attribute("holder", info.sender)
+            .add_attribute("tokens", token_ids.join(",")))
+    }
+
+    fn release_escrow(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        token_ids: Vec<String>,
+        recipient: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let recipient = recipient
+            .map(|recipient| deps.api.addr_validate(&recipient))
+            .transpose()?;
+
+        for token_id in token_ids.iter() {
+            let holder = self.escrow.may_load(deps.storage, token_id)?;
+            if holder.as_ref() != Some(&info.sender) {
+                return Err(ContractError::NotEscrowHolder {});
+            }
+
+            self.escrow.remove(deps.storage, token_id);
+
+            //The holder decides who the token goes to, e.g. the current owner of the character it was equipped on
+            if let Some(recipient) = &recipient {
+                let mut token = self.tokens.load(deps.storage, token_id)?;
+                token.owner = recipient.clone();
+                token.approvals = vec![];
+                self.tokens.save(deps.storage, token_id, &token)?;
+            }
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "release_escrow")
+            .add_attribute("holder", info.sender)
+            .add_attribute(
+                "recipient",
+                recipient.map(|r| r.into_string()).unwrap_or_default(),
+            )
+            .add_attribute("tokens", token_ids.join(",")))
+    }
+
+    fn update_escrow_agents(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        let mut agents = self
+            .escrow_agents
+            .may_load(deps.storage)?
+            .unwrap_or_default();
+        agents.retain(|agent| !remove.contains(&agent.to_string()));
+        for agent in add {
+            let agent = deps.api.addr_validate(&agent)?;
+            if !agents.contains(&agent) {
+                agents.push(agent);
+            }
+        }
+        self.escrow_agents.save(deps.storage, &agents)?;
+
+        Ok(Response::new().add_attribute("action", "update_escrow_agents"))
+    }
+
     // helpers
     pub fn _transfer_nft(
         &self,
@@ -440,6 +607,10 @@ impl Cw721TraitContract<'_> {
         token_id: &str,
     ) -> Result<TokenInfo<Metadata>, ContractError> {
         let mut token = self.tokens.load(deps.storage, token_id)?;
+        //An equipped trait stays with its owner until the escrow holder releases it
+        if self.escrow.has(deps.storage, token_id) {
+            return Err(ContractError::TraitInEscrow {});
+        }
         // ensure we have permissions
         self.check_can_send(deps.as_ref(), env, info, &token)?;
         // set owner and remove existing approvals
@@ -471,6 +642,10 @@ impl Cw721TraitContract<'_> {
 
         // only difference between approve and revoke
         if add {
+            //No new spenders for an escrowed trait, revoking is still allowed
+            if self.escrow.has(deps.storage, token_id) {
+                return Err(ContractError::TraitInEscrow {});
+            }
             // reject expired data as invalid
             let expires = expires.unwrap_or_default();
             if expires.is_expired(&env.block) {
@@ -516,6 +691,22 @@ impl Cw721TraitContract<'_> {
         }
     }
 
+    /// returns true iff the sender can burn the token, escrowed tokens can only be burned by their escrow holder
+    pub fn check_can_burn(
+        &self,
+        deps: Deps,
+        env: &Env,
+        info: &MessageInfo,
+        token_id: &str,
+        token: &TokenInfo<Metadata>,
+    ) -> Result<(), ContractError> {
+        match self.escrow.may_load(deps.storage, token_id)? {
+            Some(holder) if holder == info.sender => Ok(()),
+            Some(_) => Err(ContractError::TraitInEscrow {}),
+            None => self.check_can_send(deps, env, info, token),
+        }
+    }
+
     /// returns true iff the sender can transfer ownership of the token
     pub fn check_can_send(
         &self,
@@ -562,3 +753,14 @@ pub fn share_validate(share: Decimal) -> Result<Decimal, ContractError> {
 
     Ok(share)
 }
+
+//The template must be a valid url once its placeholders are filled
+fn validate_image_template(image_template: &str) -> Result<(), ContractError> {
+    let sample = image_template
+        .replace("{token_id}", "1")
+        .replace("{trait_type}", "type")
+        .replace("{trait_value}", "value")
+        .replace("{image_key}", "type/value");
+    Url::parse(&sample)?;
+    Ok(())
+}
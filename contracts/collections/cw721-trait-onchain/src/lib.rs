@@ -8,7 +8,8 @@ pub mod state;
 #[cfg(test)]
 mod contract_tests;
 
-pub use crate::msg::{ExecuteMsg, InstantiateMsg, MinterResponse, QueryMsg};
+pub use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, MinterResponse, QueryMsg};
+pub use crate::query::TokenMetadataQuery;
 pub use crate::state::Cw721Contract;
 
 // These types are re-exported so that contracts interacting with this
@@ -34,7 +35,8 @@ pub mod entry {
     
     #[cfg(not(feature = "library"))]
     use cosmwasm_std::entry_point;
-    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+    use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+    use semver::Version;
 
     // This makes a conscious choice on the various generics used by the contract
     #[cfg_attr(not(feature = "library"), entry_point)]
@@ -66,6 +68,36 @@ pub mod entry {
         let tract = Cw721TraitContract::default();
         tract.query(deps, env, msg)
     }
+
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+        let previous = ensure_from_older_version(deps.storage)?;
+        //The escrow map added after 0.1.0 starts empty, so no state needs to be moved
+        if let Some(registry) = msg.trait_registry {
+            let registry = deps.api.addr_validate(&registry)?;
+            Cw721TraitContract::default()
+                .trait_registry
+                .save(deps.storage, &registry)?;
+        }
+        cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "migrate")
+            .add_attribute("from_version", previous.to_string())
+            .add_attribute("to_version", CONTRACT_VERSION))
+    }
+
+    fn ensure_from_older_version(storage: &dyn Storage) -> Result<Version, ContractError> {
+        let stored = cw2::get_contract_version(storage)?;
+        if stored.contract != CONTRACT_NAME {
+            return Err(ContractError::WrongMigrateContract(stored.contract));
+        }
+        let previous: Version = stored.version.parse()?;
+        if previous > CONTRACT_VERSION.parse()? {
+            return Err(ContractError::WrongMigrateVersion(stored.version));
+        }
+        Ok(previous)
+    }
 }
 
 #[cfg(test)]
@@ -74,6 +106,8 @@ mod tests {
     use cw2::ContractVersion;
     use cw721_base::msg::CollectionInfo;
 
+    use crate::error::ContractError;
+
     use super::*;
 
     /// Make sure cw2 version info is properly initialized during instantiation.
@@ -97,6 +131,8 @@ mod tests {
                     explicit_content: None,
                     royalty_info: None,
                 },
+                trait_registry: None,
+                image_template: None,
             },
         )
         .unwrap();
@@ -110,4 +146,52 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn migrate_from_older_version() {
+        let mut deps = mock_dependencies();
+
+        //Layout stored by 0.1.0
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        entry::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                trait_registry: Some("mintables".into()),
+            },
+        )
+        .unwrap();
+        let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+        let registry = Cw721TraitContract::default()
+            .trait_registry(deps.as_ref())
+            .unwrap();
+        assert_eq!(registry.registry, Some("mintables".to_string()));
+
+        //Downgrades are refused
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "9.0.0").unwrap();
+        let err = entry::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                trait_registry: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::WrongMigrateVersion("9.0.0".into()));
+
+        cw2::set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "0.1.0").unwrap();
+        let err = entry::migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                trait_registry: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WrongMigrateContract("crates.io:cw20-base".into())
+        );
+    }
 }
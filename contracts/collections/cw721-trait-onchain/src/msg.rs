@@ -17,6 +17,21 @@ pub struct InstantiateMsg {
 
     //Info of collection to be kept on chain
     pub collection_info: CollectionInfo<RoyaltyInfoResponse>,
+
+    //Mintables contract holding the trait registry, mints are validated against it when set
+    #[serde(default)]
+    pub trait_registry: Option<String>,
+
+    //Url of the trait images, `{token_id}`, `{trait_type}`, `{trait_value}` and `{image_key}` are replaced for each trait
+    #[serde(default)]
+    pub image_template: Option<String>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    //Sets the trait registry on collections instantiated before it existed
+    #[serde(default)]
+    pub trait_registry: Option<String>,
 }
 
 #[cw_serde]
@@ -80,6 +95,26 @@ pub enum ExecuteMsg<T, E> {
         token_ids: Vec<String>,
     },
 
+    /// Hold NFTs the sender has access to in escrow for the sender (e.g. traits equipped on a character).
+    /// Only the minter and the escrow agents can escrow. Escrowed NFTs cannot be escrowed again and
+    /// can only be burned by the escrow holder
+    Escrow {
+        token_ids: Vec<String>,
+    },
+
+    /// Release NFTs from escrow, can only be called by the escrow holder.
+    /// With a recipient the NFTs are given to it, otherwise they stay with their owner
+    ReleaseEscrow {
+        token_ids: Vec<String>,
+        recipient: Option<String>,
+    },
+
+    /// Add and remove the contracts allowed to escrow NFTs, only the minter can do it
+    UpdateEscrowAgents {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+
     // Update collection information
     UpdateCollectionInfo {
         collection_info: UpdateCollectionInfoMsg<RoyaltyInfoResponse>,
@@ -88,6 +123,11 @@ pub enum ExecuteMsg<T, E> {
     // Freeze collection information
     FreezeCollectionInfo {},
 
+    //Set or remove the image url template, only the collection creator can do it
+    SetImageTemplate {
+        image_template: Option<String>,
+    },
+
     /// Extension msg
     Extension {
         msg: E,
@@ -181,6 +221,22 @@ pub enum QueryMsg<Q: JsonSchema> {
     #[returns(CollectionInfoResponse)]
     CollectionInfo {},
 
+    /// Return the address holding the token in escrow, if any
+    #[returns(EscrowResponse)]
+    Escrow { token_id: String },
+
+    /// Return the contracts allowed to escrow NFTs besides the minter
+    #[returns(Vec<String>)]
+    EscrowAgents {},
+
+    /// Return the mintables contract used as trait registry, if any
+    #[returns(TraitRegistryResponse)]
+    TraitRegistry {},
+
+    /// Return the metadata of a trait following the ERC721 Metadata JSON Schema
+    #[returns(TokenMetadataResponse)]
+    TokenMetadata { token_id: String },
+
     /// Extension query
     #[returns(())]
     Extension { msg: Q },
@@ -201,3 +257,28 @@ pub struct CollectionInfoResponse {
     pub explicit_content: Option<bool>,
     pub royalty_info: Option<RoyaltyInfoResponse>,
 }
+
+#[cw_serde]
+pub struct EscrowResponse {
+    pub holder: Option<String>,
+}
+
+#[cw_serde]
+pub struct TraitRegistryResponse {
+    pub registry: Option<String>,
+}
+
+/// Token metadata in the shape of the ERC721 Metadata JSON Schema used by marketplaces and wallets
+#[cw_serde]
+pub struct TokenMetadataResponse {
+    pub name: String,
+    pub description: String,
+    pub image: Option<String>,
+    pub attributes: Vec<MetadataAttribute>,
+}
+
+#[cw_serde]
+pub struct MetadataAttribute {
+    pub trait_type: String,
+    pub value: String,
+}
@@ -13,8 +13,13 @@ use cw721::{
 };
 use cw_storage_plus::Bound;
 use cw_utils::maybe_addr;
+use mintables::msg::QueryMsg as MintablesQueryMsg;
+use mintables::state::TraitValueInfo;
 
-use crate::msg::{MinterResponse, QueryMsg};
+use crate::msg::{
+    EscrowResponse, Extension, MetadataAttribute, MinterResponse, QueryMsg, TokenMetadataResponse,
+    TraitRegistryResponse,
+};
 use crate::state::{Approval, Cw721Contract, TokenInfo};
 
 const DEFAULT_LIMIT: u32 = 10;
@@ -249,7 +254,10 @@ where
     E: CustomMsg,
     Q: CustomMsg,
 {
-    pub fn query(&self, deps: Deps, env: Env, msg: QueryMsg<Q>) -> StdResult<Binary> {
+    pub fn query(&self, deps: Deps, env: Env, msg: QueryMsg<Q>) -> StdResult<Binary>
+    where
+        Self: TokenMetadataQuery,
+    {
         match msg {
             QueryMsg::Minter {} => to_binary(&self.minter(deps)?),
             QueryMsg::ContractInfo {} => to_binary(&self.contract_info(deps)?),
@@ -321,6 +329,12 @@ where
             }
             QueryMsg::Ownership {} => to_binary(&Self::ownership(deps)?),
             QueryMsg::CollectionInfo {} => to_binary(&self.collection_info(deps)?),
+            QueryMsg::Escrow { token_id } => to_binary(&self.escrow_holder(deps, token_id)?),
+            QueryMsg::EscrowAgents {} => to_binary(&self.escrow_agents(deps)?),
+            QueryMsg::TraitRegistry {} => to_binary(&self.trait_registry(deps)?),
+            QueryMsg::TokenMetadata { token_id } => {
+                to_binary(&self.token_metadata(deps, token_id)?)
+            }
             QueryMsg::Extension { msg: _ } => Ok(Binary::default()),
         }
     }
@@ -337,6 +351,34 @@ where
         cw_ownable::get_ownership(deps.storage)
     }
 
+    pub fn escrow_holder(&self, deps: Deps, token_id: String) -> StdResult<EscrowResponse> {
+        let holder = self
+            .escrow
+            .may_load(deps.storage, &token_id)?
+            .map(|a| a.into_string());
+
+        Ok(EscrowResponse { holder })
+    }
+
+    pub fn escrow_agents(&self, deps: Deps) -> StdResult<Vec<String>> {
+        Ok(self
+            .escrow_agents
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|a| a.into_string())
+            .collect())
+    }
+
+    pub fn trait_registry(&self, deps: Deps) -> StdResult<TraitRegistryResponse> {
+        let registry = self
+            .trait_registry
+            .may_load(deps.storage)?
+            .map(|a| a.into_string());
+
+        Ok(TraitRegistryResponse { registry })
+    }
+
     fn collection_info(&self, deps: Deps) -> StdResult<CollectionInfoResponse> {
         let info = self.collection_info.load(deps.storage)?;
 
@@ -384,3 +426,75 @@ fn humanize_approval(approval: &Approval) -> cw721::Approval {
         expires: approval.expires,
     }
 }
+
+/// Describes tokens following the ERC721 Metadata JSON Schema, for the contracts whose extension it knows
+pub trait TokenMetadataQuery {
+    fn token_metadata(&self, deps: Deps, token_id: String) -> StdResult<TokenMetadataResponse>;
+}
+
+impl<'a, C, E, Q> TokenMetadataQuery for Cw721Contract<'a, Extension, C, E, Q>
+where
+    C: CustomMsg,
+    E: CustomMsg,
+    Q: CustomMsg,
+{
+    //Name and image key come from the trait registry when the trait is listed there
+    fn token_metadata(&self, deps: Deps, token_id: String) -> StdResult<TokenMetadataResponse> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        let collection_info = self.collection_info.load(deps.storage)?;
+        let trait_info = token.extension;
+
+        let registered = match self.trait_registry.may_load(deps.storage)? {
+            Some(registry) => deps
+                .querier
+                .query_wasm_smart::<TraitValueInfo>(
+                    registry,
+                    &MintablesQueryMsg::TraitValue {
+                        trait_type: trait_info.trait_type.clone(),
+                        trait_value: trait_info.trait_value.clone(),
+                    },
+                )
+                .ok(),
+            None => None,
+        };
+        let (name, image_key) = match registered {
+            Some(registered) => (registered.display_name, registered.image_key),
+            None => (
+                trait_info.trait_value.clone(),
+                format!("{}/{}", trait_info.trait_type, trait_info.trait_value),
+            ),
+        };
+
+        let image = match self.image_template.may_load(deps.storage)? {
+            Some(template) => Some(
+                template
+                    .replace("{token_id}", &token_id)
+                    .replace("{trait_type}", &trait_info.trait_type)
+                    .replace("{trait_value}", &trait_info.trait_value)
+                    .replace("{image_key}", &image_key),
+            ),
+            None => collection_info.image,
+        };
+        let equipped = self.escrow.has(deps.storage, &token_id);
+
+        Ok(TokenMetadataResponse {
+            name,
+            description: collection_info.description,
+            image,
+            attributes: vec![
+                MetadataAttribute {
+                    trait_type: trait_info.trait_type,
+                    value: trait_info.trait_value,
+                },
+                MetadataAttribute {
+                    trait_type: "rarity".to_string(),
+                    value: trait_info.trait_rarity,
+                },
+                MetadataAttribute {
+                    trait_type: "equipped".to_string(),
+                    value: equipped.to_string(),
+                },
+            ],
+        })
+    }
+}
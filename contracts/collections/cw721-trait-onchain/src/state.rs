@@ -22,6 +22,14 @@ where
     /// Stored as (granter, operator) giving operator full control over granter's account
     pub operators: Map<'a, (&'a Addr, &'a Addr), Expiration>,
     pub tokens: IndexedMap<'a, &'a str, TokenInfo<T>, TokenIndexes<'a, T>>,
+    /// Stored as token_id -> holder, the address that keeps the token in escrow (e.g. equipped on a character)
+    pub escrow: Map<'a, &'a str, Addr>,
+    /// Contracts allowed to escrow tokens besides the minter, set by the minter
+    pub escrow_agents: Item<'a, Vec<Addr>>,
+    /// Mintables contract whose trait registry every minted trait must be listed in
+    pub trait_registry: Item<'a, Addr>,
+    /// Url of the token images with placeholders for the token id and the trait
+    pub image_template: Item<'a, String>,
 
     pub(crate) _custom_response: PhantomData<C>,
     pub(crate) _custom_query: PhantomData<Q>,
@@ -43,6 +51,10 @@ where
             "operators",
             "tokens",
             "tokens__owner",
+            "escrow",
+            "escrow_agents",
+            "trait_registry",
+            "image_template",
         )
     }
 }
@@ -53,6 +65,7 @@ where
     E: CustomMsg,
     Q: CustomMsg,
 {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         contract_key: &'a str,
         token_count_key: &'a str,
@@ -61,6 +74,10 @@ where
         operator_key: &'a str,
         tokens_key: &'a str,
         tokens_owner_key: &'a str,
+        escrow_key: &'a str,
+        escrow_agents_key: &'a str,
+        trait_registry_key: &'a str,
+        image_template_key: &'a str,
     ) -> Self {
         let indexes = TokenIndexes {
             owner: MultiIndex::new(token_owner_idx, tokens_key, tokens_owner_key),
@@ -72,6 +89,10 @@ where
             frozen_collection_info: Item::new(frozen_key),
             operators: Map::new(operator_key),
             tokens: IndexedMap::new(tokens_key, indexes),
+            escrow: Map::new(escrow_key),
+            escrow_agents: Item::new(escrow_agents_key),
+            trait_registry: Item::new(trait_registry_key),
+            image_template: Item::new(image_template_key),
             _custom_response: PhantomData,
             _custom_execute: PhantomData,
             _custom_query: PhantomData,
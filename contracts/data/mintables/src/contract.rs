@@ -1,44 +1,394 @@
-use cosmwasm_std::{DepsMut, MessageInfo, Response, StdResult};
-use cw2::set_contract_version;
+use cosmwasm_std::{DepsMut, MessageInfo, Order, Response, StdResult, Storage};
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::{Item, Map};
+use semver::Version;
+use serde::{de::DeserializeOwned, Serialize};
 
+use crate::error::ContractError;
 use crate::state::{
-    CHARACTERS, CHARACTER_BUNDLES, CHARACTER_LOOTBOXES, TRAITS, TRAIT_BUNDLES, TRAIT_LOOTBOXES,
+    character_catalog, trait_catalog, Character, CharacterBundle, CharacterLootbox, Supply,
+    CHARACTER_BUNDLES, CHARACTER_LOOTBOXES, LEGACY_CHARACTERS, LEGACY_CHARACTER_BUNDLES,
+    LEGACY_CHARACTER_BUNDLE_CATALOG, LEGACY_CHARACTER_CATALOG, LEGACY_CHARACTER_LOOTBOXES,
+    LEGACY_CHARACTER_LOOTBOX_CATALOG, LEGACY_SLOTS, LEGACY_TRAITS, LEGACY_TRAIT_BUNDLES,
+    LEGACY_TRAIT_LOOTBOXES, MANAGERS, SLOTS, TRAIT_BUNDLES, TRAIT_LOOTBOXES, TRAIT_TYPES,
+    TRAIT_VALUES,
 };
+use crate::state::{TraitTypeInfo, TraitValueInfo};
 
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-pub fn instantiate(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+pub fn instantiate(
+    deps: DepsMut,
+    info: MessageInfo,
+    slots: Vec<String>,
+) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     cw_ownable::initialize_owner(
         deps.storage,
         deps.api,
         Some(&info.sender.clone().into_string()),
     )?;
-    TRAITS.save(deps.storage, &vec![])?;
-    CHARACTERS.save(deps.storage, &vec![])?;
-    TRAIT_BUNDLES.save(deps.storage, &vec![])?;
-    CHARACTER_BUNDLES.save(deps.storage, &vec![])?;
-    TRAIT_LOOTBOXES.save(deps.storage, &vec![])?;
-    CHARACTER_LOOTBOXES.save(deps.storage, &vec![])?;
+    MANAGERS.save(deps.storage, &vec![])?;
+    exec::validate_slots(&slots)?;
+    SLOTS.save(deps.storage, &slots)?;
 
     Ok(Response::new()
         .add_attribute("action", "Instantiating mintables contract")
         .add_attribute("owner", info.sender))
 }
 
+pub fn migrate(deps: DepsMut, new_managers: Vec<String>) -> Result<Response, ContractError> {
+    let previous = ensure_from_older_version(deps.storage)?;
+    if previous < Version::new(0, 2, 0) {
+        migrate_legacy_catalogs(deps.storage)?;
+    }
+    if previous < Version::new(0, 3, 0) {
+        migrate_character_slots(deps.storage)?;
+        seed_trait_registry(deps.storage)?;
+    }
+
+    //The managers are seeded here so they can record mints as soon as the migration is done
+    let mut managers = MANAGERS.may_load(deps.storage)?.unwrap_or_default();
+    for new_manager in new_managers {
+        let new_manager = deps.api.addr_validate(&new_manager)?;
+        if !managers.contains(&new_manager) {
+            managers.push(new_manager)
+        }
+    }
+    MANAGERS.save(deps.storage, &managers)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", previous.to_string())
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+//Only this contract can be migrated, and never to an older version
+fn ensure_from_older_version(storage: &dyn Storage) -> Result<Version, ContractError> {
+    let stored = get_contract_version(storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::WrongMigrateContract(stored.contract));
+    }
+    let previous: Version = stored.version.parse()?;
+    if previous > CONTRACT_VERSION.parse()? {
+        return Err(ContractError::WrongMigrateVersion(stored.version));
+    }
+    Ok(previous)
+}
+
+//0.1.0 stored each catalog as a single vector, they are moved to one entry per id
+fn migrate_legacy_catalogs(storage: &mut dyn Storage) -> StdResult<()> {
+    let traits = trait_catalog();
+    for t in take_legacy(storage, LEGACY_TRAITS)? {
+        traits.save(storage, t.id, &t)?;
+    }
+    //Characters keep their 0.1.0 layout here, they are converted and indexed with the slot migration
+    for c in take_legacy(storage, LEGACY_CHARACTERS)? {
+        LEGACY_CHARACTER_CATALOG.save(storage, c.id, &c)?;
+    }
+    migrate_catalog(storage, LEGACY_TRAIT_BUNDLES, TRAIT_BUNDLES)?;
+    migrate_catalog(
+        storage,
+        LEGACY_CHARACTER_BUNDLES,
+        LEGACY_CHARACTER_BUNDLE_CATALOG,
+    )?;
+    migrate_catalog(storage, LEGACY_TRAIT_LOOTBOXES, TRAIT_LOOTBOXES)?;
+    migrate_catalog(
+        storage,
+        LEGACY_CHARACTER_LOOTBOXES,
+        LEGACY_CHARACTER_LOOTBOX_CATALOG,
+    )?;
+
+    //Entries saved before the secondary indexes existed are saved again so they get indexed
+    let entries = traits
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (id, t) in entries {
+        traits.save(storage, id, &t)?;
+    }
+
+    Ok(())
+}
+
+//Up to 0.2.0 characters had one field per slot, they are moved into the slot list of the registry
+fn migrate_character_slots(storage: &mut dyn Storage) -> StdResult<()> {
+    if SLOTS.may_load(storage)?.unwrap_or_default().is_empty() {
+        let slots: Vec<String> = LEGACY_SLOTS.iter().map(|slot| slot.to_string()).collect();
+        SLOTS.save(storage, &slots)?;
+    }
+
+    let characters = character_catalog();
+    let entries = LEGACY_CHARACTER_CATALOG
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (id, c) in entries {
+        //The raw entry is removed first, the indexed map would try to clear indexes it never had
+        LEGACY_CHARACTER_CATALOG.remove(storage, id);
+        characters.save(storage, id, &Character::from(c))?;
+    }
+
+    let entries = LEGACY_CHARACTER_BUNDLE_CATALOG
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (id, bundle) in entries {
+        CHARACTER_BUNDLES.save(storage, id, &CharacterBundle::from(bundle))?;
+    }
+
+    let entries = LEGACY_CHARACTER_LOOTBOX_CATALOG
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (id, lootbox) in entries {
+        CHARACTER_LOOTBOXES.save(storage, id, &CharacterLootbox::from(lootbox))?;
+    }
+
+    Ok(())
+}
+
+//Catalog entries are only accepted with registered traits from 0.3.0, so the traits they already use are registered
+fn seed_trait_registry(storage: &mut dyn Storage) -> StdResult<()> {
+    let mut used: Vec<(String, String)> = vec![];
+    let traits = trait_catalog();
+    for entry in traits.range(storage, None, None, Order::Ascending) {
+        let (_, t) = entry?;
+        used.push((t.trait_type, t.trait_value));
+    }
+    for entry in TRAIT_BUNDLES.range(storage, None, None, Order::Ascending) {
+        let (_, bundle) = entry?;
+        used.extend(
+            bundle
+                .traits
+                .into_iter()
+                .map(|t| (t.trait_type, t.trait_value)),
+        );
+    }
+    for entry in TRAIT_LOOTBOXES.range(storage, None, None, Order::Ascending) {
+        let (_, lootbox) = entry?;
+        used.extend(
+            lootbox
+                .traits
+                .into_iter()
+                .map(|t| (t.trait_type, t.trait_value)),
+        );
+    }
+    let characters = character_catalog();
+    for entry in characters.range(storage, None, None, Order::Ascending) {
+        let (_, c) = entry?;
+        used.extend(c.traits);
+    }
+    for entry in CHARACTER_BUNDLES.range(storage, None, None, Order::Ascending) {
+        let (_, bundle) = entry?;
+        used.extend(bundle.characters.into_iter().flat_map(|c| c.traits));
+    }
+    for entry in CHARACTER_LOOTBOXES.range(storage, None, None, Order::Ascending) {
+        let (_, lootbox) = entry?;
+        used.extend(lootbox.characters.into_iter().flat_map(|c| c.traits));
+    }
+
+    //The names are used for display until the owner sets better ones
+    for (trait_type, trait_value) in used {
+        if !TRAIT_TYPES.has(storage, &trait_type) {
+            let type_info = TraitTypeInfo {
+                trait_type: trait_type.clone(),
+                display_name: trait_type.clone(),
+                image_key: trait_type.clone(),
+            };
+            TRAIT_TYPES.save(storage, &trait_type, &type_info)?;
+        }
+        if !TRAIT_VALUES.has(storage, (&trait_type, &trait_value)) {
+            let value_info = TraitValueInfo {
+                trait_type: trait_type.clone(),
+                trait_value: trait_value.clone(),
+                display_name: trait_value.clone(),
+                image_key: format!("{}/{}", trait_type, trait_value),
+            };
+            TRAIT_VALUES.save(storage, (&trait_type, &trait_value), &value_info)?;
+        }
+    }
+    Ok(())
+}
+
+fn migrate_catalog<T: Supply + Serialize + DeserializeOwned>(
+    storage: &mut dyn Storage,
+    legacy: Item<Vec<T>>,
+    store: Map<u32, T>,
+) -> StdResult<()> {
+    for item in take_legacy(storage, legacy)? {
+        store.save(storage, item.id(), &item)?;
+    }
+    Ok(())
+}
+
+fn take_legacy<T: Serialize + DeserializeOwned>(
+    storage: &mut dyn Storage,
+    legacy: Item<Vec<T>>,
+) -> StdResult<Vec<T>> {
+    let items = legacy.may_load(storage)?.unwrap_or_default();
+    legacy.remove(storage);
+    Ok(items)
+}
+
 pub mod exec {
-    use cosmwasm_std::{DepsMut, MessageInfo, Response};
+    use cosmwasm_std::{DepsMut, MessageInfo, Order, Response, StdResult, Storage};
 
     use crate::{
         error::ContractError,
+        msg::MintableKind,
         state::{
-            Character, CharacterBundle, CharacterLootbox, Trait, TraitBundle, TraitLootbox,
-            CHARACTERS, CHARACTER_BUNDLES, CHARACTER_LOOTBOXES, TRAITS, TRAIT_BUNDLES,
-            TRAIT_LOOTBOXES,
+            character_catalog, trait_catalog, Character, CharacterBundle, CharacterLootbox, Recipe,
+            RecipeOutput, SaleWindow, Supply, Trait, TraitBundle, TraitLootbox, TraitTypeInfo,
+            TraitValueInfo, CHARACTER_BUNDLES, CHARACTER_LOOTBOXES, MANAGERS, RECIPES, SLOTS,
+            TRAIT_BUNDLES, TRAIT_LOOTBOXES, TRAIT_TYPES, TRAIT_VALUES,
         },
     };
 
+    pub fn set_trait_types(
+        deps: DepsMut,
+        info: MessageInfo,
+        trait_types: Vec<TraitTypeInfo>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        for type_info in trait_types {
+            if type_info.trait_type.is_empty() {
+                return Err(ContractError::InvalidTraitRegistry {});
+            }
+            TRAIT_TYPES.save(deps.storage, &type_info.trait_type, &type_info)?;
+        }
+
+        Ok(Response::new().add_attribute("action", "set_trait_types"))
+    }
+
+    pub fn remove_trait_types(
+        deps: DepsMut,
+        info: MessageInfo,
+        trait_types: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        for trait_type in trait_types {
+            TRAIT_TYPES.remove(deps.storage, &trait_type);
+            let values = TRAIT_VALUES
+                .prefix(&trait_type)
+                .keys(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<String>>>()?;
+            for trait_value in values {
+                TRAIT_VALUES.remove(deps.storage, (&trait_type, &trait_value));
+            }
+        }
+
+        Ok(Response::new().add_attribute("action", "remove_trait_types"))
+    }
+
+    pub fn set_trait_values(
+        deps: DepsMut,
+        info: MessageInfo,
+        trait_values: Vec<TraitValueInfo>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        for value_info in trait_values {
+            if value_info.trait_value.is_empty() {
+                return Err(ContractError::InvalidTraitRegistry {});
+            }
+            if !TRAIT_TYPES.has(deps.storage, &value_info.trait_type) {
+                return Err(ContractError::UnregisteredTrait {});
+            }
+            TRAIT_VALUES.save(
+                deps.storage,
+                (&value_info.trait_type, &value_info.trait_value),
+                &value_info,
+            )?;
+        }
+
+        Ok(Response::new().add_attribute("action", "set_trait_values"))
+    }
+
+    pub fn remove_trait_values(
+        deps: DepsMut,
+        info: MessageInfo,
+        trait_values: Vec<(String, String)>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        for (trait_type, trait_value) in trait_values {
+            TRAIT_VALUES.remove(deps.storage, (&trait_type, &trait_value));
+        }
+
+        Ok(Response::new().add_attribute("action", "remove_trait_values"))
+    }
+
+    fn assert_registered(
+        storage: &dyn Storage,
+        trait_type: &str,
+        trait_value: &str,
+    ) -> Result<(), ContractError> {
+        if !TRAIT_VALUES.has(storage, (trait_type, trait_value)) {
+            return Err(ContractError::UnregisteredTrait {});
+        }
+        Ok(())
+    }
+
+    fn assert_registered_traits(
+        storage: &dyn Storage,
+        traits: &[Trait],
+    ) -> Result<(), ContractError> {
+        for t in traits {
+            assert_registered(storage, &t.trait_type, &t.trait_value)?;
+        }
+        Ok(())
+    }
+
+    //Characters already in the catalog keep the slots they have, removed slots just stop being accepted
+    pub fn set_slots(
+        deps: DepsMut,
+        info: MessageInfo,
+        slots: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        validate_slots(&slots)?;
+        SLOTS.save(deps.storage, &slots)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_slots")
+            .add_attribute("slots", slots.join(",")))
+    }
+
+    pub(crate) fn validate_slots(slots: &[String]) -> Result<(), ContractError> {
+        for (i, slot) in slots.iter().enumerate() {
+            if slot.is_empty() || slots[..i].contains(slot) {
+                return Err(ContractError::InvalidSlots {});
+            }
+        }
+        Ok(())
+    }
+
+    //Every slot of the character must be registered with a registered value, its traits are sorted in the registry order
+    fn order_traits(storage: &dyn Storage, character: &mut Character) -> Result<(), ContractError> {
+        let slots = SLOTS.may_load(storage)?.unwrap_or_default();
+
+        let mut positions = Vec::with_capacity(character.traits.len());
+        for (slot, value) in &character.traits {
+            assert_registered(storage, slot, value)?;
+            let position = slots
+                .iter()
+                .position(|registered| registered == slot)
+                .ok_or(ContractError::InvalidSlot {})?;
+            if positions.contains(&position) {
+                return Err(ContractError::InvalidSlot {});
+            }
+            positions.push(position);
+        }
+        character
+            .traits
+            .sort_by_key(|(slot, _)| slots.iter().position(|registered| registered == slot));
+
+        Ok(())
+    }
+
     pub fn add_traits(
         deps: DepsMut,
         info: MessageInfo,
@@ -46,17 +396,17 @@ pub mod exec {
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
 
-        let mut traits = TRAITS.load(deps.storage)?;
-
-        for new_trait in new_traits {
-            if traits.iter().any(|t| t.id == new_trait.id) {
+        let traits = trait_catalog();
+        for mut new_trait in new_traits {
+            new_trait.minted = 0;
+            validate_sale_window(&new_trait)?;
+            assert_registered(deps.storage, &new_trait.trait_type, &new_trait.trait_value)?;
+            if traits.has(deps.storage, new_trait.id) {
                 return Err(ContractError::IDExists {});
             }
-            traits.push(new_trait)
+            traits.save(deps.storage, new_trait.id, &new_trait)?;
         }
 
-        TRAITS.save(deps.storage, &traits)?;
-
         Ok(Response::new().add_attribute("action", "add_traits"))
     }
 
@@ -67,14 +417,11 @@ pub mod exec {
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
 
-        let mut traits = TRAITS.load(deps.storage)?;
-
+        let traits = trait_catalog();
         for trait_id in trait_ids {
-            traits.retain(|t| t.id != trait_id);
+            traits.remove(deps.storage, trait_id)?;
         }
 
-        TRAITS.save(deps.storage, &traits)?;
-
         Ok(Response::new().add_attribute("action", "remove_traits"))
     }
 
@@ -85,17 +432,17 @@ pub mod exec {
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
 
-        let mut characters = CHARACTERS.load(deps.storage)?;
-
-        for new_character in new_characters {
-            if characters.iter().any(|c| c.id == new_character.id) {
+        let characters = character_catalog();
+        for mut new_character in new_characters {
+            new_character.minted = 0;
+            validate_sale_window(&new_character)?;
+            order_traits(deps.storage, &mut new_character)?;
+            if characters.has(deps.storage, new_character.id) {
                 return Err(ContractError::IDExists {});
             }
-            characters.push(new_character)
+            characters.save(deps.storage, new_character.id, &new_character)?;
         }
 
-        CHARACTERS.save(deps.storage, &characters)?;
-
         Ok(Response::new().add_attribute("action", "add_characters"))
     }
 
@@ -106,14 +453,11 @@ pub mod exec {
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
 
-        let mut characters = CHARACTERS.load(deps.storage)?;
-
+        let characters = character_catalog();
         for character_id in character_ids {
-            characters.retain(|c| c.id != character_id);
+            characters.remove(deps.storage, character_id)?;
         }
 
-        CHARACTERS.save(deps.storage, &characters)?;
-
         Ok(Response::new().add_attribute("action", "remove_characters"))
     }
 
@@ -124,20 +468,19 @@ pub mod exec {
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
 
-        let mut trait_bundles = TRAIT_BUNDLES.load(deps.storage)?;
-
-        for new_trait_bundle in new_trait_bundles {
-            if trait_bundles.iter().any(|tb| tb.id == new_trait_bundle.id) {
+        for mut new_trait_bundle in new_trait_bundles {
+            new_trait_bundle.minted = 0;
+            validate_sale_window(&new_trait_bundle)?;
+            if TRAIT_BUNDLES.has(deps.storage, new_trait_bundle.id) {
                 return Err(ContractError::IDExists {});
             }
             if new_trait_bundle.traits.is_empty() {
                 return Err(ContractError::Empty {});
             }
-            trait_bundles.push(new_trait_bundle)
+            assert_registered_traits(deps.storage, &new_trait_bundle.traits)?;
+            TRAIT_BUNDLES.save(deps.storage, new_trait_bundle.id, &new_trait_bundle)?;
         }
 
-        TRAIT_BUNDLES.save(deps.storage, &trait_bundles)?;
-
         Ok(Response::new().add_attribute("action", "add_trait_bundles"))
     }
 
@@ -148,14 +491,10 @@ pub mod exec {
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
 
-        let mut trait_bundles = TRAIT_BUNDLES.load(deps.storage)?;
-
         for trait_bundle_id in trait_bundle_ids {
-            trait_bundles.retain(|tb| tb.id != trait_bundle_id);
+            TRAIT_BUNDLES.remove(deps.storage, trait_bundle_id);
         }
 
-        TRAIT_BUNDLES.save(deps.storage, &trait_bundles)?;
-
         Ok(Response::new().add_attribute("action", "remove_trait_bundles"))
     }
 
@@ -166,23 +505,21 @@ pub mod exec {
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
 
-        let mut character_bundles = CHARACTER_BUNDLES.load(deps.storage)?;
-
-        for new_character_bundle in new_character_bundles {
-            if character_bundles
-                .iter()
-                .any(|cb| cb.id == new_character_bundle.id)
-            {
+        for mut new_character_bundle in new_character_bundles {
+            new_character_bundle.minted = 0;
+            validate_sale_window(&new_character_bundle)?;
+            if CHARACTER_BUNDLES.has(deps.storage, new_character_bundle.id) {
                 return Err(ContractError::IDExists {});
             }
             if new_character_bundle.characters.is_empty() {
                 return Err(ContractError::Empty {});
             }
-            character_bundles.push(new_character_bundle)
+            for character in &mut new_character_bundle.characters {
+                order_traits(deps.storage, character)?;
+            }
+            CHARACTER_BUNDLES.save(deps.storage, new_character_bundle.id, &new_character_bundle)?;
         }
 
-        CHARACTER_BUNDLES.save(deps.storage, &character_bundles)?;
-
         Ok(Response::new().add_attribute("action", "add_character_bundles"))
     }
 
@@ -193,14 +530,10 @@ pub mod exec {
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
 
-        let mut character_bundles = CHARACTER_BUNDLES.load(deps.storage)?;
-
         for character_bundle_id in character_bundle_ids {
-            character_bundles.retain(|cb| cb.id != character_bundle_id);
+            CHARACTER_BUNDLES.remove(deps.storage, character_bundle_id);
         }
 
-        CHARACTER_BUNDLES.save(deps.storage, &character_bundles)?;
-
         Ok(Response::new().add_attribute("action", "remove_character_bundles"))
     }
 
@@ -211,34 +544,26 @@ pub mod exec {
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
 
-        let mut trait_lootboxes = TRAIT_LOOTBOXES.load(deps.storage)?;
-
-        for new_trait_lootbox in new_trait_lootboxes {
-            if trait_lootboxes
-                .iter()
-                .any(|tl| tl.id == new_trait_lootbox.id)
-            {
+        for mut new_trait_lootbox in new_trait_lootboxes {
+            new_trait_lootbox.minted = 0;
+            validate_sale_window(&new_trait_lootbox)?;
+            if TRAIT_LOOTBOXES.has(deps.storage, new_trait_lootbox.id) {
                 return Err(ContractError::IDExists {});
             }
 
             if new_trait_lootbox.traits.is_empty() {
                 return Err(ContractError::Empty {});
             }
+            assert_registered_traits(deps.storage, &new_trait_lootbox.traits)?;
 
-            if new_trait_lootbox.traits.len() != new_trait_lootbox.possibilities.len() {
-                return Err(ContractError::NotSameLength {});
-            }
-
-            let sum: u32 = new_trait_lootbox.possibilities.iter().sum();
-            if sum != 100 {
-                return Err(ContractError::InvalidProbabilities {});
-            }
+            validate_possibilities(
+                new_trait_lootbox.traits.len(),
+                &new_trait_lootbox.possibilities,
+            )?;
 
-            trait_lootboxes.push(new_trait_lootbox)
+            TRAIT_LOOTBOXES.save(deps.storage, new_trait_lootbox.id, &new_trait_lootbox)?;
         }
 
-        TRAIT_LOOTBOXES.save(deps.storage, &trait_lootboxes)?;
-
         Ok(Response::new().add_attribute("action", "add_trait_lootboxes"))
     }
 
@@ -249,14 +574,10 @@ pub mod exec {
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
 
-        let mut trait_lootboxes = TRAIT_LOOTBOXES.load(deps.storage)?;
-
         for trait_lootbox_id in trait_lootbox_ids {
-            trait_lootboxes.retain(|tl| tl.id != trait_lootbox_id);
+            TRAIT_LOOTBOXES.remove(deps.storage, trait_lootbox_id);
         }
 
-        TRAIT_LOOTBOXES.save(deps.storage, &trait_lootboxes)?;
-
         Ok(Response::new().add_attribute("action", "remove_trait_lootboxes"))
     }
 
@@ -267,37 +588,53 @@ pub mod exec {
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
 
-        let mut character_lootboxes = CHARACTER_LOOTBOXES.load(deps.storage)?;
-
-        for new_character_lootbox in new_character_lootboxes {
-            if character_lootboxes
-                .iter()
-                .any(|cl| cl.id == new_character_lootbox.id)
-            {
+        for mut new_character_lootbox in new_character_lootboxes {
+            new_character_lootbox.minted = 0;
+            validate_sale_window(&new_character_lootbox)?;
+            if CHARACTER_LOOTBOXES.has(deps.storage, new_character_lootbox.id) {
                 return Err(ContractError::IDExists {});
             }
 
             if new_character_lootbox.characters.is_empty() {
                 return Err(ContractError::Empty {});
             }
-
-            if new_character_lootbox.characters.len() != new_character_lootbox.possibilities.len() {
-                return Err(ContractError::NotSameLength {});
+            for character in &mut new_character_lootbox.characters {
+                order_traits(deps.storage, character)?;
             }
 
-            let sum: u32 = new_character_lootbox.possibilities.iter().sum();
-            if sum != 100 {
-                return Err(ContractError::InvalidProbabilities {});
-            }
+            validate_possibilities(
+                new_character_lootbox.characters.len(),
+                &new_character_lootbox.possibilities,
+            )?;
 
-            character_lootboxes.push(new_character_lootbox)
+            CHARACTER_LOOTBOXES.save(
+                deps.storage,
+                new_character_lootbox.id,
+                &new_character_lootbox,
+            )?;
         }
 
-        CHARACTER_LOOTBOXES.save(deps.storage, &character_lootboxes)?;
-
         Ok(Response::new().add_attribute("action", "add_character_lootboxes"))
     }
 
+    //Possibilities are weights over their own total, so odds like 0.1% can be expressed with a total of 1000
+    fn validate_possibilities(goods: usize, possibilities: &[u32]) -> Result<(), ContractError> {
+        if goods != possibilities.len() {
+            return Err(ContractError::NotSameLength {});
+        }
+
+        if possibilities.contains(&0) {
+            return Err(ContractError::ZeroProbability {});
+        }
+
+        possibilities
+            .iter()
+            .try_fold(0u32, |total, p| total.checked_add(*p))
+            .ok_or(ContractError::ProbabilitiesOverflow {})?;
+
+        Ok(())
+    }
+
     pub fn remove_character_lootboxes(
         deps: DepsMut,
         info: MessageInfo,
@@ -305,59 +642,601 @@ pub mod exec {
     ) -> Result<Response, ContractError> {
         cw_ownable::assert_owner(deps.storage, &info.sender)?;
 
-        let mut character_lootboxes = CHARACTER_LOOTBOXES.load(deps.storage)?;
-
         for character_lootbox_id in character_lootbox_ids {
-            character_lootboxes.retain(|cl| cl.id != character_lootbox_id);
+            CHARACTER_LOOTBOXES.remove(deps.storage, character_lootbox_id);
         }
 
-        CHARACTER_LOOTBOXES.save(deps.storage, &character_lootboxes)?;
-
         Ok(Response::new().add_attribute("action", "remove_character_lootboxes"))
     }
+
+    pub fn add_recipes(
+        deps: DepsMut,
+        info: MessageInfo,
+        new_recipes: Vec<Recipe>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        for new_recipe in new_recipes {
+            validate_recipe(deps.storage, &new_recipe)?;
+            if RECIPES.has(deps.storage, new_recipe.id) {
+                return Err(ContractError::IDExists {});
+            }
+            RECIPES.save(deps.storage, new_recipe.id, &new_recipe)?;
+        }
+
+        Ok(Response::new().add_attribute("action", "add_recipes"))
+    }
+
+    pub fn update_recipes(
+        deps: DepsMut,
+        info: MessageInfo,
+        recipes: Vec<Recipe>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        for recipe in recipes {
+            validate_recipe(deps.storage, &recipe)?;
+            if !RECIPES.has(deps.storage, recipe.id) {
+                return Err(ContractError::NotFound {});
+            }
+            RECIPES.save(deps.storage, recipe.id, &recipe)?;
+        }
+
+        Ok(Response::new().add_attribute("action", "update_recipes"))
+    }
+
+    pub fn remove_recipes(
+        deps: DepsMut,
+        info: MessageInfo,
+        recipe_ids: Vec<u32>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        for recipe_id in recipe_ids {
+            RECIPES.remove(deps.storage, recipe_id);
+        }
+
+        Ok(Response::new().add_attribute("action", "remove_recipes"))
+    }
+
+    fn validate_recipe(storage: &dyn Storage, recipe: &Recipe) -> Result<(), ContractError> {
+        if recipe.inputs.is_empty() || recipe.inputs.iter().any(|input| input.amount == 0) {
+            return Err(ContractError::InvalidRecipe {});
+        }
+        for input in &recipe.inputs {
+            match &input.trait_value {
+                Some(trait_value) => assert_registered(storage, &input.trait_type, trait_value)?,
+                None if !TRAIT_TYPES.has(storage, &input.trait_type) => {
+                    return Err(ContractError::UnregisteredTrait {})
+                }
+                None => {}
+            }
+        }
+        if let RecipeOutput::Random { trait_type, .. } = &recipe.output {
+            if !TRAIT_TYPES.has(storage, trait_type) {
+                return Err(ContractError::UnregisteredTrait {});
+            }
+        }
+        Ok(())
+    }
+
+    pub fn add_managers(
+        deps: DepsMut,
+        info: MessageInfo,
+        new_managers: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        let mut managers = MANAGERS.may_load(deps.storage)?.unwrap_or_default();
+
+        for new_manager in new_managers {
+            let new_manager = deps.api.addr_validate(&new_manager)?;
+            if !managers.contains(&new_manager) {
+                managers.push(new_manager)
+            }
+        }
+
+        MANAGERS.save(deps.storage, &managers)?;
+
+        Ok(Response::new().add_attribute("action", "add_managers"))
+    }
+
+    pub fn remove_managers(
+        deps: DepsMut,
+        info: MessageInfo,
+        manager_addresses: Vec<String>,
+    ) -> Result<Response, ContractError> {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+        let mut managers = MANAGERS.may_load(deps.storage)?.unwrap_or_default();
+
+        for manager_address in manager_addresses {
+            managers.retain(|m| *m != manager_address);
+        }
+
+        MANAGERS.save(deps.storage, &managers)?;
+
+        Ok(Response::new().add_attribute("action", "remove_managers"))
+    }
+
+    pub fn record_mint(
+        deps: DepsMut,
+        info: MessageInfo,
+        kind: MintableKind,
+        id: u32,
+    ) -> Result<Response, ContractError> {
+        let managers = MANAGERS.may_load(deps.storage)?.unwrap_or_default();
+        if !managers.contains(&info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        match kind {
+            MintableKind::Trait => {
+                trait_catalog().update(deps.storage, id, increment_minted)?;
+            }
+            MintableKind::Character => {
+                character_catalog().update(deps.storage, id, increment_minted)?;
+            }
+            MintableKind::TraitBundle => {
+                TRAIT_BUNDLES.update(deps.storage, id, increment_minted)?;
+            }
+            MintableKind::CharacterBundle => {
+                CHARACTER_BUNDLES.update(deps.storage, id, increment_minted)?;
+            }
+            MintableKind::TraitLootbox => {
+                TRAIT_LOOTBOXES.update(deps.storage, id, increment_minted)?;
+            }
+            MintableKind::CharacterLootbox => {
+                CHARACTER_LOOTBOXES.update(deps.storage, id, increment_minted)?;
+            }
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "record_mint")
+            .add_attribute("id", id.to_string()))
+    }
+
+    pub fn release_mint(
+        deps: DepsMut,
+        info: MessageInfo,
+        kind: MintableKind,
+        id: u32,
+    ) -> Result<Response, ContractError> {
+        let managers = MANAGERS.may_load(deps.storage)?.unwrap_or_default();
+        if !managers.contains(&info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        match kind {
+            MintableKind::Trait => {
+                trait_catalog().update(deps.storage, id, decrement_minted)?;
+            }
+            MintableKind::Character => {
+                character_catalog().update(deps.storage, id, decrement_minted)?;
+            }
+            MintableKind::TraitBundle => {
+                TRAIT_BUNDLES.update(deps.storage, id, decrement_minted)?;
+            }
+            MintableKind::CharacterBundle => {
+                CHARACTER_BUNDLES.update(deps.storage, id, decrement_minted)?;
+            }
+            MintableKind::TraitLootbox => {
+                TRAIT_LOOTBOXES.update(deps.storage, id, decrement_minted)?;
+            }
+            MintableKind::CharacterLootbox => {
+                CHARACTER_LOOTBOXES.update(deps.storage, id, decrement_minted)?;
+            }
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "release_mint")
+            .add_attribute("id", id.to_string()))
+    }
+
+    fn increment_minted<T: Supply>(item: Option<T>) -> Result<T, ContractError> {
+        let mut item = item.ok_or(ContractError::NotFound {})?;
+
+        if item.is_sold_out() {
+            return Err(ContractError::SoldOut {});
+        }
+        item.record_mint();
+
+        Ok(item)
+    }
+
+    fn decrement_minted<T: Supply>(item: Option<T>) -> Result<T, ContractError> {
+        let mut item = item.ok_or(ContractError::NotFound {})?;
+        item.release_mint();
+
+        Ok(item)
+    }
+
+    fn validate_sale_window<T: SaleWindow>(item: &T) -> Result<(), ContractError> {
+        if let (Some(start), Some(end)) = (item.sale_start(), item.sale_end()) {
+            //A height and a time cannot be compared, so mixed bounds are accepted
+            if start >= end {
+                return Err(ContractError::InvalidSaleWindow {});
+            }
+        }
+        Ok(())
+    }
 }
 
 pub mod query {
-    use cosmwasm_std::{Deps, StdResult};
+    use cosmwasm_std::{BlockInfo, Coin, Deps, Env, Order, StdResult, Storage, Timestamp};
+    use cw_storage_plus::{Bound, Map};
+    use serde::{de::DeserializeOwned, Serialize};
 
     use crate::{
         msg::{
-            CharacterBundlesResp, CharacterLootboxesResp, CharactersResp, TraitBundlesResp,
-            TraitLootboxesResp, TraitsResp,
+            ActiveItemsResp, CharacterBundlesResp, CharacterLootboxesResp, CharactersResp,
+            ManagersResp, MintableKind, RecipesResp, SlotsResp, SupplyResp, TraitBundlesResp,
+            TraitLootboxesResp, TraitTypesResp, TraitValuesResp, TraitsResp,
         },
         state::{
-            CHARACTERS, CHARACTER_BUNDLES, CHARACTER_LOOTBOXES, TRAITS, TRAIT_BUNDLES,
-            TRAIT_LOOTBOXES,
+            character_catalog, trait_catalog, Character, CharacterBundle, CharacterLootbox, Recipe,
+            SaleWindow, Supply, Trait, TraitBundle, TraitLootbox, TraitTypeInfo, TraitValueInfo,
+            CHARACTER_BUNDLES, CHARACTER_LOOTBOXES, MANAGERS, RECIPES, SLOTS, TRAIT_BUNDLES,
+            TRAIT_LOOTBOXES, TRAIT_TYPES, TRAIT_VALUES,
         },
     };
 
-    pub fn traits(deps: Deps) -> StdResult<TraitsResp> {
-        let traits = TRAITS.load(deps.storage)?;
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 100;
+
+    pub fn traits(
+        deps: Deps,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<TraitsResp> {
+        let start = start_after.map(Bound::exclusive);
+        let entries = trait_catalog().range(deps.storage, start, None, Order::Ascending);
+        let traits = collect_page(entries, limit)?;
+        Ok(TraitsResp { traits })
+    }
+
+    pub fn traits_by(
+        deps: Deps,
+        trait_type: Option<String>,
+        trait_value: Option<String>,
+        rarity: Option<String>,
+        denom: Option<String>,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<TraitsResp> {
+        let catalog = trait_catalog();
+        let start = start_after.map(Bound::exclusive);
+
+        //The most selective index is walked and the remaining filters are checked per entry
+        let entries = match (&trait_type, &trait_value, &rarity, &denom) {
+            (Some(trait_type), Some(trait_value), Some(rarity), _) => catalog
+                .idx
+                .attributes
+                .prefix((trait_type.clone(), trait_value.clone(), rarity.clone()))
+                .range(deps.storage, start, None, Order::Ascending),
+            (Some(trait_type), ..) => catalog.idx.trait_type.prefix(trait_type.clone()).range(
+                deps.storage,
+                start,
+                None,
+                Order::Ascending,
+            ),
+            (None, _, Some(rarity), _) => catalog.idx.rarity.prefix(rarity.clone()).range(
+                deps.storage,
+                start,
+                None,
+                Order::Ascending,
+            ),
+            (None, _, None, Some(denom)) => catalog.idx.denom.prefix(denom.clone()).range(
+                deps.storage,
+                start,
+                None,
+                Order::Ascending,
+            ),
+            (None, _, None, None) => catalog.range(deps.storage, start, None, Order::Ascending),
+        };
+
+        let entries = entries.filter(|entry| match entry {
+            Ok((_, t)) => {
+                matches(&trait_type, &t.trait_type)
+                    && matches(&trait_value, &t.trait_value)
+                    && matches(&rarity, &t.trait_rarity)
+                    && matches_price(&denom, &t.mint_price)
+            }
+            Err(_) => true,
+        });
+        let traits = collect_page(entries, limit)?;
         Ok(TraitsResp { traits })
     }
 
-    pub fn characters(deps: Deps) -> StdResult<CharactersResp> {
-        let characters = CHARACTERS.load(deps.storage)?;
+    pub fn characters(
+        deps: Deps,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<CharactersResp> {
+        let start = start_after.map(Bound::exclusive);
+        let entries = character_catalog().range(deps.storage, start, None, Order::Ascending);
+        let characters = collect_page(entries, limit)?;
         Ok(CharactersResp { characters })
     }
 
-    pub fn trait_bundles(deps: Deps) -> StdResult<TraitBundlesResp> {
-        let bundles = TRAIT_BUNDLES.load(deps.storage)?;
+    pub fn characters_by(
+        deps: Deps,
+        rarity: Option<String>,
+        denom: Option<String>,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<CharactersResp> {
+        let catalog = character_catalog();
+        let start = start_after.map(Bound::exclusive);
+
+        let entries = match (&rarity, &denom) {
+            (Some(rarity), _) => catalog.idx.rarity.prefix(rarity.clone()).range(
+                deps.storage,
+                start,
+                None,
+                Order::Ascending,
+            ),
+            (None, Some(denom)) => catalog.idx.denom.prefix(denom.clone()).range(
+                deps.storage,
+                start,
+                None,
+                Order::Ascending,
+            ),
+            (None, None) => catalog.range(deps.storage, start, None, Order::Ascending),
+        };
+
+        let entries = entries.filter(|entry| match entry {
+            Ok((_, c)) => matches(&rarity, &c.rarity) && matches_price(&denom, &c.mint_price),
+            Err(_) => true,
+        });
+        let characters = collect_page(entries, limit)?;
+        Ok(CharactersResp { characters })
+    }
+
+    pub fn trait_bundles(
+        deps: Deps,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<TraitBundlesResp> {
+        let bundles = paginate(deps.storage, &TRAIT_BUNDLES, start_after, limit)?;
         Ok(TraitBundlesResp { bundles })
     }
 
-    pub fn character_bundles(deps: Deps) -> StdResult<CharacterBundlesResp> {
-        let bundles = CHARACTER_BUNDLES.load(deps.storage)?;
+    pub fn character_bundles(
+        deps: Deps,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<CharacterBundlesResp> {
+        let bundles = paginate(deps.storage, &CHARACTER_BUNDLES, start_after, limit)?;
         Ok(CharacterBundlesResp { bundles })
     }
 
-    pub fn trait_lootboxes(deps: Deps) -> StdResult<TraitLootboxesResp> {
-        let lootboxes = TRAIT_LOOTBOXES.load(deps.storage)?;
+    pub fn trait_lootboxes(
+        deps: Deps,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<TraitLootboxesResp> {
+        let lootboxes = paginate(deps.storage, &TRAIT_LOOTBOXES, start_after, limit)?;
         Ok(TraitLootboxesResp { lootboxes })
     }
 
-    pub fn character_lootboxes(deps: Deps) -> StdResult<CharacterLootboxesResp> {
-        let lootboxes = CHARACTER_LOOTBOXES.load(deps.storage)?;
+    pub fn character_lootboxes(
+        deps: Deps,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<CharacterLootboxesResp> {
+        let lootboxes = paginate(deps.storage, &CHARACTER_LOOTBOXES, start_after, limit)?;
         Ok(CharacterLootboxesResp { lootboxes })
     }
+
+    pub fn trait_by_id(deps: Deps, id: u32) -> StdResult<Trait> {
+        trait_catalog().load(deps.storage, id)
+    }
+
+    pub fn character_by_id(deps: Deps, id: u32) -> StdResult<Character> {
+        character_catalog().load(deps.storage, id)
+    }
+
+    pub fn trait_bundle_by_id(deps: Deps, id: u32) -> StdResult<TraitBundle> {
+        TRAIT_BUNDLES.load(deps.storage, id)
+    }
+
+    pub fn character_bundle_by_id(deps: Deps, id: u32) -> StdResult<CharacterBundle> {
+        CHARACTER_BUNDLES.load(deps.storage, id)
+    }
+
+    pub fn trait_lootbox_by_id(deps: Deps, id: u32) -> StdResult<TraitLootbox> {
+        TRAIT_LOOTBOXES.load(deps.storage, id)
+    }
+
+    pub fn character_lootbox_by_id(deps: Deps, id: u32) -> StdResult<CharacterLootbox> {
+        CHARACTER_LOOTBOXES.load(deps.storage, id)
+    }
+
+    pub fn recipes(
+        deps: Deps,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<RecipesResp> {
+        let recipes = paginate(deps.storage, &RECIPES, start_after, limit)?;
+        Ok(RecipesResp { recipes })
+    }
+
+    pub fn recipe_by_id(deps: Deps, id: u32) -> StdResult<Recipe> {
+        RECIPES.load(deps.storage, id)
+    }
+
+    pub fn supply(deps: Deps, kind: MintableKind, id: u32) -> StdResult<SupplyResp> {
+        match kind {
+            MintableKind::Trait => Ok(supply_of(&trait_catalog().load(deps.storage, id)?)),
+            MintableKind::Character => Ok(supply_of(&character_catalog().load(deps.storage, id)?)),
+            MintableKind::TraitBundle => Ok(supply_of(&TRAIT_BUNDLES.load(deps.storage, id)?)),
+            MintableKind::CharacterBundle => {
+                Ok(supply_of(&CHARACTER_BUNDLES.load(deps.storage, id)?))
+            }
+            MintableKind::TraitLootbox => Ok(supply_of(&TRAIT_LOOTBOXES.load(deps.storage, id)?)),
+            MintableKind::CharacterLootbox => {
+                Ok(supply_of(&CHARACTER_LOOTBOXES.load(deps.storage, id)?))
+            }
+        }
+    }
+
+    pub fn active_items(
+        deps: Deps,
+        env: Env,
+        kind: MintableKind,
+        height: Option<u64>,
+        time: Option<Timestamp>,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<ActiveItemsResp> {
+        let mut block = env.block;
+        block.height = height.unwrap_or(block.height);
+        block.time = time.unwrap_or(block.time);
+
+        let start = start_after.map(Bound::exclusive);
+        let (storage, order) = (deps.storage, Order::Ascending);
+        let ids = match kind {
+            MintableKind::Trait => active_ids(
+                trait_catalog().range(storage, start, None, order),
+                &block,
+                limit,
+            ),
+            MintableKind::Character => active_ids(
+                character_catalog().range(storage, start, None, order),
+                &block,
+                limit,
+            ),
+            MintableKind::TraitBundle => active_ids(
+                TRAIT_BUNDLES.range(storage, start, None, order),
+                &block,
+                limit,
+            ),
+            MintableKind::CharacterBundle => active_ids(
+                CHARACTER_BUNDLES.range(storage, start, None, order),
+                &block,
+                limit,
+            ),
+            MintableKind::TraitLootbox => active_ids(
+                TRAIT_LOOTBOXES.range(storage, start, None, order),
+                &block,
+                limit,
+            ),
+            MintableKind::CharacterLootbox => active_ids(
+                CHARACTER_LOOTBOXES.range(storage, start, None, order),
+                &block,
+                limit,
+            ),
+        }?;
+
+        Ok(ActiveItemsResp { ids })
+    }
+
+    pub fn managers(deps: Deps) -> StdResult<ManagersResp> {
+        let managers = MANAGERS.may_load(deps.storage)?.unwrap_or_default();
+        Ok(ManagersResp { managers })
+    }
+
+    pub fn slots(deps: Deps) -> StdResult<SlotsResp> {
+        let slots = SLOTS.may_load(deps.storage)?.unwrap_or_default();
+        Ok(SlotsResp { slots })
+    }
+
+    pub fn trait_type(deps: Deps, trait_type: String) -> StdResult<TraitTypeInfo> {
+        TRAIT_TYPES.load(deps.storage, &trait_type)
+    }
+
+    pub fn trait_types(
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TraitTypesResp> {
+        let start = start_after.as_deref().map(Bound::exclusive);
+        let entries = TRAIT_TYPES.range(deps.storage, start, None, Order::Ascending);
+        let trait_types = collect_page(entries, limit)?;
+        Ok(TraitTypesResp { trait_types })
+    }
+
+    pub fn trait_value(
+        deps: Deps,
+        trait_type: String,
+        trait_value: String,
+    ) -> StdResult<TraitValueInfo> {
+        TRAIT_VALUES.load(deps.storage, (&trait_type, &trait_value))
+    }
+
+    pub fn trait_values(
+        deps: Deps,
+        trait_type: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TraitValuesResp> {
+        let start = start_after.as_deref().map(Bound::exclusive);
+        let entries =
+            TRAIT_VALUES
+                .prefix(&trait_type)
+                .range(deps.storage, start, None, Order::Ascending);
+        let trait_values = collect_page(entries, limit)?;
+        Ok(TraitValuesResp { trait_values })
+    }
+
+    fn supply_of<T: Supply>(item: &T) -> SupplyResp {
+        SupplyResp {
+            max_supply: item.max_supply(),
+            minted: item.minted(),
+            remaining: item.remaining_supply(),
+        }
+    }
+
+    fn active_ids<T: Supply + SaleWindow>(
+        entries: impl Iterator<Item = StdResult<(u32, T)>>,
+        block: &BlockInfo,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<u32>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        entries
+            .filter(|entry| match entry {
+                Ok((_, item)) => item.is_on_sale(block) && !item.is_sold_out(),
+                Err(_) => true,
+            })
+            .take(limit)
+            .map(|entry| entry.map(|(id, _)| id))
+            .collect()
+    }
+
+    fn matches(filter: &Option<String>, value: &str) -> bool {
+        match filter {
+            Some(filter) => filter == value,
+            None => true,
+        }
+    }
+
+    //Entries priced by rarity only match an empty denom, as in the index
+    fn matches_price(denom: &Option<String>, price: &Option<Coin>) -> bool {
+        let price_denom = price.as_ref().map_or("", |price| price.denom.as_str());
+        matches(denom, price_denom)
+    }
+
+    fn paginate<T: Serialize + DeserializeOwned>(
+        storage: &dyn Storage,
+        store: &Map<u32, T>,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<T>> {
+        let start = start_after.map(Bound::exclusive);
+        collect_page(store.range(storage, start, None, Order::Ascending), limit)
+    }
+
+    fn collect_page<K, T>(
+        entries: impl Iterator<Item = StdResult<(K, T)>>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<T>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+        entries
+            .take(limit)
+            .map(|item| item.map(|(_, v)| v))
+            .collect()
+    }
 }
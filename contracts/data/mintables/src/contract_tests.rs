@@ -0,0 +1,980 @@
+#![cfg(test)]
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{coin, from_binary, Addr, Deps, DepsMut, Timestamp};
+use cw_utils::Scheduled;
+
+use crate::error::ContractError;
+use crate::msg::{
+    ActiveItemsResp, ExecuteMsg, InstantiateMsg, ManagersResp, MigrateMsg, MintableKind, QueryMsg,
+    RecipesResp, SlotsResp, SupplyResp, TraitTypesResp, TraitValuesResp, TraitsResp,
+};
+use crate::state::{
+    trait_catalog, Character, CharacterBundle, LegacyCharacter, LegacyCharacterBundle, Recipe,
+    RecipeInput, RecipeOutput, Trait, TraitLootbox, TraitTypeInfo, TraitValueInfo,
+    LEGACY_CHARACTERS, LEGACY_CHARACTER_BUNDLES, LEGACY_TRAITS,
+};
+use crate::{execute, instantiate, migrate, query};
+
+const OWNER: &str = "owner";
+
+fn test_trait(id: u32) -> Trait {
+    Trait {
+        id,
+        trait_type: "eyes".to_string(),
+        trait_value: format!("value{}", id),
+        trait_rarity: "common".to_string(),
+        mint_price: Some(coin(100, "uccat")),
+        max_supply: None,
+        minted: 0,
+        per_address_limit: None,
+        sale_start: None,
+        sale_end: None,
+    }
+}
+
+//Registers the types and values of the traits built by the tests
+fn register_test_traits(mut deps: DepsMut) {
+    let trait_types = ["background", "ears", "eyes", "hat"];
+    execute(
+        deps.branch(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::SetTraitTypes {
+            trait_types: trait_types
+                .iter()
+                .map(|trait_type| TraitTypeInfo {
+                    trait_type: trait_type.to_string(),
+                    display_name: trait_type.to_uppercase(),
+                    image_key: trait_type.to_string(),
+                })
+                .collect(),
+        },
+    )
+    .unwrap();
+    let trait_values = trait_types
+        .iter()
+        .flat_map(|trait_type| {
+            (1..=20).map(move |id| TraitValueInfo {
+                trait_type: trait_type.to_string(),
+                trait_value: format!("value{}", id),
+                display_name: format!("Value {}", id),
+                image_key: format!("{}/value{}", trait_type, id),
+            })
+        })
+        .collect();
+    execute(
+        deps.branch(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::SetTraitValues { trait_values },
+    )
+    .unwrap();
+}
+
+fn add_lootbox(possibilities: Vec<u32>, traits: usize) -> Result<(), ContractError> {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg { slots: vec![] },
+    )
+    .unwrap();
+    register_test_traits(deps.as_mut());
+
+    let lootbox = TraitLootbox {
+        id: 1,
+        traits: (1..=traits as u32).map(test_trait).collect(),
+        possibilities,
+        mint_price: coin(100, "uccat"),
+        max_supply: None,
+        minted: 0,
+        per_address_limit: None,
+        sale_start: None,
+        sale_end: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::AddTraitLootboxes {
+            new_trait_lootboxes: vec![lootbox.clone()],
+        },
+    )?;
+
+    let res: TraitLootbox =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::TraitLootbox { id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(res, lootbox);
+    Ok(())
+}
+
+#[test]
+fn lootbox_possibilities_are_weights() {
+    //0.1% odds for the second trait
+    add_lootbox(vec![999, 1], 2).unwrap();
+    add_lootbox(vec![3, 5, 7], 3).unwrap();
+}
+
+#[test]
+fn invalid_lootbox_possibilities() {
+    assert_eq!(add_lootbox(vec![], 0), Err(ContractError::Empty {}));
+    assert_eq!(
+        add_lootbox(vec![100], 2),
+        Err(ContractError::NotSameLength {})
+    );
+    assert_eq!(
+        add_lootbox(vec![50, 25, 25], 2),
+        Err(ContractError::NotSameLength {})
+    );
+    assert_eq!(
+        add_lootbox(vec![100, 0], 2),
+        Err(ContractError::ZeroProbability {})
+    );
+    assert_eq!(
+        add_lootbox(vec![u32::MAX, 1], 2),
+        Err(ContractError::ProbabilitiesOverflow {})
+    );
+}
+
+#[test]
+fn record_mints_until_sold_out() {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg { slots: vec![] },
+    )
+    .unwrap();
+    register_test_traits(deps.as_mut());
+
+    //Counters sent by the owner are ignored
+    let mut new_trait = test_trait(1);
+    new_trait.max_supply = Some(2);
+    new_trait.minted = 2;
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::AddTraits {
+            new_traits: vec![new_trait],
+        },
+    )
+    .unwrap();
+
+    let record = ExecuteMsg::RecordMint {
+        kind: MintableKind::Trait,
+        id: 1,
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("manager", &[]),
+        record.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::AddManagers {
+            managers: vec!["manager".to_string()],
+        },
+    )
+    .unwrap();
+
+    for _ in 0..2 {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("manager", &[]),
+            record.clone(),
+        )
+        .unwrap();
+    }
+    let err = execute(deps.as_mut(), mock_env(), mock_info("manager", &[]), record).unwrap_err();
+    assert_eq!(err, ContractError::SoldOut {});
+
+    let supply: SupplyResp = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Supply {
+                kind: MintableKind::Trait,
+                id: 1,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        supply,
+        SupplyResp {
+            max_supply: Some(2),
+            minted: 2,
+            remaining: Some(0),
+        }
+    );
+
+    //Refunded mints give their supply back
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("manager", &[]),
+        ExecuteMsg::ReleaseMint {
+            kind: MintableKind::Trait,
+            id: 1,
+        },
+    )
+    .unwrap();
+    let supply: SupplyResp = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Supply {
+                kind: MintableKind::Trait,
+                id: 1,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(supply.remaining, Some(1));
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("manager", &[]),
+        ExecuteMsg::RecordMint {
+            kind: MintableKind::TraitBundle,
+            id: 1,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotFound {});
+}
+
+fn trait_ids(deps: Deps, start_after: Option<u32>, limit: Option<u32>) -> Vec<u32> {
+    let res: TraitsResp =
+        from_binary(&query(deps, mock_env(), QueryMsg::Traits { start_after, limit }).unwrap())
+            .unwrap();
+    res.traits.iter().map(|t| t.id).collect()
+}
+
+#[test]
+fn paginated_traits() {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg { slots: vec![] },
+    )
+    .unwrap();
+    register_test_traits(deps.as_mut());
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::AddTraits {
+            new_traits: (1..=5).map(test_trait).collect(),
+        },
+    )
+    .unwrap();
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::AddTraits {
+            new_traits: vec![test_trait(3)],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::IDExists {});
+
+    assert_eq!(trait_ids(deps.as_ref(), None, None), vec![1, 2, 3, 4, 5]);
+    assert_eq!(trait_ids(deps.as_ref(), None, Some(2)), vec![1, 2]);
+    assert_eq!(trait_ids(deps.as_ref(), Some(2), Some(2)), vec![3, 4]);
+    assert_eq!(trait_ids(deps.as_ref(), Some(4), None), vec![5]);
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::RemoveTraits { ids: vec![2, 4] },
+    )
+    .unwrap();
+    assert_eq!(trait_ids(deps.as_ref(), None, None), vec![1, 3, 5]);
+    assert!(query(deps.as_ref(), mock_env(), QueryMsg::Trait { id: 2 }).is_err());
+}
+
+#[test]
+fn migrate_legacy_catalog() {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg { slots: vec![] },
+    )
+    .unwrap();
+
+    //Catalog stored as a single vector by 0.1.0
+    cw2::set_contract_version(deps.as_mut().storage, "mintables", "0.1.0").unwrap();
+    LEGACY_TRAITS
+        .save(deps.as_mut().storage, &vec![test_trait(7), test_trait(3)])
+        .unwrap();
+    //Characters still had one field per slot
+    let legacy_character = LegacyCharacter {
+        id: 2,
+        ears: Some("pointy".to_string()),
+        eyes: None,
+        mouth: Some("smile".to_string()),
+        fur_type: None,
+        fur_color: None,
+        tail_shape: None,
+        rarity: "rare".to_string(),
+        locked: false,
+        mint_price: None,
+        max_supply: None,
+        minted: 0,
+        per_address_limit: None,
+        sale_start: None,
+        sale_end: None,
+    };
+    LEGACY_CHARACTERS
+        .save(deps.as_mut().storage, &vec![legacy_character.clone()])
+        .unwrap();
+    LEGACY_CHARACTER_BUNDLES
+        .save(
+            deps.as_mut().storage,
+            &vec![LegacyCharacterBundle {
+                id: 4,
+                characters: vec![legacy_character],
+                mint_price: coin(100, "uccat"),
+                max_supply: None,
+                minted: 0,
+                per_address_limit: None,
+                sale_start: None,
+                sale_end: None,
+            }],
+        )
+        .unwrap();
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            managers: vec!["trait_manager".to_string(), "character_manager".to_string()],
+        },
+    )
+    .unwrap();
+
+    //0.1.0 kept no managers, the ones given to the migration can record mints right away
+    let res: ManagersResp =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Managers {}).unwrap()).unwrap();
+    assert_eq!(
+        res.managers,
+        vec![
+            Addr::unchecked("trait_manager"),
+            Addr::unchecked("character_manager")
+        ]
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("trait_manager", &[]),
+        ExecuteMsg::RecordMint {
+            kind: MintableKind::Trait,
+            id: 3,
+        },
+    )
+    .unwrap();
+
+    assert!(LEGACY_TRAITS.may_load(&deps.storage).unwrap().is_none());
+    assert_eq!(
+        trait_catalog().load(&deps.storage, 7).unwrap(),
+        test_trait(7)
+    );
+    let res: Trait =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Trait { id: 3 }).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        Trait {
+            minted: 1,
+            ..test_trait(3)
+        }
+    );
+
+    //Migrated entries are reachable through the secondary indexes
+    assert_eq!(
+        traits_by(deps.as_ref(), Some("eyes"), None, None, None),
+        vec![3, 7]
+    );
+
+    //Characters get their slots moved into the registry order
+    let slots = vec![
+        ("ears".to_string(), "pointy".to_string()),
+        ("mouth".to_string(), "smile".to_string()),
+    ];
+    let res: Character =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Character { id: 2 }).unwrap())
+            .unwrap();
+    assert_eq!(res.traits, slots);
+    let res: CharacterBundle = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::CharacterBundle { id: 4 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.characters[0].traits, slots);
+    let res: SlotsResp =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Slots {}).unwrap()).unwrap();
+    assert_eq!(res.slots.len(), 6);
+    assert_eq!(res.slots[5], "tail_shape");
+
+    //Traits already in the catalogs are registered with their names
+    let res: TraitValueInfo = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::TraitValue {
+                trait_type: "ears".to_string(),
+                trait_value: "pointy".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.display_name, "pointy");
+    assert_eq!(res.image_key, "ears/pointy");
+    assert!(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::TraitType {
+            trait_type: "eyes".to_string(),
+        },
+    )
+    .is_ok());
+
+    let version = cw2::get_contract_version(&deps.storage).unwrap();
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    //Downgrades and other contracts are refused
+    cw2::set_contract_version(deps.as_mut().storage, "mintables", "9.0.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { managers: vec![] }).unwrap_err();
+    assert_eq!(err, ContractError::WrongMigrateVersion("9.0.0".to_string()));
+    cw2::set_contract_version(deps.as_mut().storage, "trait-manager", "0.1.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { managers: vec![] }).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::WrongMigrateContract("trait-manager".to_string())
+    );
+}
+
+fn traits_by(
+    deps: Deps,
+    trait_type: Option<&str>,
+    trait_value: Option<&str>,
+    rarity: Option<&str>,
+    denom: Option<&str>,
+) -> Vec<u32> {
+    let msg = QueryMsg::TraitsBy {
+        trait_type: trait_type.map(str::to_string),
+        trait_value: trait_value.map(str::to_string),
+        rarity: rarity.map(str::to_string),
+        denom: denom.map(str::to_string),
+        start_after: None,
+        limit: None,
+    };
+    let res: TraitsResp = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+    res.traits.iter().map(|t| t.id).collect()
+}
+
+#[test]
+fn filter_traits() {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg { slots: vec![] },
+    )
+    .unwrap();
+    register_test_traits(deps.as_mut());
+
+    let mut new_traits: Vec<Trait> = (1..=4).map(test_trait).collect();
+    new_traits[1].trait_type = "hat".to_string();
+    new_traits[2].trait_rarity = "rare".to_string();
+    new_traits[3].mint_price = Some(coin(5, "uatom"));
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::AddTraits { new_traits },
+    )
+    .unwrap();
+
+    let deps = deps.as_ref();
+    assert_eq!(
+        traits_by(deps, Some("eyes"), None, None, None),
+        vec![1, 3, 4]
+    );
+    assert_eq!(traits_by(deps, None, None, Some("rare"), None), vec![3]);
+    assert_eq!(traits_by(deps, None, None, None, Some("uatom")), vec![4]);
+    assert_eq!(
+        traits_by(deps, Some("eyes"), None, Some("common"), Some("uccat")),
+        vec![1]
+    );
+    assert_eq!(
+        traits_by(deps, Some("eyes"), Some("value3"), Some("rare"), None),
+        vec![3]
+    );
+    assert!(traits_by(deps, Some("eyes"), Some("value3"), Some("common"), None).is_empty());
+    assert_eq!(traits_by(deps, None, None, None, None), vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn active_items() {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg { slots: vec![] },
+    )
+    .unwrap();
+    register_test_traits(deps.as_mut());
+
+    let now = mock_env().block.time;
+    let height = mock_env().block.height;
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::AddTraits {
+            new_traits: vec![Trait {
+                sale_start: Some(Scheduled::AtHeight(height + 10)),
+                sale_end: Some(Scheduled::AtHeight(height)),
+                ..test_trait(1)
+            }],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidSaleWindow {});
+
+    let new_traits = vec![
+        test_trait(1),
+        Trait {
+            sale_start: Some(Scheduled::AtTime(now.plus_seconds(60))),
+            ..test_trait(2)
+        },
+        Trait {
+            sale_end: Some(Scheduled::AtTime(now.plus_seconds(60))),
+            ..test_trait(3)
+        },
+        Trait {
+            sale_start: Some(Scheduled::AtHeight(height)),
+            max_supply: Some(0),
+            ..test_trait(4)
+        },
+    ];
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::AddTraits { new_traits },
+    )
+    .unwrap();
+
+    let active = |time: Option<Timestamp>| -> Vec<u32> {
+        let msg = QueryMsg::ActiveItems {
+            kind: MintableKind::Trait,
+            height: None,
+            time,
+            start_after: None,
+            limit: None,
+        };
+        let res: ActiveItemsResp =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.ids
+    };
+    assert_eq!(active(None), vec![1, 3]);
+    assert_eq!(active(Some(now.plus_seconds(60))), vec![1, 2]);
+}
+
+fn test_recipe(id: u32, amount: u32) -> Recipe {
+    Recipe {
+        id,
+        inputs: vec![RecipeInput {
+            trait_type: "eyes".to_string(),
+            trait_value: None,
+            trait_rarity: "common".to_string(),
+            amount,
+        }],
+        output: RecipeOutput::Random {
+            trait_type: "eyes".to_string(),
+            trait_rarity: "rare".to_string(),
+        },
+    }
+}
+
+#[test]
+fn manage_recipes() {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg { slots: vec![] },
+    )
+    .unwrap();
+    register_test_traits(deps.as_mut());
+
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::AddRecipes {
+            new_recipes: vec![test_recipe(1, 0)],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidRecipe {});
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::AddRecipes {
+            new_recipes: vec![test_recipe(1, 3), test_recipe(2, 5)],
+        },
+    )
+    .unwrap();
+
+    //Only existing recipes can be edited
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::UpdateRecipes {
+            recipes: vec![test_recipe(3, 2)],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::NotFound {});
+
+    let mut edited = test_recipe(1, 2);
+    edited.output = RecipeOutput::Trait { id: 7 };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::UpdateRecipes {
+            recipes: vec![edited.clone()],
+        },
+    )
+    .unwrap();
+    let res: Recipe =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Recipe { id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(res, edited);
+    assert_eq!(res.input_count(), 2);
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::RemoveRecipes { ids: vec![1] },
+    )
+    .unwrap();
+    let res: RecipesResp = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Recipes {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.recipes, vec![test_recipe(2, 5)]);
+}
+
+fn test_character(id: u32, traits: &[(&str, &str)]) -> Character {
+    Character {
+        id,
+        traits: traits
+            .iter()
+            .map(|(slot, value)| (slot.to_string(), value.to_string()))
+            .collect(),
+        rarity: "common".to_string(),
+        locked: false,
+        mint_price: None,
+        max_supply: None,
+        minted: 0,
+        per_address_limit: None,
+        sale_start: None,
+        sale_end: None,
+    }
+}
+
+#[test]
+fn character_slots_registry() {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg {
+            slots: vec!["eyes".to_string(), "hat".to_string()],
+        },
+    )
+    .unwrap();
+    register_test_traits(deps.as_mut());
+
+    //Only the owner can replace the registry and names must be unique
+    let set_slots = |slots: &[&str]| ExecuteMsg::SetSlots {
+        slots: slots.iter().map(|slot| slot.to_string()).collect(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        set_slots(&["eyes"]),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Ownership(_)));
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        set_slots(&["eyes", "eyes"]),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidSlots {});
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        set_slots(&["background", "eyes", "hat"]),
+    )
+    .unwrap();
+    let res: SlotsResp =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Slots {}).unwrap()).unwrap();
+    assert_eq!(res.slots, vec!["background", "eyes", "hat"]);
+
+    //Unknown or repeated slots are refused
+    for traits in [
+        vec![("ears", "value1")],
+        vec![("hat", "value1"), ("hat", "value2")],
+    ] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::AddCharacters {
+                new_characters: vec![test_character(1, &traits)],
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidSlot {});
+    }
+
+    //Slots are stored in the registry order
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::AddCharacters {
+            new_characters: vec![test_character(
+                1,
+                &[
+                    ("hat", "value1"),
+                    ("background", "value1"),
+                    ("eyes", "value1"),
+                ],
+            )],
+        },
+    )
+    .unwrap();
+    let res: Character =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Character { id: 1 }).unwrap())
+            .unwrap();
+    assert_eq!(
+        res,
+        test_character(
+            1,
+            &[
+                ("background", "value1"),
+                ("eyes", "value1"),
+                ("hat", "value1")
+            ]
+        )
+    );
+}
+
+#[test]
+fn trait_registry() {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg {
+            slots: vec!["eyes".to_string()],
+        },
+    )
+    .unwrap();
+
+    //Catalog entries can only use registered traits
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::AddTraits {
+            new_traits: vec![test_trait(1)],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::UnregisteredTrait {});
+
+    //Values need their type registered first
+    let value_info = TraitValueInfo {
+        trait_type: "eyes".to_string(),
+        trait_value: "value1".to_string(),
+        display_name: "Laser eyes".to_string(),
+        image_key: "eyes/laser.png".to_string(),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::SetTraitValues {
+            trait_values: vec![value_info.clone()],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::UnregisteredTrait {});
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("anyone", &[]),
+        ExecuteMsg::SetTraitTypes {
+            trait_types: vec![],
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Ownership(_)));
+    register_test_traits(deps.as_mut());
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::SetTraitValues {
+            trait_values: vec![value_info.clone()],
+        },
+    )
+    .unwrap();
+
+    let res: TraitValueInfo = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::TraitValue {
+                trait_type: "eyes".to_string(),
+                trait_value: "value1".to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res, value_info);
+    let res: TraitTypesResp = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::TraitTypes {
+                start_after: Some("ears".to_string()),
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let trait_types: Vec<String> = res.trait_types.into_iter().map(|t| t.trait_type).collect();
+    assert_eq!(trait_types, vec!["eyes", "hat"]);
+    let res: TraitValuesResp = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::TraitValues {
+                trait_type: "hat".to_string(),
+                start_after: None,
+                limit: Some(3),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let trait_values: Vec<String> = res
+        .trait_values
+        .into_iter()
+        .map(|t| t.trait_value)
+        .collect();
+    assert_eq!(trait_values, vec!["value1", "value10", "value11"]);
+
+    //Removed values and the values of removed types are no longer accepted
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::RemoveTraitValues {
+            trait_values: vec![("eyes".to_string(), "value1".to_string())],
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        ExecuteMsg::RemoveTraitTypes {
+            trait_types: vec!["hat".to_string()],
+        },
+    )
+    .unwrap();
+    assert!(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::TraitValue {
+            trait_type: "hat".to_string(),
+            trait_value: "value2".to_string(),
+        },
+    )
+    .is_err());
+    for new_character in [
+        test_character(1, &[("eyes", "value1")]),
+        test_character(1, &[("hat", "value2")]),
+    ] {
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::AddCharacters {
+                new_characters: vec![new_character],
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::UnregisteredTrait {});
+    }
+}
@@ -19,6 +19,51 @@ pub enum ContractError {
     #[error("Goods cannot be empty")]
     Empty {},
 
-    #[error("Sum of lootbox probabilities must be 100")]
-    InvalidProbabilities {},
+    #[error("Lootbox probabilities cannot be zero")]
+    ZeroProbability {},
+
+    #[error("Sum of lootbox probabilities overflows")]
+    ProbabilitiesOverflow {},
+
+    #[error("Only managers can record mints")]
+    Unauthorized {},
+
+    #[error("ID does not exist")]
+    NotFound {},
+
+    #[error("Sold out")]
+    SoldOut {},
+
+    #[error("Sale start must be before sale end")]
+    InvalidSaleWindow {},
+
+    #[error("Slot names must be unique and not empty")]
+    InvalidSlots {},
+
+    #[error("Character slots must be registered and appear only once")]
+    InvalidSlot {},
+
+    #[error("Trait type or value is not in the registry")]
+    UnregisteredTrait {},
+
+    #[error("Trait types and values cannot be empty")]
+    InvalidTraitRegistry {},
+
+    #[error("Recipes need at least one input and every input amount must be positive")]
+    InvalidRecipe {},
+
+    #[error("found version ({0}) which is newer than the code being migrated to")]
+    WrongMigrateVersion(String),
+
+    #[error("found contract ({0}) while attempting to migrate")]
+    WrongMigrateContract(String),
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
 }
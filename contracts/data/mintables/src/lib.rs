@@ -1,38 +1,124 @@
 mod contract;
 pub mod error;
 pub mod msg;
-mod state;
+pub mod state;
+
+#[cfg(test)]
+mod contract_tests;
+
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
 use error::ContractError;
-use msg::{ExecuteMsg, InstantiateMsg};
+use msg::{ExecuteMsg, InstantiateMsg, MigrateMsg};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    _msg: InstantiateMsg,
-) -> StdResult<Response> {
-    contract::instantiate(deps, info)
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    contract::instantiate(deps, info, msg.slots)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
     use contract::query;
     use msg::QueryMsg::*;
 
     match msg {
-        Traits {} => to_binary(&query::traits(deps)?),
-        Characters {} => to_binary(&query::characters(deps)?),
-        TraitBundles {} => to_binary(&query::trait_bundles(deps)?),
-        CharacterBundles {} => to_binary(&query::character_bundles(deps)?),
-        TraitLootboxes {} => to_binary(&query::trait_lootboxes(deps)?),
-        CharacterLootboxes {} => to_binary(&query::character_lootboxes(deps)?),
+        Traits { start_after, limit } => to_binary(&query::traits(deps, start_after, limit)?),
+        Characters { start_after, limit } => {
+            to_binary(&query::characters(deps, start_after, limit)?)
+        }
+        TraitsBy {
+            trait_type,
+            trait_value,
+            rarity,
+            denom,
+            start_after,
+            limit,
+        } => to_binary(&query::traits_by(
+            deps,
+            trait_type,
+            trait_value,
+            rarity,
+            denom,
+            start_after,
+            limit,
+        )?),
+        CharactersBy {
+            rarity,
+            denom,
+            start_after,
+            limit,
+        } => to_binary(&query::characters_by(
+            deps,
+            rarity,
+            denom,
+            start_after,
+            limit,
+        )?),
+        TraitBundles { start_after, limit } => {
+            to_binary(&query::trait_bundles(deps, start_after, limit)?)
+        }
+        CharacterBundles { start_after, limit } => {
+            to_binary(&query::character_bundles(deps, start_after, limit)?)
+        }
+        TraitLootboxes { start_after, limit } => {
+            to_binary(&query::trait_lootboxes(deps, start_after, limit)?)
+        }
+        CharacterLootboxes { start_after, limit } => {
+            to_binary(&query::character_lootboxes(deps, start_after, limit)?)
+        }
+        Trait { id } => to_binary(&query::trait_by_id(deps, id)?),
+        Character { id } => to_binary(&query::character_by_id(deps, id)?),
+        TraitBundle { id } => to_binary(&query::trait_bundle_by_id(deps, id)?),
+        CharacterBundle { id } => to_binary(&query::character_bundle_by_id(deps, id)?),
+        TraitLootbox { id } => to_binary(&query::trait_lootbox_by_id(deps, id)?),
+        CharacterLootbox { id } => to_binary(&query::character_lootbox_by_id(deps, id)?),
+        Recipe { id } => to_binary(&query::recipe_by_id(deps, id)?),
+        Recipes { start_after, limit } => to_binary(&query::recipes(deps, start_after, limit)?),
+        Supply { kind, id } => to_binary(&query::supply(deps, kind, id)?),
+        ActiveItems {
+            kind,
+            height,
+            time,
+            start_after,
+            limit,
+        } => to_binary(&query::active_items(
+            deps,
+            env,
+            kind,
+            height,
+            time,
+            start_after,
+            limit,
+        )?),
+        Managers {} => to_binary(&query::managers(deps)?),
+        Slots {} => to_binary(&query::slots(deps)?),
+        TraitType { trait_type } => to_binary(&query::trait_type(deps, trait_type)?),
+        TraitTypes { start_after, limit } => {
+            to_binary(&query::trait_types(deps, start_after, limit)?)
+        }
+        TraitValue {
+            trait_type,
+            trait_value,
+        } => to_binary(&query::trait_value(deps, trait_type, trait_value)?),
+        TraitValues {
+            trait_type,
+            start_after,
+            limit,
+        } => to_binary(&query::trait_values(deps, trait_type, start_after, limit)?),
     }
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    contract::migrate(deps, msg.managers)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -63,5 +149,19 @@ pub fn execute(
             new_character_lootboxes,
         } => add_character_lootboxes(deps, info, new_character_lootboxes),
         ExecuteMsg::RemoveCharacterLootboxes { ids } => remove_character_lootboxes(deps, info, ids),
+        ExecuteMsg::AddRecipes { new_recipes } => add_recipes(deps, info, new_recipes),
+        ExecuteMsg::UpdateRecipes { recipes } => update_recipes(deps, info, recipes),
+        ExecuteMsg::RemoveRecipes { ids } => remove_recipes(deps, info, ids),
+        ExecuteMsg::AddManagers { managers } => add_managers(deps, info, managers),
+        ExecuteMsg::RemoveManagers { managers } => remove_managers(deps, info, managers),
+        ExecuteMsg::SetSlots { slots } => set_slots(deps, info, slots),
+        ExecuteMsg::SetTraitTypes { trait_types } => set_trait_types(deps, info, trait_types),
+        ExecuteMsg::RemoveTraitTypes { trait_types } => remove_trait_types(deps, info, trait_types),
+        ExecuteMsg::SetTraitValues { trait_values } => set_trait_values(deps, info, trait_values),
+        ExecuteMsg::RemoveTraitValues { trait_values } => {
+            remove_trait_values(deps, info, trait_values)
+        }
+        ExecuteMsg::RecordMint { kind, id } => record_mint(deps, info, kind, id),
+        ExecuteMsg::ReleaseMint { kind, id } => release_mint(deps, info, kind, id),
     }
 }
@@ -1,28 +1,131 @@
 use cosmwasm_schema::cw_serde;
 use cosmwasm_schema::QueryResponses;
+use cosmwasm_std::{Addr, Timestamp};
 
 use crate::state::Character;
 use crate::state::CharacterBundle;
 use crate::state::CharacterLootbox;
+use crate::state::Recipe;
 use crate::state::Trait;
 use crate::state::TraitBundle;
 use crate::state::TraitLootbox;
+use crate::state::TraitTypeInfo;
+use crate::state::TraitValueInfo;
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
     #[returns(TraitsResp)]
-    Traits {},
+    Traits {
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
     #[returns(CharactersResp)]
-    Characters {},
+    Characters {
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    //Filters are optional and combined, results are ordered by id
+    #[returns(TraitsResp)]
+    TraitsBy {
+        trait_type: Option<String>,
+        trait_value: Option<String>,
+        rarity: Option<String>,
+        denom: Option<String>,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    #[returns(CharactersResp)]
+    CharactersBy {
+        rarity: Option<String>,
+        denom: Option<String>,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
     #[returns(TraitBundlesResp)]
-    TraitBundles {},
+    TraitBundles {
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
     #[returns(CharacterBundlesResp)]
-    CharacterBundles {},
+    CharacterBundles {
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
     #[returns(TraitLootboxesResp)]
-    TraitLootboxes {},
+    TraitLootboxes {
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
     #[returns(CharacterLootboxesResp)]
-    CharacterLootboxes {},
+    CharacterLootboxes {
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    #[returns(Trait)]
+    Trait { id: u32 },
+    #[returns(Character)]
+    Character { id: u32 },
+    #[returns(TraitBundle)]
+    TraitBundle { id: u32 },
+    #[returns(CharacterBundle)]
+    CharacterBundle { id: u32 },
+    #[returns(TraitLootbox)]
+    TraitLootbox { id: u32 },
+    #[returns(CharacterLootbox)]
+    CharacterLootbox { id: u32 },
+    #[returns(Recipe)]
+    Recipe { id: u32 },
+    #[returns(RecipesResp)]
+    Recipes {
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    #[returns(SupplyResp)]
+    Supply { kind: MintableKind, id: u32 },
+    //Ids on sale and not sold out, height and time default to the current block
+    #[returns(ActiveItemsResp)]
+    ActiveItems {
+        kind: MintableKind,
+        height: Option<u64>,
+        time: Option<Timestamp>,
+        start_after: Option<u32>,
+        limit: Option<u32>,
+    },
+    #[returns(ManagersResp)]
+    Managers {},
+    #[returns(SlotsResp)]
+    Slots {},
+    #[returns(TraitTypeInfo)]
+    TraitType { trait_type: String },
+    #[returns(TraitTypesResp)]
+    TraitTypes {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    //Fails when the value is not registered for the type, used by the managers and the trait collection
+    #[returns(TraitValueInfo)]
+    TraitValue {
+        trait_type: String,
+        trait_value: String,
+    },
+    #[returns(TraitValuesResp)]
+    TraitValues {
+        trait_type: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+//Kinds of catalog entries that keep track of their supply
+#[cw_serde]
+pub enum MintableKind {
+    Trait,
+    Character,
+    TraitBundle,
+    CharacterBundle,
+    TraitLootbox,
+    CharacterLootbox,
 }
 
 #[cw_serde]
@@ -53,7 +156,56 @@ pub struct CharacterLootboxesResp {
 }
 
 #[cw_serde]
-pub struct InstantiateMsg {}
+pub struct RecipesResp {
+    pub recipes: Vec<Recipe>,
+}
+
+#[cw_serde]
+pub struct SupplyResp {
+    pub max_supply: Option<u32>,
+    pub minted: u32,
+    //None when the supply is unlimited
+    pub remaining: Option<u32>,
+}
+
+#[cw_serde]
+pub struct ActiveItemsResp {
+    pub ids: Vec<u32>,
+}
+
+#[cw_serde]
+pub struct ManagersResp {
+    pub managers: Vec<Addr>,
+}
+
+#[cw_serde]
+pub struct SlotsResp {
+    pub slots: Vec<String>,
+}
+
+#[cw_serde]
+pub struct TraitTypesResp {
+    pub trait_types: Vec<TraitTypeInfo>,
+}
+
+#[cw_serde]
+pub struct TraitValuesResp {
+    pub trait_values: Vec<TraitValueInfo>,
+}
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    //Trait slots of the characters, in the order they are kept on them
+    #[serde(default)]
+    pub slots: Vec<String>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    //Managers allowed to record mints, added to the stored ones. Contracts migrated from 0.1.0 have none
+    #[serde(default)]
+    pub managers: Vec<String>,
+}
 
 #[cw_serde]
 pub enum ExecuteMsg {
@@ -93,4 +245,50 @@ pub enum ExecuteMsg {
     RemoveCharacterLootboxes {
         ids: Vec<u32>,
     },
+    AddRecipes {
+        new_recipes: Vec<Recipe>,
+    },
+    //Replaces recipes that already exist
+    UpdateRecipes {
+        recipes: Vec<Recipe>,
+    },
+    RemoveRecipes {
+        ids: Vec<u32>,
+    },
+    AddManagers {
+        managers: Vec<String>,
+    },
+    RemoveManagers {
+        managers: Vec<String>,
+    },
+    //Replaces the registry of trait slots, characters added later must only use these
+    SetSlots {
+        slots: Vec<String>,
+    },
+    //Adds trait types or replaces the ones already registered
+    SetTraitTypes {
+        trait_types: Vec<TraitTypeInfo>,
+    },
+    //Removing a type also removes its values
+    RemoveTraitTypes {
+        trait_types: Vec<String>,
+    },
+    //Adds values of registered types or replaces the ones already registered
+    SetTraitValues {
+        trait_values: Vec<TraitValueInfo>,
+    },
+    //Pairs of trait type and value
+    RemoveTraitValues {
+        trait_values: Vec<(String, String)>,
+    },
+    //Sent by the managers when they mint an entry, fails once the entry is sold out
+    RecordMint {
+        kind: MintableKind,
+        id: u32,
+    },
+    //Sent by the managers when a recorded mint is refunded, which gives its supply back
+    ReleaseMint {
+        kind: MintableKind,
+        id: u32,
+    },
 }
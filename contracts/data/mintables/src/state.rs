@@ -1,5 +1,6 @@
-use cosmwasm_std::Coin;
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, BlockInfo, Coin};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw_utils::Scheduled;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -10,22 +11,48 @@ pub struct Trait {
     pub trait_type: String,
     pub trait_value: String,
     pub trait_rarity: String,
-    pub mint_price: Coin,
+    //When empty the managers charge the price they have configured for the rarity
+    #[serde(default)]
+    pub mint_price: Option<Coin>,
+    //Optional cap on how many can be sold, the minted counter is kept by the managers
+    #[serde(default)]
+    pub max_supply: Option<u32>,
+    #[serde(default)]
+    pub minted: u32,
+    //Optional cap on how many a single address can buy, enforced by the managers
+    #[serde(default)]
+    pub per_address_limit: Option<u32>,
+    //Optional sale window, by block time or height
+    #[serde(default)]
+    pub sale_start: Option<Scheduled>,
+    #[serde(default)]
+    pub sale_end: Option<Scheduled>,
 }
 
 //Premade Character structure
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Character {
     pub id: u32,
-    pub ears: Option<String>,
-    pub eyes: Option<String>,
-    pub mouth: Option<String>,
-    pub fur_type: Option<String>,
-    pub fur_color: Option<String>,
-    pub tail_shape: Option<String>,
+    //Slot and value of each filled slot, kept in the order of the slot registry
+    pub traits: Vec<(String, String)>,
     pub rarity: String,
     pub locked: bool,
-    pub mint_price: Coin,
+    //When empty the managers charge the price they have configured for the rarity
+    #[serde(default)]
+    pub mint_price: Option<Coin>,
+    //Optional cap on how many can be sold, the minted counter is kept by the managers
+    #[serde(default)]
+    pub max_supply: Option<u32>,
+    #[serde(default)]
+    pub minted: u32,
+    //Optional cap on how many a single address can buy, enforced by the managers
+    #[serde(default)]
+    pub per_address_limit: Option<u32>,
+    //Optional sale window, by block time or height
+    #[serde(default)]
+    pub sale_start: Option<Scheduled>,
+    #[serde(default)]
+    pub sale_end: Option<Scheduled>,
 }
 
 //Trait bundle structure
@@ -34,6 +61,19 @@ pub struct TraitBundle {
     pub id: u32,
     pub traits: Vec<Trait>,
     pub mint_price: Coin,
+    //Optional cap on how many can be sold, the minted counter is kept by the managers
+    #[serde(default)]
+    pub max_supply: Option<u32>,
+    #[serde(default)]
+    pub minted: u32,
+    //Optional cap on how many a single address can buy, enforced by the managers
+    #[serde(default)]
+    pub per_address_limit: Option<u32>,
+    //Optional sale window, by block time or height
+    #[serde(default)]
+    pub sale_start: Option<Scheduled>,
+    #[serde(default)]
+    pub sale_end: Option<Scheduled>,
 }
 
 //Character bundle structure
@@ -42,6 +82,19 @@ pub struct CharacterBundle {
     pub id: u32,
     pub characters: Vec<Character>,
     pub mint_price: Coin,
+    //Optional cap on how many can be sold, the minted counter is kept by the managers
+    #[serde(default)]
+    pub max_supply: Option<u32>,
+    #[serde(default)]
+    pub minted: u32,
+    //Optional cap on how many a single address can buy, enforced by the managers
+    #[serde(default)]
+    pub per_address_limit: Option<u32>,
+    //Optional sale window, by block time or height
+    #[serde(default)]
+    pub sale_start: Option<Scheduled>,
+    #[serde(default)]
+    pub sale_end: Option<Scheduled>,
 }
 
 //Trait Lootbox structure
@@ -51,6 +104,19 @@ pub struct TraitLootbox {
     pub traits: Vec<Trait>,
     pub possibilities: Vec<u32>,
     pub mint_price: Coin,
+    //Optional cap on how many can be sold, the minted counter is kept by the managers
+    #[serde(default)]
+    pub max_supply: Option<u32>,
+    #[serde(default)]
+    pub minted: u32,
+    //Optional cap on how many a single address can buy, enforced by the managers
+    #[serde(default)]
+    pub per_address_limit: Option<u32>,
+    //Optional sale window, by block time or height
+    #[serde(default)]
+    pub sale_start: Option<Scheduled>,
+    #[serde(default)]
+    pub sale_end: Option<Scheduled>,
 }
 
 //Character Lootbox structure
@@ -60,11 +126,423 @@ pub struct CharacterLootbox {
     pub characters: Vec<Character>,
     pub possibilities: Vec<u32>,
     pub mint_price: Coin,
+    //Optional cap on how many can be sold, the minted counter is kept by the managers
+    #[serde(default)]
+    pub max_supply: Option<u32>,
+    #[serde(default)]
+    pub minted: u32,
+    //Optional cap on how many a single address can buy, enforced by the managers
+    #[serde(default)]
+    pub per_address_limit: Option<u32>,
+    //Optional sale window, by block time or height
+    #[serde(default)]
+    pub sale_start: Option<Scheduled>,
+    #[serde(default)]
+    pub sale_end: Option<Scheduled>,
+}
+
+//Trait type accepted by the managers and the trait collection, with what clients show for it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TraitTypeInfo {
+    pub trait_type: String,
+    pub display_name: String,
+    //Key clients resolve to an image, e.g. a file name under their asset base URL
+    pub image_key: String,
+}
+
+//Value accepted for a registered trait type
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TraitValueInfo {
+    pub trait_type: String,
+    pub trait_value: String,
+    pub display_name: String,
+    pub image_key: String,
+}
+
+//One group of inputs of a crafting recipe, e.g. 3 common eyes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecipeInput {
+    pub trait_type: String,
+    //Any value of the type is accepted when empty
+    #[serde(default)]
+    pub trait_value: Option<String>,
+    pub trait_rarity: String,
+    pub amount: u32,
+}
+
+//What a crafting recipe mints
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RecipeOutput {
+    //The catalog trait with this id
+    Trait {
+        id: u32,
+    },
+    //Any catalog trait of this type and rarity that is not sold out, drawn by the manager
+    Random {
+        trait_type: String,
+        trait_rarity: String,
+    },
+}
+
+//Crafting recipe structure, the traits matching the inputs are burnt to mint the output
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Recipe {
+    pub id: u32,
+    pub inputs: Vec<RecipeInput>,
+    pub output: RecipeOutput,
+}
+
+impl Recipe {
+    pub fn input_count(&self) -> u32 {
+        self.inputs.iter().map(|input| input.amount).sum()
+    }
+}
+
+//Trait slots of the characters up to 0.2.0, when each one was its own field
+pub const LEGACY_SLOTS: [&str; 6] = [
+    "ears",
+    "eyes",
+    "mouth",
+    "fur_type",
+    "fur_color",
+    "tail_shape",
+];
+
+//Character layout stored up to 0.2.0, only read to migrate the catalog
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyCharacter {
+    pub id: u32,
+    pub ears: Option<String>,
+    pub eyes: Option<String>,
+    pub mouth: Option<String>,
+    pub fur_type: Option<String>,
+    pub fur_color: Option<String>,
+    pub tail_shape: Option<String>,
+    pub rarity: String,
+    pub locked: bool,
+    #[serde(default)]
+    pub mint_price: Option<Coin>,
+    #[serde(default)]
+    pub max_supply: Option<u32>,
+    #[serde(default)]
+    pub minted: u32,
+    #[serde(default)]
+    pub per_address_limit: Option<u32>,
+    #[serde(default)]
+    pub sale_start: Option<Scheduled>,
+    #[serde(default)]
+    pub sale_end: Option<Scheduled>,
+}
+
+impl From<LegacyCharacter> for Character {
+    fn from(legacy: LegacyCharacter) -> Self {
+        let values = [
+            legacy.ears,
+            legacy.eyes,
+            legacy.mouth,
+            legacy.fur_type,
+            legacy.fur_color,
+            legacy.tail_shape,
+        ];
+        Character {
+            id: legacy.id,
+            traits: LEGACY_SLOTS
+                .iter()
+                .zip(values)
+                .filter_map(|(slot, value)| value.map(|value| (slot.to_string(), value)))
+                .collect(),
+            rarity: legacy.rarity,
+            locked: legacy.locked,
+            mint_price: legacy.mint_price,
+            max_supply: legacy.max_supply,
+            minted: legacy.minted,
+            per_address_limit: legacy.per_address_limit,
+            sale_start: legacy.sale_start,
+            sale_end: legacy.sale_end,
+        }
+    }
+}
+
+//Character bundle layout stored up to 0.2.0
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyCharacterBundle {
+    pub id: u32,
+    pub characters: Vec<LegacyCharacter>,
+    pub mint_price: Coin,
+    #[serde(default)]
+    pub max_supply: Option<u32>,
+    #[serde(default)]
+    pub minted: u32,
+    #[serde(default)]
+    pub per_address_limit: Option<u32>,
+    #[serde(default)]
+    pub sale_start: Option<Scheduled>,
+    #[serde(default)]
+    pub sale_end: Option<Scheduled>,
+}
+
+impl From<LegacyCharacterBundle> for CharacterBundle {
+    fn from(legacy: LegacyCharacterBundle) -> Self {
+        CharacterBundle {
+            id: legacy.id,
+            characters: legacy.characters.into_iter().map(Character::from).collect(),
+            mint_price: legacy.mint_price,
+            max_supply: legacy.max_supply,
+            minted: legacy.minted,
+            per_address_limit: legacy.per_address_limit,
+            sale_start: legacy.sale_start,
+            sale_end: legacy.sale_end,
+        }
+    }
+}
+
+//Character lootbox layout stored up to 0.2.0
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyCharacterLootbox {
+    pub id: u32,
+    pub characters: Vec<LegacyCharacter>,
+    pub possibilities: Vec<u32>,
+    pub mint_price: Coin,
+    #[serde(default)]
+    pub max_supply: Option<u32>,
+    #[serde(default)]
+    pub minted: u32,
+    #[serde(default)]
+    pub per_address_limit: Option<u32>,
+    #[serde(default)]
+    pub sale_start: Option<Scheduled>,
+    #[serde(default)]
+    pub sale_end: Option<Scheduled>,
 }
 
-pub const TRAITS: Item<Vec<Trait>> = Item::new("traits");
-pub const CHARACTERS: Item<Vec<Character>> = Item::new("characters");
-pub const TRAIT_BUNDLES: Item<Vec<TraitBundle>> = Item::new("trait_bundle");
-pub const CHARACTER_BUNDLES: Item<Vec<CharacterBundle>> = Item::new("character_bundle");
-pub const TRAIT_LOOTBOXES: Item<Vec<TraitLootbox>> = Item::new("trait_lootboxes");
-pub const CHARACTER_LOOTBOXES: Item<Vec<CharacterLootbox>> = Item::new("character_lootboxes");
+impl From<LegacyCharacterLootbox> for CharacterLootbox {
+    fn from(legacy: LegacyCharacterLootbox) -> Self {
+        CharacterLootbox {
+            id: legacy.id,
+            characters: legacy.characters.into_iter().map(Character::from).collect(),
+            possibilities: legacy.possibilities,
+            mint_price: legacy.mint_price,
+            max_supply: legacy.max_supply,
+            minted: legacy.minted,
+            per_address_limit: legacy.per_address_limit,
+            sale_start: legacy.sale_start,
+            sale_end: legacy.sale_end,
+        }
+    }
+}
+
+/// Supply tracking shared by all the catalog entries
+pub trait Supply {
+    fn id(&self) -> u32;
+    fn max_supply(&self) -> Option<u32>;
+    fn minted(&self) -> u32;
+    fn per_address_limit(&self) -> Option<u32>;
+    fn record_mint(&mut self);
+    fn release_mint(&mut self);
+
+    fn remaining_supply(&self) -> Option<u32> {
+        self.max_supply()
+            .map(|max_supply| max_supply.saturating_sub(self.minted()))
+    }
+
+    fn is_sold_out(&self) -> bool {
+        self.remaining_supply() == Some(0)
+    }
+
+    //Whether an address that bought this entry `minted` times can buy it again
+    fn address_allows(&self, minted: u32) -> bool {
+        self.per_address_limit()
+            .map_or(true, |limit| minted < limit)
+    }
+}
+
+/// Sale window shared by all the catalog entries
+pub trait SaleWindow {
+    fn sale_start(&self) -> Option<Scheduled>;
+    fn sale_end(&self) -> Option<Scheduled>;
+
+    //The window opens at sale_start and closes at sale_end, a missing bound leaves that side open
+    fn is_on_sale(&self, block: &BlockInfo) -> bool {
+        let started = match self.sale_start() {
+            Some(start) => start.is_triggered(block),
+            None => true,
+        };
+        let ended = match self.sale_end() {
+            Some(end) => end.is_triggered(block),
+            None => false,
+        };
+        started && !ended
+    }
+}
+
+macro_rules! impl_catalog_entry {
+    ($($item:ty),*) => {
+        $(
+            impl Supply for $item {
+                fn id(&self) -> u32 {
+                    self.id
+                }
+
+                fn max_supply(&self) -> Option<u32> {
+                    self.max_supply
+                }
+
+                fn minted(&self) -> u32 {
+                    self.minted
+                }
+
+                fn per_address_limit(&self) -> Option<u32> {
+                    self.per_address_limit
+                }
+
+                fn record_mint(&mut self) {
+                    self.minted += 1;
+                }
+
+                fn release_mint(&mut self) {
+                    self.minted = self.minted.saturating_sub(1);
+                }
+            }
+
+            impl SaleWindow for $item {
+                fn sale_start(&self) -> Option<Scheduled> {
+                    self.sale_start
+                }
+
+                fn sale_end(&self) -> Option<Scheduled> {
+                    self.sale_end
+                }
+            }
+        )*
+    };
+}
+
+impl_catalog_entry!(
+    Trait,
+    Character,
+    TraitBundle,
+    CharacterBundle,
+    TraitLootbox,
+    CharacterLootbox,
+    LegacyCharacterBundle,
+    LegacyCharacterLootbox
+);
+
+//Entries priced by rarity are indexed under an empty denom
+fn price_denom(price: &Option<Coin>) -> String {
+    price
+        .as_ref()
+        .map(|price| price.denom.clone())
+        .unwrap_or_default()
+}
+
+pub struct TraitIndexes<'a> {
+    pub trait_type: MultiIndex<'a, String, Trait, u32>,
+    pub rarity: MultiIndex<'a, String, Trait, u32>,
+    pub denom: MultiIndex<'a, String, Trait, u32>,
+    //Type, value and rarity, used by the trait manager to find the trait being minted
+    pub attributes: MultiIndex<'a, (String, String, String), Trait, u32>,
+}
+
+impl<'a> IndexList<Trait> for TraitIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Trait>> + '_> {
+        let v: Vec<&dyn Index<Trait>> = vec![
+            &self.trait_type,
+            &self.rarity,
+            &self.denom,
+            &self.attributes,
+        ];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn trait_catalog<'a>() -> IndexedMap<'a, u32, Trait, TraitIndexes<'a>> {
+    let indexes = TraitIndexes {
+        trait_type: MultiIndex::new(
+            |_pk, t: &Trait| t.trait_type.clone(),
+            "traits",
+            "traits__trait_type",
+        ),
+        rarity: MultiIndex::new(
+            |_pk, t: &Trait| t.trait_rarity.clone(),
+            "traits",
+            "traits__rarity",
+        ),
+        denom: MultiIndex::new(
+            |_pk, t: &Trait| price_denom(&t.mint_price),
+            "traits",
+            "traits__denom",
+        ),
+        attributes: MultiIndex::new(
+            |_pk, t: &Trait| {
+                (
+                    t.trait_type.clone(),
+                    t.trait_value.clone(),
+                    t.trait_rarity.clone(),
+                )
+            },
+            "traits",
+            "traits__attributes",
+        ),
+    };
+    IndexedMap::new("traits", indexes)
+}
+
+pub struct CharacterIndexes<'a> {
+    pub rarity: MultiIndex<'a, String, Character, u32>,
+    pub denom: MultiIndex<'a, String, Character, u32>,
+}
+
+impl<'a> IndexList<Character> for CharacterIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Character>> + '_> {
+        let v: Vec<&dyn Index<Character>> = vec![&self.rarity, &self.denom];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn character_catalog<'a>() -> IndexedMap<'a, u32, Character, CharacterIndexes<'a>> {
+    let indexes = CharacterIndexes {
+        rarity: MultiIndex::new(
+            |_pk, c: &Character| c.rarity.clone(),
+            "characters",
+            "characters__rarity",
+        ),
+        denom: MultiIndex::new(
+            |_pk, c: &Character| price_denom(&c.mint_price),
+            "characters",
+            "characters__denom",
+        ),
+    };
+    IndexedMap::new("characters", indexes)
+}
+
+//Catalog entries keyed by their id
+pub const TRAIT_BUNDLES: Map<u32, TraitBundle> = Map::new("trait_bundles");
+pub const CHARACTER_BUNDLES: Map<u32, CharacterBundle> = Map::new("character_bundles");
+pub const TRAIT_LOOTBOXES: Map<u32, TraitLootbox> = Map::new("trait_lootboxes");
+pub const CHARACTER_LOOTBOXES: Map<u32, CharacterLootbox> = Map::new("character_lootboxes");
+pub const RECIPES: Map<u32, Recipe> = Map::new("recipes");
+//Trait slots a character can have, in the order they are kept on the characters
+pub const SLOTS: Item<Vec<String>> = Item::new("slots");
+//Registry of the trait types and values that can be minted, character slots are filled with these
+pub const TRAIT_TYPES: Map<&str, TraitTypeInfo> = Map::new("trait_types");
+pub const TRAIT_VALUES: Map<(&str, &str), TraitValueInfo> = Map::new("trait_values");
+
+//Up to 0.1.0 each catalog was stored as a single vector, only read to migrate them
+pub const LEGACY_TRAITS: Item<Vec<Trait>> = Item::new("traits");
+pub const LEGACY_CHARACTERS: Item<Vec<LegacyCharacter>> = Item::new("characters");
+pub const LEGACY_TRAIT_BUNDLES: Item<Vec<TraitBundle>> = Item::new("trait_bundle");
+pub const LEGACY_CHARACTER_BUNDLES: Item<Vec<LegacyCharacterBundle>> =
+    Item::new("character_bundle");
+pub const LEGACY_TRAIT_LOOTBOXES: Item<Vec<TraitLootbox>> = Item::new("trait_lootboxes");
+pub const LEGACY_CHARACTER_LOOTBOXES: Item<Vec<LegacyCharacterLootbox>> =
+    Item::new("character_lootboxes");
+//Up to 0.2.0 the characters had one field per slot, only read to migrate them
+pub const LEGACY_CHARACTER_CATALOG: Map<u32, LegacyCharacter> = Map::new("characters");
+pub const LEGACY_CHARACTER_BUNDLE_CATALOG: Map<u32, LegacyCharacterBundle> =
+    Map::new("character_bundles");
+pub const LEGACY_CHARACTER_LOOTBOX_CATALOG: Map<u32, LegacyCharacterLootbox> =
+    Map::new("character_lootboxes");
+//Managers allowed to record mints against the supply of the catalog entries
+pub const MANAGERS: Item<Vec<Addr>> = Item::new("managers");
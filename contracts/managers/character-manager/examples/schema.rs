@@ -1,4 +1,4 @@
-use character_manager::msg::ExecuteMsg;
+use character_manager::msg::{ExecuteMsg, MigrateMsg};
 use cosmwasm_std::Empty;
 use utils::msg::BaseCharacterManagerCreateMsg as InstantiateMsg;
 use utils::query::CharacterManagerQueryMsg as QueryMsg;
@@ -9,5 +9,6 @@ fn main() {
         instantiate: InstantiateMsg<Empty>,
         execute: ExecuteMsg,
         query: QueryMsg,
+        migrate: MigrateMsg,
     }
 }
\ No newline at end of file
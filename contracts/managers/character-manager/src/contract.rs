@@ -1,36 +1,66 @@
 use crate::{
-    msg::ExecuteMsg,
+    msg::{ExecuteMsg, MigrateMsg, ReceiveMsg},
     state::{
-        increment_token_index, Config, COLLECTION_ADDRESS, CONFIG, MINTABLE_COLLECTION_ADDRESS,
-        TRAIT_COLLECTION_ADDRESS,
+        increment_commitment_index, increment_token_index, record_block_entropy, Config,
+        DismantleCommitment, FusionCommitment, Lineage, LootboxCommitment, Moderation,
+        ADDRESS_MINTS, BLOCKED_NAMES, BLOCK_ENTROPY, BLOCK_MINTS, BLOCK_TOTAL_MINTS, BUNDLE_MINTS,
+        CHARACTER_MINTS, COLLECTION_ADDRESS, CONFIG, DISMANTLE_COMMITMENTS, FUSION_COMMITMENTS,
+        GUARDIAN, LAST_RENAMES, LEGACY_CONFIG, LEGACY_LOOTBOX_COMMITMENTS, LINEAGE,
+        LOOTBOX_COMMITMENTS, LOOTBOX_MINTS, MINTABLE_COLLECTION_ADDRESS, MODERATION_HISTORY,
+        MODERATORS, NAMES, PAUSED, PENDING_CHARACTERS, PENDING_ENTROPY, PRESALE_MINTS,
+        PRESALE_PHASES, REVENUE, TRAIT_COLLECTION_ADDRESS,
     },
     ContractError,
 };
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    coins, to_binary, Addr, BankMsg, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo,
-    Reply, Response, StdResult, SubMsg, WasmMsg,
+    coin, from_binary, to_binary, Addr, Api, Binary, BlockInfo, Coin, CosmosMsg, Deps, DepsMut,
+    Empty, Env, Event, HexBinary, MessageInfo, Order, QuerierWrapper, Reply, Response, StdResult,
+    Storage, SubMsg, Uint128, WasmMsg,
 };
-use cw2::set_contract_version;
-use cw721::{Cw721QueryMsg, NftInfoResponse, TokensResponse};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::Cw20ReceiveMsg;
+use cw721::{AllNftInfoResponse, Cw721QueryMsg, OwnerOfResponse, TokensResponse};
 use cw721_character_onchain::{
     msg::{CharacterInfoResponse, Extension, Metadata},
     ExecuteMsg as CharacterExecuteMsg, InstantiateMsg, QueryMsg as CharacterQueryMsg,
 };
 use cw721_trait_onchain::{msg::Extension as TraitExtension, ExecuteMsg as TraitExecuteMsg};
+use cw_storage_plus::Map;
 use cw_utils::{one_coin, parse_reply_instantiate_data};
-use mintables::msg::{CharacterBundlesResp, CharacterLootboxesResp, CharactersResp, QueryMsg};
+use mintables::msg::{
+    ExecuteMsg as MintablesExecuteMsg, MintableKind, QueryMsg, SlotsResp, TraitsResp,
+};
+use mintables::state::{
+    Character, CharacterBundle, CharacterLootbox, SaleWindow, Supply, TraitValueInfo,
+};
+use semver::Version;
 use sha2::{Digest, Sha256};
 use utils::{
-    msg::{BaseCharacterManagerCreateMsg, UpdateCharacterManagerParamsMsg},
-    query::{AllowedCollectionCodeIdResponse, CharacterManagerConfigResponse, CharacterManagerQueryMsg},
-    U64Ext, NATIVE_DENOM,
+    block_mints,
+    merkle::parse_hash,
+    msg::{
+        BaseCharacterManagerCreateMsg, RandomnessProxyExecuteMsg, TraitManagerMintMsg,
+        UpdateCharacterManagerParamsMsg,
+    },
+    query::{
+        AllowedCollectionCodeIdResponse, CharacterManagerConfigResponse, CharacterManagerQueryMsg,
+        LineageResponse, LootboxCommitmentResponse, MintAllowanceResponse, ModerationResponse,
+        PauseStatusResponse, PresaleMintsResponse, ResolveNameResponse, RevenueResponse, SaleItem,
+    },
+    random::{drawn_position, random_number_1_to_x},
+    DismantleConfig, FusionConfig, MintLimits, NameConfig, Operation, Payment, PresalePhase,
+    RandomnessSource, RevenueSplit, RevenueTotals,
 };
 
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 const INSTANTIATE_CW721_REPLY_ID: u64 = 1;
+/// Number of blocks after a lootbox, fusion or dismantle commitment in which its secret can be revealed
+pub const REVEAL_TIMEOUT: u64 = 100;
+/// Page size used when scanning the mintables catalog
+const CATALOG_PAGE_LIMIT: u32 = 100;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -47,42 +77,10 @@ pub fn instantiate(
         Some(&info.sender.clone().into_string()),
     )?;
 
-    let range = 0..100;
-    if !range.contains(&msg.manager_params.burn_ratio) {
-        return Err(ContractError::InvalidBurnRatio {});
-    }
-
-    //If mint price is not fully burned then there must be a valid destination address to send funds to
-    if msg.manager_params.burn_ratio != 100 && msg.manager_params.destination.is_none() {
-        return Err(ContractError::NoMintDestination {});
-    }
-
-    //If we are selling anything using a non native denom, we need a destination address as we will not burn those non native tokens.
-    if msg.manager_params.empty_character_mint_price.denom != NATIVE_DENOM
-        && msg.manager_params.destination.is_none()
-    {
-        return Err(ContractError::NoMintDestination {});
-    }
-
-    if msg
-        .manager_params
-        .character_mint_prices
-        .iter()
-        .any(|coin| coin.denom != NATIVE_DENOM)
-        && msg.manager_params.destination.is_none()
-    {
-        return Err(ContractError::NoMintDestination {});
-    }
-
-    if msg.manager_params.destination.is_some() {
-        deps.api.addr_validate(
-            &msg.manager_params
-                .destination
-                .clone()
-                .unwrap()
-                .into_string(),
-        )?;
-    }
+    validate_revenue_split(deps.api, &msg.manager_params.revenue_split)?;
+    validate_fusion(&msg.manager_params.fusion)?;
+    validate_dismantle(deps.api, &msg.manager_params.dismantle)?;
+    validate_names(&msg.manager_params.names)?;
 
     //The mint prices and rarities arrays must be same length, 1-to-1 price/rarity
     if msg.manager_params.character_mint_prices.len() != msg.manager_params.character_rarities.len()
@@ -90,11 +88,22 @@ pub fn instantiate(
         return Err(ContractError::NotSameLength {});
     }
 
+    //The randomness beacon proxy must be a valid address as it is the only one allowed to deliver randomness
+    if let RandomnessSource::Beacon { proxy_addr } = &msg.manager_params.randomness_source {
+        deps.api.addr_validate(proxy_addr.as_str())?;
+    }
+
     let config = Config {
         collection_code_id: msg.collection_params.code_id,
         empty_character_mint_price: msg.manager_params.empty_character_mint_price,
-        burn_ratio: msg.manager_params.burn_ratio,
-        destination: msg.manager_params.destination,
+        character_mint_prices: msg.manager_params.character_mint_prices,
+        character_rarities: msg.manager_params.character_rarities,
+        revenue_split: msg.manager_params.revenue_split,
+        mint_limits: msg.manager_params.mint_limits,
+        randomness_source: msg.manager_params.randomness_source,
+        fusion: msg.manager_params.fusion,
+        dismantle: msg.manager_params.dismantle,
+        names: msg.manager_params.names,
         extension: Empty {},
     };
 
@@ -125,6 +134,7 @@ pub fn instantiate(
             symbol: msg.collection_params.symbol.unwrap(),
             minter: env.contract.address.to_string(),
             collection_info,
+            image_template: msg.collection_params.image_template.clone(),
         })?,
         funds: info.funds,
         admin: None,
@@ -151,42 +161,255 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    record_block_entropy(deps.storage, &env.block)?;
     match msg {
-        ExecuteMsg::Mint { token_info } => {
-            mint(deps, info.clone(), token_info, info.sender.into_string())
+        ExecuteMsg::Mint {
+            character_id,
+            token_info,
+        } => {
+            let payment = Payment::Native(one_coin(&info)?);
+            let receiver = info.sender.to_string();
+            mint(
+                deps,
+                info,
+                env,
+                payment,
+                character_id,
+                token_info,
+                receiver,
+                None,
+            )
         }
         ExecuteMsg::MintTo {
+            character_id,
             token_info,
             receiver,
-        } => mint(deps, info, token_info, receiver),
+        } => {
+            let payment = Payment::Native(one_coin(&info)?);
+            mint(
+                deps,
+                info,
+                env,
+                payment,
+                character_id,
+                token_info,
+                receiver,
+                None,
+            )
+        }
+        ExecuteMsg::MintWithProof {
+            phase_id,
+            proof,
+            character_id,
+            token_info,
+            receiver,
+        } => {
+            let payment = Payment::Native(one_coin(&info)?);
+            let receiver = receiver.unwrap_or(info.sender.to_string());
+            let presale = Some((phase_id, proof));
+            mint(
+                deps,
+                info,
+                env,
+                payment,
+                character_id,
+                token_info,
+                receiver,
+                presale,
+            )
+        }
         ExecuteMsg::MintBundle {
             bundle_id,
             receiver,
-        } => mint_bundle(deps, info, bundle_id, receiver),
+        } => {
+            let payment = Payment::Native(one_coin(&info)?);
+            mint_bundle(deps, info, env, payment, bundle_id, receiver)
+        }
         ExecuteMsg::OpenLootbox {
             lootbox_id,
             receiver,
-        } => open_lootbox(deps, info, env, lootbox_id, receiver),
-        ExecuteMsg::ChangeName { token_id, new_name } => change_name(deps, info, token_id, new_name),
+            commitment,
+        } => {
+            let payment = Payment::Native(one_coin(&info)?);
+            open_lootbox(deps, info, env, payment, lootbox_id, receiver, commitment)
+        }
+        ExecuteMsg::Receive(wrapper) => receive_cw20(deps, env, info, wrapper),
+        ExecuteMsg::RevealLootbox {
+            commitment_id,
+            secret,
+        } => reveal_lootbox(deps, env, commitment_id, secret),
+        ExecuteMsg::ResolveExpiredLootbox { commitment_id } => {
+            resolve_expired_lootbox(deps, env, commitment_id)
+        }
+        ExecuteMsg::ReceiveRandomness { job_id, randomness } => {
+            receive_randomness(deps, info, job_id, randomness)
+        }
+        ExecuteMsg::ChangeName { token_id, new_name } => {
+            let payment = if info.funds.is_empty() {
+                None
+            } else {
+                Some(Payment::Native(one_coin(&info)?))
+            };
+            change_name(deps, info, env, payment, token_id, new_name)
+        }
         ExecuteMsg::ModifyCharacter {
             token_id,
             trait_ids,
         } => modify_character(deps, info, token_id, trait_ids),
+        ExecuteMsg::UnequipTraits {
+            token_id,
+            trait_types,
+        } => unequip_traits(deps, info, token_id, trait_types),
+        ExecuteMsg::SwapTrait { token_id, trait_id } => swap_trait(deps, info, token_id, trait_id),
         ExecuteMsg::LockCharacter { token_id } => lock_character(deps, info, token_id),
+        ExecuteMsg::Fuse {
+            parent_a,
+            parent_b,
+            commitment,
+        } => fuse(deps, info, env, parent_a, parent_b, commitment),
+        ExecuteMsg::RevealFusion {
+            commitment_id,
+            secret,
+        } => reveal_fusion(deps, env, commitment_id, secret),
+        ExecuteMsg::ResolveExpiredFusion { commitment_id } => {
+            resolve_expired_fusion(deps, env, commitment_id)
+        }
+        ExecuteMsg::Dismantle {
+            token_id,
+            commitment,
+        } => dismantle(deps, info, env, token_id, commitment),
+        ExecuteMsg::RevealDismantle {
+            commitment_id,
+            secret,
+        } => reveal_dismantle(deps, env, commitment_id, secret),
+        ExecuteMsg::ResolveExpiredDismantle { commitment_id } => {
+            resolve_expired_dismantle(deps, env, commitment_id)
+        }
         ExecuteMsg::UpdateConfig { new_config } => update_config(deps, info, new_config),
+        ExecuteMsg::SetPresalePhase { phase_id, phase } => {
+            set_presale_phase(deps, info, phase_id, phase)
+        }
+        ExecuteMsg::Withdraw { denom, amount, to } => withdraw(deps, info, denom, amount, to),
+        ExecuteMsg::Pause { operations } => set_paused(deps, info, operations, true),
+        ExecuteMsg::Unpause { operations } => set_paused(deps, info, operations, false),
+        ExecuteMsg::SetGuardian { guardian } => set_guardian(deps, info, guardian),
+        ExecuteMsg::AddModerators { moderators } => add_moderators(deps, info, moderators),
+        ExecuteMsg::RemoveModerators { moderators } => remove_moderators(deps, info, moderators),
+        ExecuteMsg::ForceResetName { token_id, reason } => {
+            force_reset_name(deps, info, env, token_id, reason)
+        }
+        ExecuteMsg::UnblockName { name } => unblock_name(deps, info, name),
+        ExecuteMsg::IndexNames { start_after, limit } => {
+            index_names(deps, info, start_after, limit)
+        }
         ExecuteMsg::UpdateOwnership(action) => update_ownership(deps, env, info, action),
     }
 }
 
+//The CW20 contract calling is the payment denom and the sender of the tokens is the one minting
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let payment = Payment::Cw20(coin(wrapper.amount.u128(), info.sender.as_str()));
+    let info = MessageInfo {
+        sender: deps.api.addr_validate(&wrapper.sender)?,
+        funds: vec![],
+    };
+
+    match from_binary(&wrapper.msg)? {
+        ReceiveMsg::Mint {
+            character_id,
+            token_info,
+        } => {
+            let receiver = info.sender.to_string();
+            mint(
+                deps,
+                info,
+                env,
+                payment,
+                character_id,
+                token_info,
+                receiver,
+                None,
+            )
+        }
+        ReceiveMsg::MintTo {
+            character_id,
+            token_info,
+            receiver,
+        } => mint(
+            deps,
+            info,
+            env,
+            payment,
+            character_id,
+            token_info,
+            receiver,
+            None,
+        ),
+        ReceiveMsg::MintWithProof {
+            phase_id,
+            proof,
+            character_id,
+            token_info,
+            receiver,
+        } => {
+            let receiver = receiver.unwrap_or(info.sender.to_string());
+            let presale = Some((phase_id, proof));
+            mint(
+                deps,
+                info,
+                env,
+                payment,
+                character_id,
+                token_info,
+                receiver,
+                presale,
+            )
+        }
+        ReceiveMsg::MintBundle {
+            bundle_id,
+            receiver,
+        } => mint_bundle(deps, info, env, payment, bundle_id, receiver),
+        ReceiveMsg::OpenLootbox {
+            lootbox_id,
+            receiver,
+            commitment,
+        } => open_lootbox(deps, info, env, payment, lootbox_id, receiver, commitment),
+        ReceiveMsg::ChangeName { token_id, new_name } => {
+            change_name(deps, info, env, Some(payment), token_id, new_name)
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn mint(
     deps: DepsMut,
     info: MessageInfo,
-    token_info: Extension,
+    env: Env,
+    payment: Payment,
+    character_id: Option<u32>,
+    mut token_info: Extension,
     receiver: String,
+    presale: Option<(u32, Vec<String>)>,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Mint)?;
     deps.api.addr_validate(&receiver)?;
 
-    let funds_sent = one_coin(&info)?;
+    let funds_sent = payment.coin().clone();
+
+    let presale_phase = match presale {
+        Some((phase_id, proof)) => Some(record_presale_mint(
+            deps.storage,
+            &info.sender,
+            phase_id,
+            &proof,
+        )?),
+        None => None,
+    };
 
     let config = CONFIG.load(deps.storage)?;
     let mut res = Response::new();
@@ -196,83 +419,69 @@ pub fn mint(
         return Err(ContractError::InvalidMintTraits {});
     }
 
-    if token_info.rarity.is_some() {
+    if let Some(character_id) = character_id {
         //We are minting a pre-made character from the store
 
         //We check if the character is mintable
         let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
-        let characters_response: CharactersResp = deps
-            .querier
-            .query_wasm_smart(mintables_collection_address, &QueryMsg::Characters {})?;
-
-        let character = characters_response.characters.iter().find(|c| {
-            c.ears == token_info.ears
-                && c.eyes == token_info.eyes
-                && c.mouth == token_info.mouth
-                && c.fur_type == token_info.fur_type
-                && c.fur_color == token_info.fur_color
-                && c.tail_shape == token_info.tail_shape
-                && c.locked == token_info.locked
-                && c.rarity == token_info.rarity.clone().unwrap()
-        });
-
-        if character.is_none() {
-            return Err(ContractError::InvalidCharacter {});
+        let character = find_mintable_character(
+            deps.as_ref(),
+            &mintables_collection_address,
+            character_id,
+            &token_info,
+        )
+        .ok_or(ContractError::InvalidCharacter {})?;
+        //The slots may be sent in any order, the character is minted with the order of the catalog
+        token_info.traits = character.traits.clone();
+
+        //Characters listed without a price are sold at the price of their rarity
+        let listed_price = character
+            .mint_price
+            .clone()
+            .or_else(|| config.rarity_price(&character.rarity))
+            .ok_or(ContractError::NoMintPrice {})?;
+        if funds_sent != mint_price(&listed_price, &presale_phase) {
+            return Err(ContractError::IncorrectMintFunds {});
         }
 
-        if funds_sent != character.unwrap().mint_price {
-            return Err(ContractError::IncorrectMintFunds {});
+        if !character.is_on_sale(&env.block) {
+            return Err(ContractError::SaleNotActive {});
+        }
+        //The mint is recorded in mintables in the same transaction so the supply cannot be oversold
+        if character.is_sold_out() {
+            return Err(ContractError::SoldOut {});
         }
+        record_item_mint(deps.storage, &CHARACTER_MINTS, &info.sender, &character)?;
+        res = res.add_message(record_mint_msg(
+            &mintables_collection_address,
+            MintableKind::Character,
+            character.id,
+        )?);
     } else {
         //We are minting an empty character
 
         //We check if all traits are empty and it is not locked
-        if token_info.ears.is_some()
-            || token_info.eyes.is_some()
-            || token_info.mouth.is_some()
-            || token_info.fur_type.is_some()
-            || token_info.fur_color.is_some()
-            || token_info.tail_shape.is_some()
-            || token_info.rarity.is_some()
-            || token_info.locked == true
-        {
+        if !token_info.traits.is_empty() || token_info.rarity.is_some() || token_info.locked {
             return Err(ContractError::InvalidEmptyCharacterMint {});
         }
 
-        if funds_sent != config.empty_character_mint_price {
+        if funds_sent != mint_price(&config.empty_character_mint_price, &presale_phase) {
             return Err(ContractError::IncorrectMintFunds {});
         }
     }
 
-    //If we are minting using CoolCat tokens we apply the burn ratio if there is one
-    if funds_sent.denom == NATIVE_DENOM && config.burn_ratio > 0 {
-        let amount_burnt = config.burn_ratio.bps_to_decimal() * funds_sent.amount;
-        let burn_msg = BankMsg::Burn {
-            amount: coins(amount_burnt.u128(), NATIVE_DENOM),
-        };
-        res.messages.push(SubMsg::new(burn_msg));
-    }
+    record_address_mint(deps.storage, &env.block, &config.mint_limits, &info.sender)?;
+    res = res.add_messages(pay_out(deps.storage, &config.revenue_split, &payment)?);
 
-    //If we are minting using CoolCat we need to adjust the amount sent substracting the burnt amount
-    if funds_sent.denom == NATIVE_DENOM && config.burn_ratio != 100 {
-        let amount_sent = (100 - config.burn_ratio).bps_to_decimal() * funds_sent.amount;
-        let send_funds_msg = BankMsg::Send {
-            to_address: config.destination.unwrap().into_string(),
-            amount: coins(amount_sent.u128(), funds_sent.denom),
-        };
-        res.messages.push(SubMsg::new(send_funds_msg));
-    } else {
-        //Send full amount as nothing is burnt.
-        let send_funds_msg = BankMsg::Send {
-            to_address: config.destination.unwrap().into_string(),
-            amount: coins(funds_sent.amount.u128(), funds_sent.denom),
-        };
-        res.messages.push(SubMsg::new(send_funds_msg))
+    let token_id = increment_token_index(deps.storage)?.to_string();
+    //Names given at mint follow the same rules as renames, without the fee
+    if let Some(name) = &token_info.name {
+        claim_name(deps.storage, deps.querier, &config, name, &token_id)?;
     }
 
     // Create mint msgs
     let mint_msg = cw721_character_onchain::ExecuteMsg::<Extension, Empty>::Mint {
-        token_id: increment_token_index(deps.storage)?.to_string(),
+        token_id,
         owner: receiver.clone(),
         token_uri: None,
         extension: token_info,
@@ -295,70 +504,56 @@ pub fn mint(
 pub fn mint_bundle(
     deps: DepsMut,
     info: MessageInfo,
+    env: Env,
+    payment: Payment,
     bundle_id: u32,
     receiver: Option<String>,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Bundle)?;
     let send_to = receiver.unwrap_or(info.sender.to_string());
     deps.api.addr_validate(&send_to)?;
 
-    let funds_sent = one_coin(&info)?;
+    let funds_sent = payment.coin().clone();
 
     //We check if the bundle is mintable
     let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
-    let bundle_response: CharacterBundlesResp = deps
+    let bundle: CharacterBundle = deps
         .querier
-        .query_wasm_smart(mintables_collection_address, &QueryMsg::CharacterBundles {})?;
-
-    let bundle = bundle_response.bundles.iter().find(|b| b.id == bundle_id);
-
-    if bundle.is_none() {
-        return Err(ContractError::InvalidBundle {});
-    }
+        .query_wasm_smart(
+            &mintables_collection_address,
+            &QueryMsg::CharacterBundle { id: bundle_id },
+        )
+        .map_err(|_| ContractError::InvalidBundle {})?;
 
     let config = CONFIG.load(deps.storage)?;
     let mut res = Response::new();
 
-    if funds_sent != bundle.unwrap().mint_price {
+    if funds_sent != bundle.mint_price {
         return Err(ContractError::IncorrectMintFunds {});
     }
 
-    //If we are minting using CoolCat tokens we apply the burn ratio if there is one
-    if funds_sent.denom == NATIVE_DENOM && config.burn_ratio > 0 {
-        let amount_burnt = config.burn_ratio.bps_to_decimal() * funds_sent.amount;
-        let burn_msg = BankMsg::Burn {
-            amount: coins(amount_burnt.u128(), NATIVE_DENOM),
-        };
-        res.messages.push(SubMsg::new(burn_msg));
+    if !bundle.is_on_sale(&env.block) {
+        return Err(ContractError::SaleNotActive {});
     }
-
-    //If we are minting using CoolCat we need to adjust the amount sent substracting the burnt amount
-    if funds_sent.denom == NATIVE_DENOM && config.burn_ratio != 100 {
-        let amount_sent = (100 - config.burn_ratio).bps_to_decimal() * funds_sent.amount;
-        let send_funds_msg = BankMsg::Send {
-            to_address: config.destination.unwrap().into_string(),
-            amount: coins(amount_sent.u128(), funds_sent.denom),
-        };
-        res.messages.push(SubMsg::new(send_funds_msg));
-    } else {
-        //Send full amount as nothing is burnt.
-        let send_funds_msg = BankMsg::Send {
-            to_address: config.destination.unwrap().into_string(),
-            amount: coins(funds_sent.amount.u128(), funds_sent.denom),
-        };
-        res.messages.push(SubMsg::new(send_funds_msg))
+    if bundle.is_sold_out() {
+        return Err(ContractError::SoldOut {});
     }
+    record_item_mint(deps.storage, &BUNDLE_MINTS, &info.sender, &bundle)?;
+    res = res.add_message(record_mint_msg(
+        &mintables_collection_address,
+        MintableKind::CharacterBundle,
+        bundle_id,
+    )?);
+
+    record_address_mint(deps.storage, &env.block, &config.mint_limits, &info.sender)?;
+    res = res.add_messages(pay_out(deps.storage, &config.revenue_split, &payment)?);
 
     let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
 
-    for new_character in bundle.unwrap().characters.clone() {
+    for new_character in bundle.characters.clone() {
         let token_info = Extension {
             name: None,
-            ears: new_character.ears,
-            eyes: new_character.eyes,
-            mouth: new_character.mouth,
-            fur_type: new_character.fur_type,
-            fur_color: new_character.fur_color,
-            tail_shape: new_character.tail_shape,
+            traits: new_character.traits,
             rarity: Some(new_character.rarity),
             traits_equipped: None,
             locked: new_character.locked,
@@ -391,99 +586,263 @@ pub fn open_lootbox(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
+    payment: Payment,
     lootbox_id: u32,
     receiver: Option<String>,
+    commitment: Option<String>,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Lootbox)?;
     let send_to = receiver.unwrap_or(info.sender.to_string());
-    deps.api.addr_validate(&send_to)?;
+    let receiver_addr = deps.api.addr_validate(&send_to)?;
 
-    let funds_sent = one_coin(&info)?;
+    let config = CONFIG.load(deps.storage)?;
 
-    //We check if the bundle is mintable
-    let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
-    let lootbox_response: CharacterLootboxesResp = deps.querier.query_wasm_smart(
-        mintables_collection_address,
-        &QueryMsg::CharacterLootboxes {},
-    )?;
+    let commitment = parse_commitment(&config.randomness_source, commitment)?;
 
-    let lootbox = lootbox_response
-        .lootboxes
-        .iter()
-        .find(|lb| lb.id == lootbox_id);
+    let funds_sent = payment.coin().clone();
 
-    if lootbox.is_none() {
-        return Err(ContractError::InvalidLootbox {});
-    }
+    //We check if the bundle is mintable
+    let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
+    let lootbox: CharacterLootbox = deps
+        .querier
+        .query_wasm_smart(
+            &mintables_collection_address,
+            &QueryMsg::CharacterLootbox { id: lootbox_id },
+        )
+        .map_err(|_| ContractError::InvalidLootbox {})?;
 
-    let config = CONFIG.load(deps.storage)?;
     let mut res = Response::new();
 
-    if funds_sent != lootbox.unwrap().mint_price {
+    if funds_sent != lootbox.mint_price {
         return Err(ContractError::IncorrectMintFunds {});
     }
 
-    //If we are minting using CoolCat tokens we apply the burn ratio if there is one
-    if funds_sent.denom == NATIVE_DENOM && config.burn_ratio > 0 {
-        let amount_burnt = config.burn_ratio.bps_to_decimal() * funds_sent.amount;
-        let burn_msg = BankMsg::Burn {
-            amount: coins(amount_burnt.u128(), NATIVE_DENOM),
-        };
-        res.messages.push(SubMsg::new(burn_msg));
+    if !lootbox.is_on_sale(&env.block) {
+        return Err(ContractError::SaleNotActive {});
     }
+    if lootbox.is_sold_out() {
+        return Err(ContractError::SoldOut {});
+    }
+    record_item_mint(deps.storage, &LOOTBOX_MINTS, &info.sender, &lootbox)?;
+    res = res.add_message(record_mint_msg(
+        &mintables_collection_address,
+        MintableKind::CharacterLootbox,
+        lootbox_id,
+    )?);
+
+    record_address_mint(deps.storage, &env.block, &config.mint_limits, &info.sender)?;
+
+    //Beacon jobs keep the payment until the beacon answers, so it can be refunded if it never does
+    let (proxy, held_payment) = match &config.randomness_source {
+        RandomnessSource::Internal => {
+            res = res.add_messages(pay_out(deps.storage, &config.revenue_split, &payment)?);
+            (None, None)
+        }
+        RandomnessSource::Beacon { proxy_addr } => (Some(proxy_addr.clone()), Some(payment)),
+    };
 
-    //If we are minting using CoolCat we need to adjust the amount sent substracting the burnt amount
-    if funds_sent.denom == NATIVE_DENOM && config.burn_ratio != 100 {
-        let amount_sent = (100 - config.burn_ratio).bps_to_decimal() * funds_sent.amount;
-        let send_funds_msg = BankMsg::Send {
-            to_address: config.destination.unwrap().into_string(),
-            amount: coins(amount_sent.u128(), funds_sent.denom),
-        };
-        res.messages.push(SubMsg::new(send_funds_msg));
-    } else {
-        //Send full amount as nothing is burnt.
-        let send_funds_msg = BankMsg::Send {
-            to_address: config.destination.unwrap().into_string(),
-            amount: coins(funds_sent.amount.u128(), funds_sent.denom),
-        };
-        res.messages.push(SubMsg::new(send_funds_msg))
+    //The lootbox is paid now but its content is only drawn once the secret is revealed in a later block
+    //or the beacon delivers the randomness for it
+    let commitment_id = increment_commitment_index(deps.storage)?;
+    PENDING_ENTROPY.save(deps.storage, &env.block.height)?;
+    LOOTBOX_COMMITMENTS.save(
+        deps.storage,
+        commitment_id,
+        &LootboxCommitment {
+            owner: info.sender.clone(),
+            receiver: receiver_addr,
+            lootbox,
+            commitment,
+            committed_at: env.block.height,
+            entropy: String::new(),
+            proxy: proxy.clone(),
+            payment: held_payment,
+        },
+    )?;
+
+    if let Some(proxy_addr) = proxy {
+        res = res.add_message(randomness_request_msg(proxy_addr, commitment_id)?);
     }
 
-    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
-    let mut current = random_number_1_to_x(
+    Ok(res
+        .add_attribute("action", "open_lootbox")
+        .add_attribute("lootbox_id", lootbox_id.to_string())
+        .add_attribute("commitment_id", commitment_id.to_string())
+        .add_attribute("sender", info.sender)
+        .add_attribute("receiver", send_to))
+}
+
+pub fn reveal_lootbox(
+    deps: DepsMut,
+    env: Env,
+    commitment_id: u64,
+    secret: String,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Lootbox)?;
+    let commitment = LOOTBOX_COMMITMENTS
+        .may_load(deps.storage, commitment_id)?
+        .ok_or(ContractError::CommitmentNotFound {})?;
+
+    let seed = reveal_seed(
+        deps.as_ref(),
         &env,
-        send_to.clone(),
-        lootbox_response.lootboxes.len().try_into().unwrap(),
-        100
-    );
-    let mut position = 0;
+        commitment_id,
+        &commitment.commitment,
+        commitment.committed_at,
+        &secret,
+        &commitment.entropy,
+    )?;
+    let position = drawn_position(&commitment.lootbox.possibilities, seed);
 
-    //Find which item of the lootbox we get according to possibilities
-    for possibility in lootbox.unwrap().possibilities.clone() {
-        if current <= possibility {
-            break;
-        } else {
-            position += 1;
-            current -= possibility;
+    Ok(draw_lootbox(deps, commitment_id, commitment, position)?
+        .add_attribute("action", "reveal_lootbox"))
+}
+
+pub fn resolve_expired_lootbox(
+    deps: DepsMut,
+    env: Env,
+    commitment_id: u64,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Lootbox)?;
+    let commitment = LOOTBOX_COMMITMENTS
+        .may_load(deps.storage, commitment_id)?
+        .ok_or(ContractError::CommitmentNotFound {})?;
+
+    if env.block.height <= commitment.committed_at + REVEAL_TIMEOUT {
+        return Err(ContractError::CommitmentNotExpired {});
+    }
+
+    //Beacon jobs that never get an answer are refunded, nobody could know their result
+    if let (None, Some(payment)) = (&commitment.commitment, &commitment.payment) {
+        LOOTBOX_COMMITMENTS.remove(deps.storage, commitment_id);
+        release_item_mint(
+            deps.storage,
+            &LOOTBOX_MINTS,
+            &commitment.owner,
+            commitment.lootbox.id,
+        )?;
+        release_address_mint(
+            deps.storage,
+            &env.block,
+            commitment.committed_at,
+            &commitment.owner,
+        )?;
+        let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
+
+        let mut res = Response::new().add_message(release_mint_msg(
+            &mintables_collection_address,
+            MintableKind::CharacterLootbox,
+            commitment.lootbox.id,
+        )?);
+        if !payment.coin().amount.is_zero() {
+            res = res.add_message(payment.send_msg(&commitment.owner, payment.coin().amount)?);
+        }
+
+        return Ok(res
+            .add_attribute("action", "refund_lootbox")
+            .add_attribute("commitment_id", commitment_id.to_string())
+            .add_attribute("owner", commitment.owner));
+    }
+
+    //Lootboxes that are never revealed are forfeited, so walking away from a known result is worse than any draw
+    LOOTBOX_COMMITMENTS.remove(deps.storage, commitment_id);
+    let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
+
+    Ok(Response::new()
+        .add_message(release_mint_msg(
+            &mintables_collection_address,
+            MintableKind::CharacterLootbox,
+            commitment.lootbox.id,
+        )?)
+        .add_attribute("action", "forfeit_lootbox")
+        .add_attribute("commitment_id", commitment_id.to_string())
+        .add_attribute("owner", commitment.owner))
+}
+
+pub fn receive_randomness(
+    deps: DepsMut,
+    info: MessageInfo,
+    job_id: String,
+    randomness: HexBinary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    //Job ids are the ids of the lootbox, fusion and dismantle commitments waiting for beacon randomness
+    let commitment_id: u64 = job_id.parse().map_err(|_| ContractError::JobNotFound {})?;
+    let seed = format!("{}{}", randomness.to_hex(), commitment_id);
+
+    if let Some(commitment) = FUSION_COMMITMENTS.may_load(deps.storage, commitment_id)? {
+        assert_not_paused(deps.storage, Operation::Fuse)?;
+        if commitment.proxy.as_ref() != Some(&info.sender) {
+            return Err(ContractError::UnauthorizedRandomnessSource {});
+        }
+        return Ok(draw_fusion(deps, commitment_id, commitment, Some(seed))?
+            .add_attribute("action", "receive_randomness"));
+    }
+    if let Some(commitment) = DISMANTLE_COMMITMENTS.may_load(deps.storage, commitment_id)? {
+        assert_not_paused(deps.storage, Operation::Dismantle)?;
+        if commitment.proxy.as_ref() != Some(&info.sender) {
+            return Err(ContractError::UnauthorizedRandomnessSource {});
+        }
+        return Ok(draw_dismantle(deps, commitment_id, commitment, Some(seed))?
+            .add_attribute("action", "receive_randomness"));
+    }
+
+    assert_not_paused(deps.storage, Operation::Lootbox)?;
+    let commitment = LOOTBOX_COMMITMENTS
+        .may_load(deps.storage, commitment_id)?
+        .ok_or(ContractError::JobNotFound {})?;
+
+    //A job is answered by the proxy it was sent to, even if the randomness source changed since.
+    //Jobs stored without their proxy can only be answered by the configured one
+    let proxy = match (&commitment.proxy, &config.randomness_source) {
+        (Some(proxy), _) => Some(proxy),
+        (None, RandomnessSource::Beacon { proxy_addr }) if commitment.commitment.is_none() => {
+            Some(proxy_addr)
         }
+        _ => None,
+    };
+    if proxy != Some(&info.sender) {
+        return Err(ContractError::UnauthorizedRandomnessSource {});
     }
 
+    //The payment held for the job is paid out now that the lootbox is drawn
+    let payout_msgs = match &commitment.payment {
+        Some(payment) => pay_out(deps.storage, &config.revenue_split, payment)?,
+        None => vec![],
+    };
+
+    let position = drawn_position(&commitment.lootbox.possibilities, seed);
+
+    Ok(draw_lootbox(deps, commitment_id, commitment, position)?
+        .add_messages(payout_msgs)
+        .add_attribute("action", "receive_randomness"))
+}
+
+fn draw_lootbox(
+    deps: DepsMut,
+    commitment_id: u64,
+    commitment: LootboxCommitment,
+    position: usize,
+) -> Result<Response, ContractError> {
+    LOOTBOX_COMMITMENTS.remove(deps.storage, commitment_id);
+
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+    let lootbox = commitment.lootbox;
+
+    let character = lootbox.characters[position].clone();
     let token_info = Extension {
         name: None,
-        ears: lootbox.unwrap().characters[position].clone().ears,
-        eyes: lootbox.unwrap().characters[position].clone().eyes,
-        mouth: lootbox.unwrap().characters[position].clone().mouth,
-        fur_type: lootbox.unwrap().characters[position].clone().fur_type,
-        fur_color: lootbox.unwrap().characters[position].clone().fur_color,
-        tail_shape: lootbox.unwrap().characters[position].clone().tail_shape,
-        rarity: Some(lootbox.unwrap().characters[position].clone().rarity),
+        traits: character.traits,
+        rarity: Some(character.rarity),
         traits_equipped: None,
-        locked: lootbox.unwrap().characters[position].locked,
+        locked: character.locked,
     };
 
     // Create mint msgs
     let mint_msg = cw721_character_onchain::ExecuteMsg::<Extension, Empty>::Mint {
         token_id: increment_token_index(deps.storage)?.to_string(),
-        owner: send_to.clone(),
+        owner: commitment.receiver.to_string(),
         token_uri: None,
         extension: token_info,
     };
@@ -494,22 +853,87 @@ pub fn open_lootbox(
         funds: vec![],
     });
 
-    res = res.add_message(msg);
-
-    Ok(res
-        .add_attribute("action", "open_lootbox")
-        .add_attribute("lootbox_id", lootbox_id.to_string())
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("lootbox_id", lootbox.id.to_string())
+        .add_attribute("commitment_id", commitment_id.to_string())
         .add_attribute("won_element", position.to_string())
-        .add_attribute("sender", info.sender)
-        .add_attribute("receiver", send_to))
+        .add_attribute("receiver", commitment.receiver))
+}
+
+//Only internal randomness needs the user to commit to a secret
+fn parse_commitment(
+    randomness_source: &RandomnessSource,
+    commitment: Option<String>,
+) -> Result<Option<String>, ContractError> {
+    match randomness_source {
+        RandomnessSource::Internal => {
+            let commitment = commitment
+                .ok_or(ContractError::InvalidCommitment {})?
+                .to_lowercase();
+            if commitment.len() != 64 || !commitment.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(ContractError::InvalidCommitment {});
+            }
+            Ok(Some(commitment))
+        }
+        RandomnessSource::Beacon { .. } => Ok(None),
+    }
+}
+
+//Checks the secret of a commitment and returns the seed of its draw
+fn reveal_seed(
+    deps: Deps,
+    env: &Env,
+    commitment_id: u64,
+    commitment: &Option<String>,
+    committed_at: u64,
+    secret: &str,
+    legacy_entropy: &str,
+) -> Result<String, ContractError> {
+    let commitment_hash = commitment
+        .as_ref()
+        .ok_or(ContractError::AwaitingRandomness {})?;
+
+    if env.block.height <= committed_at {
+        return Err(ContractError::RevealTooEarly {});
+    }
+
+    if env.block.height > committed_at + REVEAL_TIMEOUT {
+        return Err(ContractError::CommitmentExpired {});
+    }
+
+    if format!("{:x}", Sha256::digest(secret.as_bytes())) != *commitment_hash {
+        return Err(ContractError::InvalidSecret {});
+    }
+
+    //The secret is mixed with the entropy of the first block after the commitment one, which nobody knew
+    //when committing. It is recorded before any message of that block, so the reveal block does not matter
+    let entropy = BLOCK_ENTROPY
+        .may_load(deps.storage, committed_at)?
+        .unwrap_or_else(|| legacy_entropy.to_string());
+    Ok(format!("{}{}{}", secret, commitment_id, entropy))
+}
+
+fn randomness_request_msg(proxy_addr: Addr, commitment_id: u64) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: proxy_addr.into_string(),
+        msg: to_binary(&RandomnessProxyExecuteMsg::GetNextRandomness {
+            job_id: commitment_id.to_string(),
+        })?,
+        funds: vec![],
+    }))
 }
 
 pub fn change_name(
     deps: DepsMut,
     info: MessageInfo,
+    env: Env,
+    payment: Option<Payment>,
     character_id: String,
     new_name: String,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Rename)?;
+    let config = CONFIG.load(deps.storage)?;
     let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
 
     let character_response: CharacterInfoResponse<Extension> = deps.querier.query_wasm_smart(
@@ -523,17 +947,41 @@ pub fn change_name(
         return Err(ContractError::NotCharacterOwner {});
     }
 
+    let now = env.block.time.seconds();
+    if let Some(cooldown) = config.names.rename_cooldown {
+        if let Some(last_rename) = LAST_RENAMES.may_load(deps.storage, &character_id)? {
+            if now < last_rename + cooldown {
+                return Err(ContractError::RenameCooldown {});
+            }
+        }
+    }
+    LAST_RENAMES.save(deps.storage, &character_id, &now)?;
+
+    let mut res = Response::new();
+    match (&config.names.rename_fee, payment) {
+        (None, None) => {}
+        (Some(fee), Some(payment)) if payment.coin() == fee => {
+            res = res.add_messages(pay_out(deps.storage, &config.revenue_split, &payment)?);
+        }
+        _ => return Err(ContractError::IncorrectRenameFee {}),
+    }
+
+    release_name(
+        deps.storage,
+        &character_response.token_info.name,
+        &character_id,
+    )?;
+    claim_name(
+        deps.storage,
+        deps.querier,
+        &config,
+        &new_name,
+        &character_id,
+    )?;
+
     let new_character_info = Metadata {
-        name: Some(new_name),
-        ears: character_response.token_info.ears,
-        eyes: character_response.token_info.eyes,
-        mouth: character_response.token_info.mouth,
-        fur_type: character_response.token_info.fur_type,
-        fur_color: character_response.token_info.fur_color,
-        tail_shape: character_response.token_info.tail_shape,
-        rarity: character_response.token_info.rarity,
-        traits_equipped: character_response.token_info.traits_equipped,
-        locked: character_response.token_info.locked,
+        name: Some(new_name.clone()),
+        ..character_response.token_info
     };
 
     let modify_msg = CharacterExecuteMsg::<Metadata, Empty>::Modify {
@@ -547,11 +995,72 @@ pub fn change_name(
         funds: vec![],
     });
 
-    Ok(Response::new()
+    Ok(res
         .add_message(msg)
         .add_attribute("action", "change_name")
         .add_attribute("sender", info.sender)
-        .add_attribute("character_id", character_id))
+        .add_attribute("character_id", character_id)
+        .add_attribute("name", new_name))
+}
+
+//Checks the name against the naming rules and indexes it for the character
+fn claim_name(
+    storage: &mut dyn Storage,
+    querier: QuerierWrapper,
+    config: &Config,
+    name: &str,
+    character_id: &str,
+) -> Result<(), ContractError> {
+    if !config.names.is_allowed(name) {
+        return Err(ContractError::InvalidName {});
+    }
+    if config.names.is_reserved(name) {
+        return Err(ContractError::ReservedName {});
+    }
+
+    let key = name.to_lowercase();
+    if BLOCKED_NAMES.has(storage, &key) {
+        return Err(ContractError::NameBlocked {});
+    }
+    if let Some(holder) = NAMES.may_load(storage, &key)? {
+        if holder != character_id && character_exists(storage, querier, &holder)? {
+            return Err(ContractError::NameTaken {});
+        }
+    }
+    NAMES.save(storage, &key, &character_id.to_string())?;
+    Ok(())
+}
+
+//Characters burnt directly through the collection keep their name in the index until it is claimed again
+fn character_exists(
+    storage: &dyn Storage,
+    querier: QuerierWrapper,
+    character_id: &str,
+) -> StdResult<bool> {
+    let collection_address = COLLECTION_ADDRESS.load(storage)?;
+    let owner: StdResult<OwnerOfResponse> = querier.query_wasm_smart(
+        collection_address,
+        &CharacterQueryMsg::<Empty>::OwnerOf {
+            token_id: character_id.to_string(),
+            include_expired: None,
+        },
+    );
+    Ok(owner.is_ok())
+}
+
+//Frees the name of a character, names given before the index existed are not in it
+fn release_name(
+    storage: &mut dyn Storage,
+    name: &Option<String>,
+    character_id: &str,
+) -> StdResult<()> {
+    if let Some(name) = name {
+        let key = name.to_lowercase();
+        if NAMES.may_load(storage, &key)?.as_deref() == Some(character_id) {
+            NAMES.remove(storage, &key);
+        }
+    }
+    Ok(())
 }
 
 pub fn modify_character(
@@ -560,116 +1069,246 @@ pub fn modify_character(
     character_id: String,
     trait_ids: Vec<String>,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Modify)?;
     let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+    let character_response = load_unlocked_character(deps.as_ref(), &info, &character_id)?;
 
-    let character_response: CharacterInfoResponse<Extension> = deps.querier.query_wasm_smart(
-        collection_address.clone(),
-        &CharacterQueryMsg::<Empty>::CharacterInfo {
-            token_id: character_id.clone(),
-        },
-    )?;
-
-    if character_response.owner != info.sender {
-        return Err(ContractError::NotCharacterOwner {});
-    }
+    let trait_collection_address = TRAIT_COLLECTION_ADDRESS.load(deps.storage)?;
+    let slots = registered_slots(deps.as_ref())?;
 
-    if character_response.token_info.locked == true {
-        return Err(ContractError::CharacterAlreadyLocked {});
-    }
+    //Traits already held in escrow for this character do not need to be escrowed again
+    let previously_equipped = character_response
+        .token_info
+        .traits_equipped
+        .unwrap_or_default();
 
+    //Slots that are not backed by an equipped trait, like the ones of premade characters, are kept
     let mut new_character_info = Metadata {
         name: character_response.token_info.name,
-        ears: None,
-        eyes: None,
-        mouth: None,
-        fur_type: None,
-        fur_color: None,
-        tail_shape: None,
+        traits: character_response.token_info.traits,
         rarity: character_response.token_info.rarity,
         traits_equipped: Some(trait_ids.clone()),
         locked: false,
     };
 
-    let trait_collection_address = TRAIT_COLLECTION_ADDRESS.load(deps.storage)?;
+    //Traits that are no longer equipped go back to their owner and their slots are cleared
+    let traits_to_release: Vec<String> = previously_equipped
+        .iter()
+        .filter(|id| !trait_ids.contains(id))
+        .cloned()
+        .collect();
+    for one_trait_id in traits_to_release.iter() {
+        let trait_info = query_trait(deps.as_ref(), &trait_collection_address, one_trait_id)?;
+        set_trait_slot(
+            &mut new_character_info,
+            &slots,
+            &trait_info.info.extension.trait_type,
+            None,
+        )?;
+    }
 
-    let all_traits_response: TokensResponse = deps.querier.query_wasm_smart(
-        trait_collection_address.clone(),
-        &Cw721QueryMsg::Tokens {
-            owner: info.sender.clone().into_string(),
-            start_after: None,
-            limit: None,
-        },
-    )?;
+    let mut trait_types: Vec<String> = vec![];
+    for one_trait_id in trait_ids.iter() {
+        let trait_info = query_trait(deps.as_ref(), &trait_collection_address, one_trait_id)?;
 
-    for one_trait_id in trait_ids {
-        if !all_traits_response.tokens.contains(&one_trait_id) {
+        if trait_info.access.owner != info.sender {
             return Err(ContractError::NotTraitOwner {});
         }
-        let trait_info: NftInfoResponse<TraitExtension> = deps.querier.query_wasm_smart(
-            trait_collection_address.clone(),
-            &Cw721QueryMsg::NftInfo {
-                token_id: one_trait_id,
-            },
-        )?;
+        assert_registered_trait(deps.as_ref(), &trait_info.info.extension)?;
 
-        match trait_info.extension.trait_type.as_str() {
-            "ears" => new_character_info.ears = Some(trait_info.extension.trait_value),
-            "eyes" => new_character_info.eyes = Some(trait_info.extension.trait_value),
-            "mouth" => new_character_info.mouth = Some(trait_info.extension.trait_value),
-            "fur_type" => new_character_info.fur_type = Some(trait_info.extension.trait_value),
-            "fur_color" => new_character_info.fur_color = Some(trait_info.extension.trait_value),
-            "tail_shape" => new_character_info.tail_shape = Some(trait_info.extension.trait_value),
-            _ => return Err(ContractError::InvalidTrait {}),
+        //Only one trait per slot, otherwise the overwritten trait would stay in escrow
+        if trait_types.contains(&trait_info.info.extension.trait_type) {
+            return Err(ContractError::DuplicateTraitType {});
         }
+        trait_types.push(trait_info.info.extension.trait_type.clone());
+
+        set_trait_slot(
+            &mut new_character_info,
+            &slots,
+            &trait_info.info.extension.trait_type,
+            Some(trait_info.info.extension.trait_value),
+        )?;
     }
 
-    let modify_msg = CharacterExecuteMsg::<Metadata, Empty>::Modify {
-        token_id: character_id.clone(),
-        new_values: new_character_info,
-    };
+    let mut res = Response::new();
 
-    let msg = CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: collection_address.to_string(),
-        msg: to_binary(&modify_msg)?,
-        funds: vec![],
-    });
+    if !traits_to_release.is_empty() {
+        res = res.add_message(release_escrow_msg(
+            &trait_collection_address,
+            traits_to_release,
+            &info.sender,
+        )?);
+    }
 
-    Ok(Response::new()
+    //Newly equipped traits are held in escrow by this manager until they are burned when locking or unequipped.
+    //The trait collection will fail if the manager is not approved for them or they are already equipped elsewhere.
+    let traits_to_escrow: Vec<String> = trait_ids
+        .iter()
+        .filter(|id| !previously_equipped.contains(id))
+        .cloned()
+        .collect();
+
+    if !traits_to_escrow.is_empty() {
+        res = res.add_message(escrow_msg(&trait_collection_address, traits_to_escrow)?);
+    }
+
+    let msg = modify_msg(&collection_address, &character_id, new_character_info)?;
+
+    Ok(res
         .add_message(msg)
         .add_attribute("action", "modify_character")
         .add_attribute("sender", info.sender)
         .add_attribute("character_id", character_id))
 }
 
-pub fn lock_character(
+pub fn unequip_traits(
     deps: DepsMut,
     info: MessageInfo,
     character_id: String,
+    trait_types: Vec<String>,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Modify)?;
     let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+    let character_response = load_unlocked_character(deps.as_ref(), &info, &character_id)?;
+    let trait_collection_address = TRAIT_COLLECTION_ADDRESS.load(deps.storage)?;
 
-    let character_response: CharacterInfoResponse<Extension> = deps.querier.query_wasm_smart(
-        collection_address.clone(),
-        &CharacterQueryMsg::<Empty>::CharacterInfo {
-            token_id: character_id.clone(),
-        },
-    )?;
+    let mut new_character_info = character_response.token_info;
+    let mut traits_equipped = new_character_info
+        .traits_equipped
+        .clone()
+        .unwrap_or_default();
+    let mut traits_to_release: Vec<String> = vec![];
+
+    for trait_type in trait_types.iter() {
+        //Only slots backed by an equipped trait can be unequipped, otherwise there is nothing to give back
+        let mut equipped_id = None;
+        for one_trait_id in traits_equipped.iter() {
+            let trait_info = query_trait(deps.as_ref(), &trait_collection_address, one_trait_id)?;
+            if &trait_info.info.extension.trait_type == trait_type {
+                equipped_id = Some(one_trait_id.clone());
+                break;
+            }
+        }
 
-    if character_response.owner != info.sender {
-        return Err(ContractError::NotCharacterOwner {});
+        let equipped_id = equipped_id.ok_or(ContractError::TraitNotEquipped {})?;
+
+        set_trait_slot(&mut new_character_info, &[], trait_type, None)?;
+        traits_equipped.retain(|id| id != &equipped_id);
+        traits_to_release.push(equipped_id);
     }
 
-    if character_response.token_info.locked == true {
-        return Err(ContractError::CharacterAlreadyLocked {});
+    new_character_info.traits_equipped = if traits_equipped.is_empty() {
+        None
+    } else {
+        Some(traits_equipped)
+    };
+
+    let mut res = Response::new();
+
+    if !traits_to_release.is_empty() {
+        res = res.add_message(release_escrow_msg(
+            &trait_collection_address,
+            traits_to_release.clone(),
+            &info.sender,
+        )?);
     }
 
+    let msg = modify_msg(&collection_address, &character_id, new_character_info)?;
+
+    Ok(res
+        .add_message(msg)
+        .add_attribute("action", "unequip_traits")
+        .add_attribute("sender", info.sender)
+        .add_attribute("character_id", character_id)
+        .add_attribute("traits", traits_to_release.join(",")))
+}
+
+pub fn swap_trait(
+    deps: DepsMut,
+    info: MessageInfo,
+    character_id: String,
+    trait_id: String,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Modify)?;
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+    let character_response = load_unlocked_character(deps.as_ref(), &info, &character_id)?;
+    let trait_collection_address = TRAIT_COLLECTION_ADDRESS.load(deps.storage)?;
+
+    let mut new_character_info = character_response.token_info;
+    let mut traits_equipped = new_character_info
+        .traits_equipped
+        .clone()
+        .unwrap_or_default();
+
+    if traits_equipped.contains(&trait_id) {
+        return Err(ContractError::TraitAlreadyEquipped {});
+    }
+
+    let new_trait = query_trait(deps.as_ref(), &trait_collection_address, &trait_id)?;
+
+    if new_trait.access.owner != info.sender {
+        return Err(ContractError::NotTraitOwner {});
+    }
+    assert_registered_trait(deps.as_ref(), &new_trait.info.extension)?;
+
+    let trait_type = new_trait.info.extension.trait_type;
+    set_trait_slot(
+        &mut new_character_info,
+        &registered_slots(deps.as_ref())?,
+        &trait_type,
+        Some(new_trait.info.extension.trait_value),
+    )?;
+
+    let mut res = Response::new();
+
+    //The trait currently equipped in the same slot, if any, goes back to its owner
+    for one_trait_id in traits_equipped.clone() {
+        let trait_info = query_trait(deps.as_ref(), &trait_collection_address, &one_trait_id)?;
+        if trait_info.info.extension.trait_type == trait_type {
+            traits_equipped.retain(|id| id != &one_trait_id);
+            res = res.add_message(release_escrow_msg(
+                &trait_collection_address,
+                vec![one_trait_id],
+                &info.sender,
+            )?);
+            break;
+        }
+    }
+
+    traits_equipped.push(trait_id.clone());
+    new_character_info.traits_equipped = Some(traits_equipped);
+
+    let msg = modify_msg(&collection_address, &character_id, new_character_info)?;
+
+    Ok(res
+        .add_message(escrow_msg(
+            &trait_collection_address,
+            vec![trait_id.clone()],
+        )?)
+        .add_message(msg)
+        .add_attribute("action", "swap_trait")
+        .add_attribute("sender", info.sender)
+        .add_attribute("character_id", character_id)
+        .add_attribute("trait_id", trait_id))
+}
+
+pub fn lock_character(
+    deps: DepsMut,
+    info: MessageInfo,
+    character_id: String,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Lock)?;
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+    let character_response = load_unlocked_character(deps.as_ref(), &info, &character_id)?;
+
     let mut res = Response::new();
 
     let trait_collection_address = TRAIT_COLLECTION_ADDRESS.load(deps.storage)?;
 
-    if character_response.token_info.traits_equipped.is_some() {
-        let burn_msg = TraitExecuteMsg::<Metadata, Empty>::BurnMultiple {
-            token_ids: character_response.token_info.traits_equipped.unwrap(),
+    //The equipped traits are held in escrow by this manager, so they can be burned without further approvals
+    if let Some(traits_equipped) = character_response.token_info.traits_equipped {
+        let burn_msg = TraitExecuteMsg::<TraitExtension, Empty>::BurnMultiple {
+            token_ids: traits_equipped,
         };
 
         let msg1 = CosmosMsg::Wasm(WasmMsg::Execute {
@@ -698,155 +1337,1557 @@ pub fn lock_character(
         .add_attribute("character_id", character_id))
 }
 
-pub fn update_ownership(
+pub fn fuse(
     deps: DepsMut,
-    env: Env,
     info: MessageInfo,
-    action: cw_ownable::Action,
+    env: Env,
+    parent_a: String,
+    parent_b: String,
+    commitment: Option<String>,
 ) -> Result<Response, ContractError> {
-    let ownership = cw_ownable::update_ownership(deps, &env.block, &info.sender, action)?;
-    Ok(Response::new().add_attributes(ownership.into_attributes()))
+    assert_not_paused(deps.storage, Operation::Fuse)?;
+    if parent_a == parent_b {
+        return Err(ContractError::SameParents {});
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let commitment = parse_commitment(&config.randomness_source, commitment)?;
+    load_unlocked_character(deps.as_ref(), &info, &parent_a)?;
+    load_unlocked_character(deps.as_ref(), &info, &parent_b)?;
+
+    //The child is only drawn once the secret is revealed in a later block or the beacon delivers
+    //the randomness for it. The parents are kept from being modified until then
+    let proxy = match config.randomness_source {
+        RandomnessSource::Internal => None,
+        RandomnessSource::Beacon { proxy_addr } => Some(proxy_addr),
+    };
+    let commitment_id = increment_commitment_index(deps.storage)?;
+    PENDING_ENTROPY.save(deps.storage, &env.block.height)?;
+    for parent in [&parent_a, &parent_b] {
+        PENDING_CHARACTERS.save(deps.storage, parent, &commitment_id)?;
+    }
+    FUSION_COMMITMENTS.save(
+        deps.storage,
+        commitment_id,
+        &FusionCommitment {
+            owner: info.sender.clone(),
+            parent_a: parent_a.clone(),
+            parent_b: parent_b.clone(),
+            commitment,
+            committed_at: env.block.height,
+            proxy: proxy.clone(),
+        },
+    )?;
+
+    let mut res = Response::new();
+    if let Some(proxy_addr) = proxy {
+        res = res.add_message(randomness_request_msg(proxy_addr, commitment_id)?);
+    }
+
+    Ok(res
+        .add_attribute("action", "fuse")
+        .add_attribute("commitment_id", commitment_id.to_string())
+        .add_attribute("sender", info.sender)
+        .add_attribute("parent_a", parent_a)
+        .add_attribute("parent_b", parent_b))
 }
 
-pub fn update_config(
+pub fn reveal_fusion(
     deps: DepsMut,
-    info: MessageInfo,
-    new_config: UpdateCharacterManagerParamsMsg,
+    env: Env,
+    commitment_id: u64,
+    secret: String,
 ) -> Result<Response, ContractError> {
-    //Only owner can update config
-    cw_ownable::assert_owner(deps.storage, &info.sender)?;
-    //If mint price is not fully burned then there must be a valid destination address to send funds to
-    if new_config.burn_ratio != 100 && new_config.destination.is_none() {
-        return Err(ContractError::NoMintDestination {});
-    }
+    assert_not_paused(deps.storage, Operation::Fuse)?;
+    let commitment = FUSION_COMMITMENTS
+        .may_load(deps.storage, commitment_id)?
+        .ok_or(ContractError::CommitmentNotFound {})?;
+
+    let seed = reveal_seed(
+        deps.as_ref(),
+        &env,
+        commitment_id,
+        &commitment.commitment,
+        commitment.committed_at,
+        &secret,
+        "",
+    )?;
+
+    Ok(draw_fusion(deps, commitment_id, commitment, Some(seed))?
+        .add_attribute("action", "reveal_fusion"))
+}
 
-    if new_config.burn_ratio != 100 {
-        deps.api
-            .addr_validate(&new_config.destination.clone().unwrap().into_string())?;
+pub fn resolve_expired_fusion(
+    deps: DepsMut,
+    env: Env,
+    commitment_id: u64,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Fuse)?;
+    let commitment = FUSION_COMMITMENTS
+        .may_load(deps.storage, commitment_id)?
+        .ok_or(ContractError::CommitmentNotFound {})?;
+
+    if env.block.height <= commitment.committed_at + REVEAL_TIMEOUT {
+        return Err(ContractError::CommitmentNotExpired {});
     }
 
-    let range = 0..100;
-    if !range.contains(&new_config.burn_ratio) {
-        return Err(ContractError::InvalidBurnRatio {});
+    //Beacon jobs that never get an answer leave the parents as they were, nobody could know their result
+    if commitment.commitment.is_none() {
+        FUSION_COMMITMENTS.remove(deps.storage, commitment_id);
+        for parent in [&commitment.parent_a, &commitment.parent_b] {
+            PENDING_CHARACTERS.remove(deps.storage, parent);
+        }
+
+        return Ok(Response::new()
+            .add_attribute("action", "cancel_fusion")
+            .add_attribute("commitment_id", commitment_id.to_string())
+            .add_attribute("owner", commitment.owner));
     }
 
-    //If we are selling anything using a non native denom, we need a destination address as we will not burn those non native tokens.
-    if new_config.empty_character_mint_price.denom != NATIVE_DENOM
-        && new_config.destination.is_none()
-    {
-        return Err(ContractError::NoMintDestination {});
+    //Fusions that are never revealed are forfeited, so walking away from a known result is worse than any draw
+    Ok(draw_fusion(deps, commitment_id, commitment, None)?
+        .add_attribute("action", "forfeit_fusion"))
+}
+
+//Consumes the parents and, given a seed, mints the child drawn from them to the owner of the fusion
+fn draw_fusion(
+    deps: DepsMut,
+    commitment_id: u64,
+    commitment: FusionCommitment,
+    seed: Option<String>,
+) -> Result<Response, ContractError> {
+    FUSION_COMMITMENTS.remove(deps.storage, commitment_id);
+    for parent in [&commitment.parent_a, &commitment.parent_b] {
+        PENDING_CHARACTERS.remove(deps.storage, parent);
     }
 
-    //If we are selling anything using a non native denom, we need a destination address as we will not burn those non native tokens.
-    if new_config
-        .character_mint_prices
-        .iter()
-        .any(|coin| coin.denom != NATIVE_DENOM)
-        && new_config.destination.is_none()
-    {
-        return Err(ContractError::NoMintDestination {});
+    let fusion = CONFIG.load(deps.storage)?.fusion;
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+    let trait_collection_address = TRAIT_COLLECTION_ADDRESS.load(deps.storage)?;
+    let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
+    let parent_a = commitment.parent_a;
+    let parent_b = commitment.parent_b;
+
+    //A parent burnt by its owner while waiting forfeits the fusion, the other one is still consumed
+    let character_a = load_pending_character(deps.as_ref(), &parent_a)?;
+    let character_b = load_pending_character(deps.as_ref(), &parent_b)?;
+
+    let mut res = Response::new();
+
+    //The traits equipped on the parents are consumed with them
+    let traits_equipped: Vec<String> = [&character_a, &character_b]
+        .into_iter()
+        .flatten()
+        .flat_map(|character| character.traits_equipped.clone().unwrap_or_default())
+        .collect();
+    if !traits_equipped.is_empty() {
+        let burn_msg = TraitExecuteMsg::<TraitExtension, Empty>::BurnMultiple {
+            token_ids: traits_equipped,
+        };
+        res = res.add_message(WasmMsg::Execute {
+            contract_addr: trait_collection_address.to_string(),
+            msg: to_binary(&burn_msg)?,
+            funds: vec![],
+        });
     }
 
-    //The mint prices and rarities arrays must be same length, 1-to-1 price/rarity
-    if new_config.character_mint_prices.len() != new_config.character_rarities.len() {
-        return Err(ContractError::NotSameLength {});
+    for (parent, character) in [(&parent_a, &character_a), (&parent_b, &character_b)] {
+        let Some(character) = character else {
+            continue;
+        };
+        let parent_msg = if fusion.burn_parents {
+            release_name(deps.storage, &character.name, parent)?;
+            CharacterExecuteMsg::<Metadata, Empty>::Burn {
+                token_id: parent.clone(),
+            }
+        } else {
+            CharacterExecuteMsg::<Metadata, Empty>::LockCharacter {
+                token_id: parent.clone(),
+            }
+        };
+        res = res.add_message(WasmMsg::Execute {
+            contract_addr: collection_address.to_string(),
+            msg: to_binary(&parent_msg)?,
+            funds: vec![],
+        });
     }
 
-    let mut config = CONFIG.load(deps.storage)?;
-    config.empty_character_mint_price = new_config.empty_character_mint_price;
-    config.burn_ratio = new_config.burn_ratio;
-    config.destination = new_config.destination;
+    res = res
+        .add_attribute("commitment_id", commitment_id.to_string())
+        .add_attribute("owner", commitment.owner.clone())
+        .add_attribute("parent_a", parent_a.clone())
+        .add_attribute("parent_b", parent_b.clone());
+    let (Some(character_a), Some(character_b), Some(seed)) = (character_a, character_b, seed)
+    else {
+        return Ok(res);
+    };
 
-    CONFIG.save(deps.storage, &config)?;
+    let slots = registered_slots(deps.as_ref())?;
+    let mut child = Metadata {
+        name: None,
+        traits: vec![],
+        rarity: None,
+        traits_equipped: None,
+        locked: false,
+    };
 
-    Ok(Response::new().add_attribute("action", "update_config"))
-}
+    for slot in &slots {
+        let slot_seed = format!("{}{}", seed, slot);
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: CharacterManagerQueryMsg) -> StdResult<Binary> {
-    match msg {
-        CharacterManagerQueryMsg::Config {} => to_binary(&query_config(deps)?),
-        CharacterManagerQueryMsg::AllowedCollectionCodeId {} => to_binary(&query_codeid(deps)?),
+        let mut mutation = None;
+        if random_number_1_to_x(format!("{}mutation", slot_seed), 100) as u64
+            <= fusion.mutation_percent
+        {
+            let values = catalog_trait_values(deps.as_ref(), &mintables_collection_address, slot)?;
+            if !values.is_empty() {
+                let position =
+                    random_number_1_to_x(format!("{}value", slot_seed), values.len() as u32);
+                mutation = values.get(position as usize - 1).cloned();
+            }
+        }
+
+        let value = match mutation {
+            Some(value) => Some(value),
+            None => {
+                let total_weight = fusion.parent_a_weight + fusion.parent_b_weight;
+                if random_number_1_to_x(format!("{}parent", slot_seed), total_weight)
+                    <= fusion.parent_a_weight
+                {
+                    character_a.trait_value(slot).cloned()
+                } else {
+                    character_b.trait_value(slot).cloned()
+                }
+            }
+        };
+        set_trait_slot(&mut child, &slots, slot, value)?;
     }
+
+    let generation = [&parent_a, &parent_b]
+        .into_iter()
+        .map(|parent| {
+            LINEAGE
+                .may_load(deps.storage, parent)
+                .map(|lineage| lineage.map_or(0, |lineage| lineage.generation))
+        })
+        .collect::<StdResult<Vec<u32>>>()?
+        .into_iter()
+        .max()
+        .unwrap_or_default()
+        + 1;
+
+    let token_id = increment_token_index(deps.storage)?.to_string();
+    LINEAGE.save(
+        deps.storage,
+        &token_id,
+        &Lineage {
+            parents: vec![parent_a, parent_b],
+            generation,
+        },
+    )?;
+
+    let mint_msg = CharacterExecuteMsg::<Metadata, Empty>::Mint {
+        token_id: token_id.clone(),
+        owner: commitment.owner.to_string(),
+        token_uri: None,
+        extension: child,
+    };
+
+    Ok(res
+        .add_message(WasmMsg::Execute {
+            contract_addr: collection_address.to_string(),
+            msg: to_binary(&mint_msg)?,
+            funds: vec![],
+        })
+        .add_attribute("token_id", token_id))
 }
 
-fn query_config(deps: Deps) -> StdResult<CharacterManagerConfigResponse<Empty>> {
+pub fn dismantle(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    character_id: String,
+    commitment: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Dismantle)?;
     let config = CONFIG.load(deps.storage)?;
-    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+    if config.dismantle.trait_manager.is_none() {
+        return Err(ContractError::DismantleDisabled {});
+    }
+    let commitment = parse_commitment(&config.randomness_source, commitment)?;
+    load_unlocked_character(deps.as_ref(), &info, &character_id)?;
+
+    //The lost slots are only drawn once the secret is revealed in a later block or the beacon delivers
+    //the randomness for it. The character is kept from being modified until then
+    let proxy = match config.randomness_source {
+        RandomnessSource::Internal => None,
+        RandomnessSource::Beacon { proxy_addr } => Some(proxy_addr),
+    };
+    let commitment_id = increment_commitment_index(deps.storage)?;
+    PENDING_ENTROPY.save(deps.storage, &env.block.height)?;
+    PENDING_CHARACTERS.save(deps.storage, &character_id, &commitment_id)?;
+    DISMANTLE_COMMITMENTS.save(
+        deps.storage,
+        commitment_id,
+        &DismantleCommitment {
+            owner: info.sender.clone(),
+            token_id: character_id.clone(),
+            commitment,
+            committed_at: env.block.height,
+            proxy: proxy.clone(),
+        },
+    )?;
 
-    Ok(CharacterManagerConfigResponse {
-        collection_address: collection_address.to_string(),
-        config,
-    })
+    let mut res = Response::new();
+    if let Some(proxy_addr) = proxy {
+        res = res.add_message(randomness_request_msg(proxy_addr, commitment_id)?);
+    }
+
+    Ok(res
+        .add_attribute("action", "dismantle")
+        .add_attribute("commitment_id", commitment_id.to_string())
+        .add_attribute("sender", info.sender)
+        .add_attribute("character_id", character_id))
 }
 
-fn query_codeid(deps: Deps) -> StdResult<AllowedCollectionCodeIdResponse> {
-    let config = CONFIG.load(deps.storage)?;
-    Ok(AllowedCollectionCodeIdResponse {
-        code_id: config.collection_code_id,
-    })
+pub fn reveal_dismantle(
+    deps: DepsMut,
+    env: Env,
+    commitment_id: u64,
+    secret: String,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Dismantle)?;
+    let commitment = DISMANTLE_COMMITMENTS
+        .may_load(deps.storage, commitment_id)?
+        .ok_or(ContractError::CommitmentNotFound {})?;
+
+    let seed = reveal_seed(
+        deps.as_ref(),
+        &env,
+        commitment_id,
+        &commitment.commitment,
+        commitment.committed_at,
+        &secret,
+        "",
+    )?;
+
+    Ok(draw_dismantle(deps, commitment_id, commitment, Some(seed))?
+        .add_attribute("action", "reveal_dismantle"))
 }
 
-// Reply callback triggered from cw721 character-onchain collection contract instantiation in instantiate()
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
-    if msg.id != INSTANTIATE_CW721_REPLY_ID {
-        return Err(ContractError::InvalidReplyID {});
+pub fn resolve_expired_dismantle(
+    deps: DepsMut,
+    env: Env,
+    commitment_id: u64,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Dismantle)?;
+    let commitment = DISMANTLE_COMMITMENTS
+        .may_load(deps.storage, commitment_id)?
+        .ok_or(ContractError::CommitmentNotFound {})?;
+
+    if env.block.height <= commitment.committed_at + REVEAL_TIMEOUT {
+        return Err(ContractError::CommitmentNotExpired {});
     }
 
-    let reply = parse_reply_instantiate_data(msg);
-    match reply {
-        Ok(res) => {
-            let collection_address = res.contract_address;
-            COLLECTION_ADDRESS.save(deps.storage, &Addr::unchecked(collection_address.clone()))?;
-            Ok(Response::default()
-                .add_attribute("action", "instantiate_sg721_reply")
-                .add_attribute("cw721_character_collection_address", collection_address))
-        }
-        Err(_) => Err(ContractError::InstantiateError {}),
+    //Beacon jobs that never get an answer leave the character as it was, nobody could know their result
+    if commitment.commitment.is_none() {
+        DISMANTLE_COMMITMENTS.remove(deps.storage, commitment_id);
+        PENDING_CHARACTERS.remove(deps.storage, &commitment.token_id);
+
+        return Ok(Response::new()
+            .add_attribute("action", "cancel_dismantle")
+            .add_attribute("commitment_id", commitment_id.to_string())
+            .add_attribute("owner", commitment.owner));
     }
+
+    //Dismantlings that are never revealed are forfeited, so walking away from a known result is worse than any draw
+    Ok(draw_dismantle(deps, commitment_id, commitment, None)?
+        .add_attribute("action", "forfeit_dismantle"))
 }
 
-//We get around using random libraries by importing the things we need from
-//https://docs.rs/rand/0.8.1/i686-unknown-linux-gnu/src/rand/rngs/xoshiro128plusplus.rs.html
+//Burns the character and, given a seed, gives its slots that are not lost back to the owner as traits.
+//Without a seed the equipped traits are burnt with the character
+fn draw_dismantle(
+    deps: DepsMut,
+    commitment_id: u64,
+    commitment: DismantleCommitment,
+    seed: Option<String>,
+) -> Result<Response, ContractError> {
+    DISMANTLE_COMMITMENTS.remove(deps.storage, commitment_id);
+    PENDING_CHARACTERS.remove(deps.storage, &commitment.token_id);
+
+    let character_id = commitment.token_id;
+    let res = Response::new()
+        .add_attribute("commitment_id", commitment_id.to_string())
+        .add_attribute("owner", commitment.owner.clone())
+        .add_attribute("character_id", character_id.clone());
+
+    //A character burnt by its owner while waiting has nothing left to give back
+    let Some(character) = load_pending_character(deps.as_ref(), &character_id)? else {
+        return Ok(res);
+    };
 
-fn random_number_1_to_x(env: &Env, sender: String, array_len: u32, x: u32) -> u32 {
-    let sha256 = Sha256::digest(
-        format!("{}{}{}{}", sender, env.block.time.nanos(), env.block.height, array_len).into_bytes(),
-    );
-    // Cut first 16 bytes from 32 byte value
-    let randomness: [u8; 16] = sha256.to_vec()[0..16].try_into().unwrap();
-    let mut state = [0; 4];
-    read_u32_into(&randomness, &mut state);
-    let rng = get_u32(&mut state);
-    let a_number = rng.checked_rem_euclid(x).unwrap() + 1;
+    let dismantle = CONFIG.load(deps.storage)?.dismantle;
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+    let trait_collection_address = TRAIT_COLLECTION_ADDRESS.load(deps.storage)?;
+    let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
 
-    a_number
-}
+    release_name(deps.storage, &character.name, &character_id)?;
+    let burn_msg = CharacterExecuteMsg::<Metadata, Empty>::Burn {
+        token_id: character_id.clone(),
+    };
+    let mut res = res.add_message(WasmMsg::Execute {
+        contract_addr: collection_address.to_string(),
+        msg: to_binary(&burn_msg)?,
+        funds: vec![],
+    });
 
-pub fn read_u32_into(src: &[u8], dst: &mut [u32]) {
-    assert!(src.len() >= 4 * dst.len());
-    for (out, chunk) in dst.iter_mut().zip(src.chunks_exact(4)) {
-        *out = u32::from_le_bytes(chunk.try_into().unwrap());
+    let traits_equipped = character.traits_equipped.clone().unwrap_or_default();
+    let Some(seed) = seed else {
+        if !traits_equipped.is_empty() {
+            let burn_msg = TraitExecuteMsg::<TraitExtension, Empty>::BurnMultiple {
+                token_ids: traits_equipped,
+            };
+            res = res.add_message(WasmMsg::Execute {
+                contract_addr: trait_collection_address.to_string(),
+                msg: to_binary(&burn_msg)?,
+                funds: vec![],
+            });
+        }
+        return Ok(res);
+    };
+    let trait_manager = dismantle
+        .trait_manager
+        .clone()
+        .ok_or(ContractError::DismantleDisabled {})?;
+
+    //Slots filled by equipped traits give those traits back instead of minting new ones
+    let mut equipped_types = vec![];
+    for one_trait_id in traits_equipped.iter() {
+        let trait_info = query_trait(deps.as_ref(), &trait_collection_address, one_trait_id)?;
+        equipped_types.push(trait_info.info.extension.trait_type);
     }
-}
 
-fn get_u32(dst: &mut [u32]) -> u32 {
-    let result_starstar = dst[0]
-        .wrapping_add(dst[3])
-        .rotate_left(7)
-        .wrapping_add(dst[0]);
+    let mut token_infos = vec![];
+    let mut lost = vec![];
+    //Fused characters inherit their slots without minting traits for them, and the parents may be kept.
+    //Only their equipped traits are given back, so fusing and dismantling does not create traits
+    let fused = LINEAGE.has(deps.storage, &character_id);
+    for (slot, value) in character.traits {
+        if equipped_types.contains(&slot) {
+            continue;
+        }
+        if fused {
+            lost.push(slot);
+            continue;
+        }
+
+        //The rarity of the trait comes from mintables, values that are not listed there are lost
+        let rarity =
+            catalog_trait_rarity(deps.as_ref(), &mintables_collection_address, &slot, &value)?;
+        let rarity = match rarity {
+            Some(rarity)
+                if random_number_1_to_x(format!("{}{}", seed, slot), 100) as u64
+                    > dismantle.loss_percent(&rarity) =>
+            {
+                rarity
+            }
+            _ => {
+                lost.push(slot);
+                continue;
+            }
+        };
 
-    let t = dst[1] << 9;
+        token_infos.push(TraitExtension {
+            trait_type: slot,
+            trait_value: value,
+            trait_rarity: rarity,
+        });
+    }
 
-    dst[2] ^= dst[0];
-    dst[3] ^= dst[1];
-    dst[1] ^= dst[2];
-    dst[0] ^= dst[3];
+    if !traits_equipped.is_empty() {
+        res = res.add_message(release_escrow_msg(
+            &trait_collection_address,
+            traits_equipped,
+            &commitment.owner,
+        )?);
+    }
 
-    dst[2] ^= t;
+    if !token_infos.is_empty() {
+        let mint_msg = TraitManagerMintMsg::MintTraits {
+            receiver: commitment.owner.to_string(),
+            token_infos,
+        };
+        res = res.add_message(WasmMsg::Execute {
+            contract_addr: trait_manager.to_string(),
+            msg: to_binary(&mint_msg)?,
+            funds: vec![],
+        });
+    }
 
-    dst[3] = dst[3].rotate_left(11);
+    Ok(res.add_attribute("lost", lost.join(",")))
+}
 
-    result_starstar
+//Rarity of the mintables trait with the type and value, if any
+fn catalog_trait_rarity(
+    deps: Deps,
+    mintables_collection_address: &Addr,
+    trait_type: &str,
+    trait_value: &str,
+) -> StdResult<Option<String>> {
+    let traits_response: TraitsResp = deps.querier.query_wasm_smart(
+        mintables_collection_address,
+        &QueryMsg::TraitsBy {
+            trait_type: Some(trait_type.to_string()),
+            trait_value: Some(trait_value.to_string()),
+            rarity: None,
+            denom: None,
+            start_after: None,
+            limit: Some(1),
+        },
+    )?;
+    Ok(traits_response
+        .traits
+        .into_iter()
+        .next()
+        .map(|t| t.trait_rarity))
+}
+
+//Values of every mintables trait of the type, the catalog is paginated
+fn catalog_trait_values(
+    deps: Deps,
+    mintables_collection_address: &Addr,
+    trait_type: &str,
+) -> StdResult<Vec<String>> {
+    let mut values = vec![];
+    let mut start_after = None;
+    loop {
+        let traits_response: TraitsResp = deps.querier.query_wasm_smart(
+            mintables_collection_address,
+            &QueryMsg::TraitsBy {
+                trait_type: Some(trait_type.to_string()),
+                trait_value: None,
+                rarity: None,
+                denom: None,
+                start_after,
+                limit: Some(CATALOG_PAGE_LIMIT),
+            },
+        )?;
+
+        match traits_response.traits.last() {
+            Some(last) => start_after = Some(last.id),
+            None => return Ok(values),
+        }
+        values.extend(traits_response.traits.into_iter().map(|t| t.trait_value));
+    }
+}
+
+fn load_unlocked_character(
+    deps: Deps,
+    info: &MessageInfo,
+    character_id: &str,
+) -> Result<CharacterInfoResponse<Extension>, ContractError> {
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+
+    let character_response: CharacterInfoResponse<Extension> = deps.querier.query_wasm_smart(
+        collection_address,
+        &CharacterQueryMsg::<Empty>::CharacterInfo {
+            token_id: character_id.to_string(),
+        },
+    )?;
+
+    if character_response.owner != info.sender {
+        return Err(ContractError::NotCharacterOwner {});
+    }
+
+    if character_response.token_info.locked {
+        return Err(ContractError::CharacterAlreadyLocked {});
+    }
+
+    if PENDING_CHARACTERS.has(deps.storage, character_id) {
+        return Err(ContractError::CharacterPending {});
+    }
+
+    Ok(character_response)
+}
+
+//Characters waiting for a draw can only change by being burnt by their owner through the collection
+fn load_pending_character(deps: Deps, character_id: &str) -> StdResult<Option<Extension>> {
+    if !character_exists(deps.storage, deps.querier, character_id)? {
+        return Ok(None);
+    }
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+
+    let character_response: CharacterInfoResponse<Extension> = deps.querier.query_wasm_smart(
+        collection_address,
+        &CharacterQueryMsg::<Empty>::CharacterInfo {
+            token_id: character_id.to_string(),
+        },
+    )?;
+    Ok(Some(character_response.token_info))
+}
+
+fn query_trait(
+    deps: Deps,
+    trait_collection_address: &Addr,
+    trait_id: &str,
+) -> StdResult<AllNftInfoResponse<TraitExtension>> {
+    deps.querier.query_wasm_smart(
+        trait_collection_address,
+        &Cw721QueryMsg::AllNftInfo {
+            token_id: trait_id.to_string(),
+            include_expired: None,
+        },
+    )
+}
+
+//Sets the character slot matching the trait type, filled slots are kept in the order of the registry
+fn set_trait_slot(
+    character: &mut Metadata,
+    slots: &[String],
+    trait_type: &str,
+    value: Option<String>,
+) -> Result<(), ContractError> {
+    character.traits.retain(|(slot, _)| slot != trait_type);
+    if let Some(value) = value {
+        if !slots.iter().any(|slot| slot == trait_type) {
+            return Err(ContractError::InvalidTrait {});
+        }
+        character.traits.push((trait_type.to_string(), value));
+        character
+            .traits
+            .sort_by_key(|(slot, _)| slots.iter().position(|registered| registered == slot));
+    }
+    Ok(())
+}
+
+//Traits dropped from the registry cannot be equipped anymore
+fn assert_registered_trait(deps: Deps, trait_info: &TraitExtension) -> Result<(), ContractError> {
+    let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
+    deps.querier
+        .query_wasm_smart::<TraitValueInfo>(
+            mintables_collection_address,
+            &QueryMsg::TraitValue {
+                trait_type: trait_info.trait_type.clone(),
+                trait_value: trait_info.trait_value.clone(),
+            },
+        )
+        .map_err(|_| ContractError::InvalidTrait {})?;
+    Ok(())
+}
+
+//Trait slots registered in mintables
+fn registered_slots(deps: Deps) -> StdResult<Vec<String>> {
+    let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
+    let slots_response: SlotsResp = deps
+        .querier
+        .query_wasm_smart(mintables_collection_address, &QueryMsg::Slots {})?;
+    Ok(slots_response.slots)
+}
+
+fn modify_msg(
+    collection_address: &Addr,
+    character_id: &str,
+    new_values: Metadata,
+) -> StdResult<CosmosMsg> {
+    let modify_msg = CharacterExecuteMsg::<Metadata, Empty>::Modify {
+        token_id: character_id.to_string(),
+        new_values,
+    };
+
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: collection_address.to_string(),
+        msg: to_binary(&modify_msg)?,
+        funds: vec![],
+    }))
+}
+
+fn escrow_msg(trait_collection_address: &Addr, token_ids: Vec<String>) -> StdResult<CosmosMsg> {
+    let escrow_msg = TraitExecuteMsg::<TraitExtension, Empty>::Escrow { token_ids };
+
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: trait_collection_address.to_string(),
+        msg: to_binary(&escrow_msg)?,
+        funds: vec![],
+    }))
+}
+
+//Released traits are given to the owner of the character they were equipped on
+fn release_escrow_msg(
+    trait_collection_address: &Addr,
+    token_ids: Vec<String>,
+    recipient: &Addr,
+) -> StdResult<CosmosMsg> {
+    let release_msg = TraitExecuteMsg::<TraitExtension, Empty>::ReleaseEscrow {
+        token_ids,
+        recipient: Some(recipient.to_string()),
+    };
+
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: trait_collection_address.to_string(),
+        msg: to_binary(&release_msg)?,
+        funds: vec![],
+    }))
+}
+
+//Counts a purchase against the caps of the buyer across the manager and within the block
+fn record_address_mint(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    limits: &MintLimits,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let minted = ADDRESS_MINTS.may_load(storage, sender)?.unwrap_or_default();
+    if !limits.address_allows(minted) {
+        return Err(ContractError::MintLimitReached {});
+    }
+    ADDRESS_MINTS.save(storage, sender, &(minted + 1))?;
+
+    if limits.per_block.is_some() {
+        let in_block = block_mints(BLOCK_MINTS.may_load(storage, sender)?, block.height);
+        if !limits.block_allows(in_block) {
+            return Err(ContractError::BlockMintLimitReached {});
+        }
+        BLOCK_MINTS.save(storage, sender, &(block.height, in_block + 1))?;
+    }
+
+    if limits.block_ceiling.is_some() {
+        let block_total = block_mints(BLOCK_TOTAL_MINTS.may_load(storage)?, block.height);
+        if !limits.ceiling_allows(block_total) {
+            return Err(ContractError::BlockCeilingReached {});
+        }
+        BLOCK_TOTAL_MINTS.save(storage, &(block.height, block_total + 1))?;
+    }
+
+    Ok(())
+}
+
+//Counts a purchase against the per address cap of the catalog entry
+fn record_item_mint(
+    storage: &mut dyn Storage,
+    item_mints: &Map<(&Addr, u32), u32>,
+    sender: &Addr,
+    item: &impl Supply,
+) -> Result<(), ContractError> {
+    let minted = item_mints
+        .may_load(storage, (sender, item.id()))?
+        .unwrap_or_default();
+    if !item.address_allows(minted) {
+        return Err(ContractError::MintLimitReached {});
+    }
+    item_mints.save(storage, (sender, item.id()), &(minted + 1))?;
+    Ok(())
+}
+
+//Takes back a purchase that was refunded from the caps of the buyer, the block caps only if it is still the purchase block
+fn release_address_mint(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    purchased_at: u64,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let minted = ADDRESS_MINTS.may_load(storage, sender)?.unwrap_or_default();
+    ADDRESS_MINTS.save(storage, sender, &minted.saturating_sub(1))?;
+
+    if block.height == purchased_at {
+        if let Some((height, in_block)) = BLOCK_MINTS.may_load(storage, sender)? {
+            if height == purchased_at {
+                BLOCK_MINTS.save(storage, sender, &(height, in_block.saturating_sub(1)))?;
+            }
+        }
+        if let Some((height, block_total)) = BLOCK_TOTAL_MINTS.may_load(storage)? {
+            if height == purchased_at {
+                BLOCK_TOTAL_MINTS.save(storage, &(height, block_total.saturating_sub(1)))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+//Takes back a refunded purchase from the per address cap of the catalog entry
+fn release_item_mint(
+    storage: &mut dyn Storage,
+    item_mints: &Map<(&Addr, u32), u32>,
+    sender: &Addr,
+    id: u32,
+) -> Result<(), ContractError> {
+    let minted = item_mints
+        .may_load(storage, (sender, id))?
+        .unwrap_or_default();
+    item_mints.save(storage, (sender, id), &minted.saturating_sub(1))?;
+    Ok(())
+}
+
+//Checks the sender's allowlist proof and counts the mint against its limit in the phase
+fn record_presale_mint(
+    storage: &mut dyn Storage,
+    sender: &Addr,
+    phase_id: u32,
+    proof: &[String],
+) -> Result<PresalePhase, ContractError> {
+    let phase = PRESALE_PHASES
+        .may_load(storage, phase_id)?
+        .ok_or(ContractError::PresalePhaseNotFound {})?;
+
+    if !phase.allows(sender.as_str(), proof)? {
+        return Err(ContractError::NotAllowlisted {});
+    }
+
+    let minted = PRESALE_MINTS
+        .may_load(storage, (phase_id, sender))?
+        .unwrap_or_default();
+    if phase.remaining(minted) == 0 {
+        return Err(ContractError::PresaleLimitReached {});
+    }
+    PRESALE_MINTS.save(storage, (phase_id, sender), &(minted + 1))?;
+
+    Ok(phase)
+}
+
+fn mint_price(price: &Coin, presale_phase: &Option<PresalePhase>) -> Coin {
+    match presale_phase {
+        Some(phase) => phase.discounted_price(price),
+        None => price.clone(),
+    }
+}
+
+//The catalog character with the id, if the token info describes it
+fn find_mintable_character(
+    deps: Deps,
+    mintables_collection_address: &Addr,
+    character_id: u32,
+    token_info: &Extension,
+) -> Option<Character> {
+    let character: Character = deps
+        .querier
+        .query_wasm_smart(
+            mintables_collection_address,
+            &QueryMsg::Character { id: character_id },
+        )
+        .ok()?;
+
+    //Catalog slots are unique, so the same length and values means the same character
+    let matches = token_info.rarity.as_ref() == Some(&character.rarity)
+        && character.traits.len() == token_info.traits.len()
+        && token_info
+            .traits
+            .iter()
+            .all(|t| character.traits.contains(t))
+        && character.locked == token_info.locked;
+    matches.then_some(character)
+}
+
+fn record_mint_msg(
+    mintables_collection_address: &Addr,
+    kind: MintableKind,
+    id: u32,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: mintables_collection_address.to_string(),
+        msg: to_binary(&MintablesExecuteMsg::RecordMint { kind, id })?,
+        funds: vec![],
+    }))
+}
+
+fn release_mint_msg(
+    mintables_collection_address: &Addr,
+    kind: MintableKind,
+    id: u32,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: mintables_collection_address.to_string(),
+        msg: to_binary(&MintablesExecuteMsg::ReleaseMint { kind, id })?,
+        funds: vec![],
+    }))
+}
+
+fn validate_revenue_split(api: &dyn Api, split: &RevenueSplit) -> Result<(), ContractError> {
+    if !split.is_valid(api)? {
+        return Err(ContractError::InvalidRevenueSplit {});
+    }
+    Ok(())
+}
+
+fn validate_dismantle(api: &dyn Api, dismantle: &DismantleConfig) -> Result<(), ContractError> {
+    if let Some(trait_manager) = &dismantle.trait_manager {
+        api.addr_validate(trait_manager.as_str())?;
+    }
+    if dismantle
+        .loss_percents
+        .iter()
+        .any(|(_, percent)| *percent > 100)
+    {
+        return Err(ContractError::InvalidDismantleConfig {});
+    }
+    Ok(())
+}
+
+fn validate_names(names: &NameConfig) -> Result<(), ContractError> {
+    if names.min_length == 0 || names.min_length > names.max_length {
+        return Err(ContractError::InvalidNameConfig {});
+    }
+    Ok(())
+}
+
+fn validate_fusion(fusion: &FusionConfig) -> Result<(), ContractError> {
+    let total_weight = fusion.parent_a_weight as u64 + fusion.parent_b_weight as u64;
+    if total_weight == 0 || total_weight > u32::MAX as u64 || fusion.mutation_percent > 100 {
+        return Err(ContractError::InvalidFusionConfig {});
+    }
+    Ok(())
+}
+
+//Records the payment in the revenue totals of its denom and pays out what is not accrued
+fn pay_out(
+    storage: &mut dyn Storage,
+    split: &RevenueSplit,
+    payment: &Payment,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let denom = &payment.coin().denom;
+    let mut totals = REVENUE
+        .may_load(storage, denom)?
+        .unwrap_or_else(|| RevenueTotals::new(payment));
+    let msgs = split.pay_out(&mut totals, payment)?;
+    REVENUE.save(storage, denom, &totals)?;
+
+    Ok(msgs)
+}
+
+pub fn update_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    action: cw_ownable::Action,
+) -> Result<Response, ContractError> {
+    let ownership = cw_ownable::update_ownership(deps, &env.block, &info.sender, action)?;
+    Ok(Response::new().add_attributes(ownership.into_attributes()))
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_config: UpdateCharacterManagerParamsMsg,
+) -> Result<Response, ContractError> {
+    //Only owner can update config
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+    validate_revenue_split(deps.api, &new_config.revenue_split)?;
+    validate_fusion(&new_config.fusion)?;
+    validate_dismantle(deps.api, &new_config.dismantle)?;
+    validate_names(&new_config.names)?;
+
+    //The mint prices and rarities arrays must be same length, 1-to-1 price/rarity
+    if new_config.character_mint_prices.len() != new_config.character_rarities.len() {
+        return Err(ContractError::NotSameLength {});
+    }
+
+    //The randomness beacon proxy must be a valid address as it is the only one allowed to deliver randomness
+    if let RandomnessSource::Beacon { proxy_addr } = &new_config.randomness_source {
+        deps.api.addr_validate(proxy_addr.as_str())?;
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    config.empty_character_mint_price = new_config.empty_character_mint_price;
+    config.character_mint_prices = new_config.character_mint_prices;
+    config.character_rarities = new_config.character_rarities;
+    config.revenue_split = new_config.revenue_split;
+    config.mint_limits = new_config.mint_limits;
+    config.randomness_source = new_config.randomness_source;
+    config.fusion = new_config.fusion;
+    config.dismantle = new_config.dismantle;
+    config.names = new_config.names;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+pub fn set_presale_phase(
+    deps: DepsMut,
+    info: MessageInfo,
+    phase_id: u32,
+    phase: Option<PresalePhase>,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    match phase {
+        Some(phase) => {
+            parse_hash(&phase.merkle_root)?;
+            if phase.discount_percent > 100 {
+                return Err(ContractError::InvalidDiscount {});
+            }
+            PRESALE_PHASES.save(deps.storage, phase_id, &phase)?;
+        }
+        None => PRESALE_PHASES.remove(deps.storage, phase_id),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_presale_phase")
+        .add_attribute("phase_id", phase_id.to_string()))
+}
+
+pub fn withdraw(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    amount: Uint128,
+    to: String,
+) -> Result<Response, ContractError> {
+    //Accrued funds belong to every recipient of the split, so only the owner sends them out
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let to = deps.api.addr_validate(&to)?;
+    let mut totals = REVENUE
+        .may_load(deps.storage, &denom)?
+        .ok_or(ContractError::InvalidWithdrawAmount {})?;
+    let msg = totals
+        .withdraw(&denom, amount, &to)?
+        .ok_or(ContractError::InvalidWithdrawAmount {})?;
+    REVENUE.save(deps.storage, &denom, &totals)?;
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "withdraw")
+        .add_attribute("denom", denom)
+        .add_attribute("amount", amount)
+        .add_attribute("to", to))
+}
+
+pub fn set_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    operations: Option<Vec<Operation>>,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    if GUARDIAN.may_load(deps.storage)?.as_ref() != Some(&info.sender) {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+    }
+
+    let operations = operations.unwrap_or_else(Operation::all);
+    let paused_operations = Operation::update_paused(
+        PAUSED.may_load(deps.storage)?.unwrap_or_default(),
+        &operations,
+        paused,
+    );
+    PAUSED.save(deps.storage, &paused_operations)?;
+
+    //The character collection only lets its minter, this manager, pause transfers
+    let mut res = Response::new();
+    if operations.contains(&Operation::Transfer) {
+        let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+        res = res.add_message(WasmMsg::Execute {
+            contract_addr: collection_address.to_string(),
+            msg: to_binary(&CharacterExecuteMsg::<Metadata, Empty>::PauseTransfers { paused })?,
+            funds: vec![],
+        });
+    }
+
+    Ok(res
+        .add_attribute("action", if paused { "pause" } else { "unpause" })
+        .add_attribute("sender", info.sender))
+}
+
+pub fn set_guardian(
+    deps: DepsMut,
+    info: MessageInfo,
+    guardian: Option<String>,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    match &guardian {
+        Some(guardian) => GUARDIAN.save(deps.storage, &deps.api.addr_validate(guardian)?)?,
+        None => GUARDIAN.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_guardian")
+        .add_attribute("guardian", guardian.unwrap_or_default()))
+}
+
+pub fn add_moderators(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_moderators: Vec<String>,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let mut moderators = MODERATORS.may_load(deps.storage)?.unwrap_or_default();
+    for new_moderator in new_moderators {
+        let new_moderator = deps.api.addr_validate(&new_moderator)?;
+        if !moderators.contains(&new_moderator) {
+            moderators.push(new_moderator);
+        }
+    }
+    MODERATORS.save(deps.storage, &moderators)?;
+
+    Ok(Response::new().add_attribute("action", "add_moderators"))
+}
+
+pub fn remove_moderators(
+    deps: DepsMut,
+    info: MessageInfo,
+    moderator_addresses: Vec<String>,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let mut moderators = MODERATORS.may_load(deps.storage)?.unwrap_or_default();
+    moderators.retain(|m| !moderator_addresses.contains(&m.to_string()));
+    MODERATORS.save(deps.storage, &moderators)?;
+
+    Ok(Response::new().add_attribute("action", "remove_moderators"))
+}
+
+fn assert_moderator(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    let moderators = MODERATORS.may_load(storage)?.unwrap_or_default();
+    if !moderators.contains(sender) {
+        cw_ownable::assert_owner(storage, sender).map_err(|_| ContractError::NotModerator {})?;
+    }
+    Ok(())
+}
+
+//Moderation is not stopped by pausing renames, the reset goes through the collection like a rename
+pub fn force_reset_name(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    character_id: String,
+    reason: String,
+) -> Result<Response, ContractError> {
+    assert_moderator(deps.storage, &info.sender)?;
+    if reason.trim().is_empty() {
+        return Err(ContractError::MissingReason {});
+    }
+
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+    let character_response: CharacterInfoResponse<Extension> = deps.querier.query_wasm_smart(
+        collection_address.clone(),
+        &CharacterQueryMsg::<Empty>::CharacterInfo {
+            token_id: character_id.clone(),
+        },
+    )?;
+    let previous_name = character_response.token_info.name.clone();
+
+    release_name(deps.storage, &previous_name, &character_id)?;
+    //The moderated name cannot be taken again, and the reset counts as a rename for the cooldown
+    if let Some(name) = &previous_name {
+        BLOCKED_NAMES.save(deps.storage, &name.to_lowercase(), &Empty {})?;
+    }
+    LAST_RENAMES.save(deps.storage, &character_id, &env.block.time.seconds())?;
+
+    let mut history = MODERATION_HISTORY
+        .may_load(deps.storage, &character_id)?
+        .unwrap_or_default();
+    history.push(Moderation {
+        moderator: info.sender.clone(),
+        previous_name: previous_name.clone(),
+        reason: reason.clone(),
+        height: env.block.height,
+    });
+    MODERATION_HISTORY.save(deps.storage, &character_id, &history)?;
+
+    let new_character_info = Metadata {
+        name: None,
+        ..character_response.token_info
+    };
+    let msg = modify_msg(&collection_address, &character_id, new_character_info)?;
+
+    let event = Event::new("force_reset_name")
+        .add_attribute("moderator", info.sender.clone())
+        .add_attribute("token_id", character_id.clone())
+        .add_attribute("previous_name", previous_name.unwrap_or_default())
+        .add_attribute("reason", reason);
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_event(event)
+        .add_attribute("action", "force_reset_name")
+        .add_attribute("sender", info.sender)
+        .add_attribute("character_id", character_id))
+}
+
+pub fn unblock_name(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+) -> Result<Response, ContractError> {
+    assert_moderator(deps.storage, &info.sender)?;
+    BLOCKED_NAMES.remove(deps.storage, &name.to_lowercase());
+
+    Ok(Response::new()
+        .add_attribute("action", "unblock_name")
+        .add_attribute("name", name))
+}
+
+//Names given before the index existed are added a page of characters at a time.
+//When characters share a name the first one indexed keeps it
+pub fn index_names(
+    deps: DepsMut,
+    info: MessageInfo,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+    let tokens: TokensResponse = deps.querier.query_wasm_smart(
+        &collection_address,
+        &CharacterQueryMsg::<Empty>::AllTokens { start_after, limit },
+    )?;
+
+    let mut indexed = 0u32;
+    for token_id in &tokens.tokens {
+        let character: CharacterInfoResponse<Extension> = deps.querier.query_wasm_smart(
+            &collection_address,
+            &CharacterQueryMsg::<Empty>::CharacterInfo {
+                token_id: token_id.clone(),
+            },
+        )?;
+        let key = match character.token_info.name {
+            Some(name) => name.to_lowercase(),
+            None => continue,
+        };
+        if let Some(holder) = NAMES.may_load(deps.storage, &key)? {
+            if holder == *token_id || character_exists(deps.storage, deps.querier, &holder)? {
+                continue;
+            }
+        }
+        NAMES.save(deps.storage, &key, token_id)?;
+        indexed += 1;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "index_names")
+        .add_attribute("indexed", indexed.to_string())
+        .add_attribute(
+            "last_token_id",
+            tokens.tokens.last().cloned().unwrap_or_default(),
+        ))
+}
+
+fn assert_not_paused(storage: &dyn Storage, operation: Operation) -> Result<(), ContractError> {
+    if PAUSED
+        .may_load(storage)?
+        .unwrap_or_default()
+        .contains(&operation)
+    {
+        return Err(ContractError::OperationPaused {});
+    }
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: CharacterManagerQueryMsg) -> StdResult<Binary> {
+    match msg {
+        CharacterManagerQueryMsg::Config {} => to_binary(&query_config(deps)?),
+        CharacterManagerQueryMsg::AllowedCollectionCodeId {} => to_binary(&query_codeid(deps)?),
+        CharacterManagerQueryMsg::LootboxCommitment { commitment_id } => {
+            to_binary(&query_lootbox_commitment(deps, commitment_id)?)
+        }
+        CharacterManagerQueryMsg::PresalePhase { phase_id } => {
+            to_binary(&PRESALE_PHASES.load(deps.storage, phase_id)?)
+        }
+        CharacterManagerQueryMsg::PresaleMints { phase_id, address } => {
+            to_binary(&query_presale_mints(deps, phase_id, address)?)
+        }
+        CharacterManagerQueryMsg::Revenue { denom } => to_binary(&query_revenue(deps, denom)?),
+        CharacterManagerQueryMsg::AllRevenue {} => to_binary(&query_all_revenue(deps)?),
+        CharacterManagerQueryMsg::MintAllowance { address, item } => {
+            to_binary(&query_mint_allowance(deps, env, address, item)?)
+        }
+        CharacterManagerQueryMsg::PauseStatus {} => to_binary(&query_pause_status(deps)?),
+        CharacterManagerQueryMsg::Lineage { token_id } => {
+            to_binary(&query_lineage(deps, token_id)?)
+        }
+        CharacterManagerQueryMsg::ResolveName { name } => {
+            to_binary(&query_resolve_name(deps, name)?)
+        }
+        CharacterManagerQueryMsg::Moderators {} => to_binary(&query_moderators(deps)?),
+        CharacterManagerQueryMsg::ModerationHistory { token_id } => {
+            to_binary(&query_moderation_history(deps, token_id)?)
+        }
+    }
+}
+
+fn query_config(deps: Deps) -> StdResult<CharacterManagerConfigResponse<Empty>> {
+    let config = CONFIG.load(deps.storage)?;
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+
+    Ok(CharacterManagerConfigResponse {
+        collection_address: collection_address.to_string(),
+        config,
+    })
+}
+
+fn query_codeid(deps: Deps) -> StdResult<AllowedCollectionCodeIdResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(AllowedCollectionCodeIdResponse {
+        code_id: config.collection_code_id,
+    })
+}
+
+fn query_presale_mints(
+    deps: Deps,
+    phase_id: u32,
+    address: String,
+) -> StdResult<PresaleMintsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let phase = PRESALE_PHASES.load(deps.storage, phase_id)?;
+    let minted = PRESALE_MINTS
+        .may_load(deps.storage, (phase_id, &address))?
+        .unwrap_or_default();
+
+    Ok(PresaleMintsResponse {
+        minted,
+        remaining: phase.remaining(minted),
+    })
+}
+
+fn query_revenue(deps: Deps, denom: String) -> StdResult<RevenueResponse> {
+    let totals = REVENUE.may_load(deps.storage, &denom)?.unwrap_or_default();
+    Ok(totals.response(denom))
+}
+
+fn query_all_revenue(deps: Deps) -> StdResult<Vec<RevenueResponse>> {
+    REVENUE
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|entry| entry.map(|(denom, totals)| totals.response(denom)))
+        .collect()
+}
+
+fn query_mint_allowance(
+    deps: Deps,
+    env: Env,
+    address: String,
+    item: Option<SaleItem>,
+) -> StdResult<MintAllowanceResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let limits = CONFIG.load(deps.storage)?.mint_limits;
+
+    let minted = ADDRESS_MINTS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let in_block = block_mints(
+        BLOCK_MINTS.may_load(deps.storage, &address)?,
+        env.block.height,
+    );
+    let block_total = block_mints(BLOCK_TOTAL_MINTS.may_load(deps.storage)?, env.block.height);
+    let mut remaining: Vec<u32> = limits
+        .remaining(minted, in_block, block_total)
+        .into_iter()
+        .collect();
+
+    let mut item_minted = 0;
+    if let Some(item) = item {
+        let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
+        let querier = deps.querier;
+        let (item_mints, id, limit) = match item {
+            SaleItem::Single { id } => {
+                let entry: Character = querier
+                    .query_wasm_smart(&mintables_collection_address, &QueryMsg::Character { id })?;
+                (&CHARACTER_MINTS, id, entry.per_address_limit)
+            }
+            SaleItem::Bundle { id } => {
+                let entry: CharacterBundle = querier.query_wasm_smart(
+                    &mintables_collection_address,
+                    &QueryMsg::CharacterBundle { id },
+                )?;
+                (&BUNDLE_MINTS, id, entry.per_address_limit)
+            }
+            SaleItem::Lootbox { id } => {
+                let entry: CharacterLootbox = querier.query_wasm_smart(
+                    &mintables_collection_address,
+                    &QueryMsg::CharacterLootbox { id },
+                )?;
+                (&LOOTBOX_MINTS, id, entry.per_address_limit)
+            }
+        };
+        item_minted = item_mints
+            .may_load(deps.storage, (&address, id))?
+            .unwrap_or_default();
+        if let Some(limit) = limit {
+            remaining.push(limit.saturating_sub(item_minted));
+        }
+    }
+
+    Ok(MintAllowanceResponse {
+        minted,
+        item_minted,
+        remaining: remaining.into_iter().min(),
+    })
+}
+
+fn query_pause_status(deps: Deps) -> StdResult<PauseStatusResponse> {
+    Ok(PauseStatusResponse {
+        paused: PAUSED.may_load(deps.storage)?.unwrap_or_default(),
+        guardian: GUARDIAN
+            .may_load(deps.storage)?
+            .map(|guardian| guardian.into_string()),
+    })
+}
+
+fn query_resolve_name(deps: Deps, name: String) -> StdResult<ResolveNameResponse> {
+    let token_id = match NAMES.may_load(deps.storage, &name.to_lowercase())? {
+        Some(token_id) if character_exists(deps.storage, deps.querier, &token_id)? => {
+            Some(token_id)
+        }
+        _ => None,
+    };
+    Ok(ResolveNameResponse { token_id })
+}
+
+fn query_moderators(deps: Deps) -> StdResult<Vec<String>> {
+    Ok(MODERATORS
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|moderator| moderator.into_string())
+        .collect())
+}
+
+fn query_moderation_history(deps: Deps, token_id: String) -> StdResult<Vec<ModerationResponse>> {
+    Ok(MODERATION_HISTORY
+        .may_load(deps.storage, &token_id)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|moderation| ModerationResponse {
+            moderator: moderation.moderator.into_string(),
+            previous_name: moderation.previous_name,
+            reason: moderation.reason,
+            height: moderation.height,
+        })
+        .collect())
+}
+
+fn query_lineage(deps: Deps, token_id: String) -> StdResult<LineageResponse> {
+    Ok(match LINEAGE.may_load(deps.storage, &token_id)? {
+        Some(lineage) => LineageResponse {
+            parents: lineage.parents,
+            generation: lineage.generation,
+        },
+        None => LineageResponse {
+            parents: vec![],
+            generation: 0,
+        },
+    })
+}
+
+fn query_lootbox_commitment(
+    deps: Deps,
+    commitment_id: u64,
+) -> StdResult<LootboxCommitmentResponse> {
+    let commitment = LOOTBOX_COMMITMENTS.load(deps.storage, commitment_id)?;
+    Ok(LootboxCommitmentResponse {
+        owner: commitment.owner.to_string(),
+        receiver: commitment.receiver.to_string(),
+        lootbox_id: commitment.lootbox.id,
+        commitment: commitment.commitment,
+        committed_at: commitment.committed_at,
+        expires_at: commitment.committed_at + REVEAL_TIMEOUT,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous = ensure_from_older_version(deps.storage)?;
+    if previous < Version::new(0, 2, 0) {
+        migrate_legacy_config(deps.storage)?;
+    }
+    if previous < Version::new(0, 3, 0) {
+        migrate_lootbox_commitments(deps.storage)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", previous.to_string())
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+fn ensure_from_older_version(storage: &dyn Storage) -> Result<Version, ContractError> {
+    let stored = get_contract_version(storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::WrongMigrateContract(stored.contract));
+    }
+    let previous: Version = stored.version.parse()?;
+    if previous > CONTRACT_VERSION.parse()? {
+        return Err(ContractError::WrongMigrateVersion(stored.version));
+    }
+    Ok(previous)
+}
+
+//0.1.0 burnt burn_ratio percent of the native payments and sent the rest to the destination
+fn migrate_legacy_config(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    let legacy = LEGACY_CONFIG.load(storage)?;
+    let dust_recipient = match legacy.destination.clone() {
+        Some(destination) => destination,
+        None => cw_ownable::get_ownership(storage)?
+            .owner
+            .ok_or(ContractError::InvalidRevenueSplit {})?,
+    };
+    let revenue_split = RevenueSplit {
+        recipients: legacy
+            .destination
+            .map(|destination| vec![(destination, 100u64.saturating_sub(legacy.burn_ratio))])
+            .unwrap_or_default(),
+        burn_weight: legacy.burn_ratio,
+        dust_recipient,
+        accrue: false,
+    };
+
+    let config = Config {
+        collection_code_id: legacy.collection_code_id,
+        empty_character_mint_price: legacy.empty_character_mint_price,
+        character_mint_prices: vec![],
+        character_rarities: vec![],
+        revenue_split,
+        mint_limits: MintLimits::default(),
+        randomness_source: RandomnessSource::Internal,
+        fusion: FusionConfig::default(),
+        dismantle: DismantleConfig::default(),
+        names: NameConfig::default(),
+        extension: legacy.extension,
+    };
+    CONFIG.save(storage, &config)?;
+    Ok(())
+}
+
+//Pending lootboxes keep a copy of their characters, which had one field per slot up to 0.2.0
+fn migrate_lootbox_commitments(storage: &mut dyn Storage) -> StdResult<()> {
+    let commitments = LEGACY_LOOTBOX_COMMITMENTS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (commitment_id, legacy) in commitments {
+        let commitment = LootboxCommitment {
+            owner: legacy.owner,
+            receiver: legacy.receiver,
+            lootbox: CharacterLootbox::from(legacy.lootbox),
+            commitment: legacy.commitment,
+            committed_at: legacy.committed_at,
+            entropy: String::new(),
+            proxy: None,
+            payment: None,
+        };
+        LOOTBOX_COMMITMENTS.save(storage, commitment_id, &commitment)?;
+    }
+    Ok(())
+}
+
+// Reply callback triggered from cw721 character-onchain collection contract instantiation in instantiate()
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.id != INSTANTIATE_CW721_REPLY_ID {
+        return Err(ContractError::InvalidReplyID {});
+    }
+
+    let reply = parse_reply_instantiate_data(msg);
+    match reply {
+        Ok(res) => {
+            let collection_address = res.contract_address;
+            COLLECTION_ADDRESS.save(deps.storage, &Addr::unchecked(collection_address.clone()))?;
+            Ok(Response::default()
+                .add_attribute("action", "instantiate_sg721_reply")
+                .add_attribute("cw721_character_collection_address", collection_address))
+        }
+        Err(_) => Err(ContractError::InstantiateError {}),
+    }
 }
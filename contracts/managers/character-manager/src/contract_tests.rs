@@ -0,0 +1,3358 @@
+#![cfg(test)]
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{
+    coin, coins, to_binary, Addr, Binary, Deps, DepsMut, Empty, Env, Event, HexBinary, MessageInfo,
+    Response, StdResult, WasmMsg,
+};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw721::{OwnerOfResponse, TokensResponse};
+use cw721_base::msg::{CollectionInfo, RoyaltyInfoResponse};
+use cw721_character_onchain::msg::{CharacterInfoResponse, Metadata};
+use cw721_trait_onchain::msg::{EscrowResponse, Metadata as TraitMetadata};
+use cw_multi_test::{
+    next_block, App, AppBuilder, AppResponse, Contract, ContractWrapper, Executor,
+};
+use cw_storage_plus::Item;
+use mintables::msg::{MintableKind, SupplyResp};
+use mintables::state::{
+    Character, CharacterLootbox, LegacyCharacter, LegacyCharacterLootbox, Trait, TraitTypeInfo,
+    TraitValueInfo,
+};
+use sha2::{Digest, Sha256};
+use utils::{
+    merkle::leaf_hash,
+    msg::{
+        CharacterManagerParams, CollectionParams, CreateCharacterManagerMsg, TraitManagerMintMsg,
+        UpdateCharacterManagerParamsMsg,
+    },
+    query::{
+        CharacterManagerConfigResponse, CharacterManagerQueryMsg, LineageResponse,
+        LootboxCommitmentResponse, MintAllowanceResponse, ModerationResponse, PauseStatusResponse,
+        PresaleMintsResponse, ResolveNameResponse, RevenueResponse, SaleItem,
+    },
+    DismantleConfig, FusionConfig, MintLimits, NameConfig, Operation, PresalePhase,
+    RandomnessSource, RevenueSplit, NATIVE_DENOM,
+};
+
+use crate::contract::{migrate, REVEAL_TIMEOUT};
+use crate::msg::{ExecuteMsg, MigrateMsg, ReceiveMsg};
+use crate::state::{
+    LegacyConfig, LegacyLootboxCommitment, CONFIG, LEGACY_CONFIG, LEGACY_LOOTBOX_COMMITMENTS,
+    LOOTBOX_COMMITMENTS,
+};
+use crate::ContractError;
+
+const OWNER: &str = "owner";
+const USER: &str = "user";
+const TREASURY: &str = "treasury";
+
+//Mock of a randomness beacon proxy, it keeps the requested jobs until randomness is published
+#[cw_serde]
+enum MockProxyExecuteMsg {
+    GetNextRandomness { job_id: String },
+    Publish { randomness: HexBinary },
+}
+
+const MOCK_PROXY_JOBS: Item<Vec<(Addr, String)>> = Item::new("jobs");
+
+fn mock_proxy_execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: MockProxyExecuteMsg,
+) -> StdResult<Response> {
+    let mut jobs = MOCK_PROXY_JOBS.may_load(deps.storage)?.unwrap_or_default();
+    match msg {
+        MockProxyExecuteMsg::GetNextRandomness { job_id } => {
+            jobs.push((info.sender, job_id));
+            MOCK_PROXY_JOBS.save(deps.storage, &jobs)?;
+            Ok(Response::new())
+        }
+        MockProxyExecuteMsg::Publish { randomness } => {
+            MOCK_PROXY_JOBS.save(deps.storage, &vec![])?;
+            let mut res = Response::new();
+            for (requester, job_id) in jobs {
+                res = res.add_message(WasmMsg::Execute {
+                    contract_addr: requester.into_string(),
+                    msg: to_binary(&ExecuteMsg::ReceiveRandomness {
+                        job_id,
+                        randomness: randomness.clone(),
+                    })?,
+                    funds: vec![],
+                });
+            }
+            Ok(res)
+        }
+    }
+}
+
+fn mock_proxy_instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn mock_proxy_query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    to_binary(&Empty {})
+}
+
+fn mock_proxy() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        mock_proxy_execute,
+        mock_proxy_instantiate,
+        mock_proxy_query,
+    ))
+}
+
+//Mock of the trait manager, it keeps the traits it is asked to mint
+const MOCK_TRAIT_MINTS: Item<Vec<(String, TraitMetadata)>> = Item::new("trait_mints");
+
+fn mock_trait_manager_execute(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: TraitManagerMintMsg<TraitMetadata>,
+) -> StdResult<Response> {
+    let mut mints = MOCK_TRAIT_MINTS.may_load(deps.storage)?.unwrap_or_default();
+    let TraitManagerMintMsg::MintTraits {
+        receiver,
+        token_infos,
+    } = msg;
+    mints.extend(token_infos.into_iter().map(|t| (receiver.clone(), t)));
+    MOCK_TRAIT_MINTS.save(deps.storage, &mints)?;
+    Ok(Response::new())
+}
+
+fn mock_trait_manager_query(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    to_binary(&MOCK_TRAIT_MINTS.may_load(deps.storage)?.unwrap_or_default())
+}
+
+fn mock_trait_manager() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        mock_trait_manager_execute,
+        mock_proxy_instantiate,
+        mock_trait_manager_query,
+    ))
+}
+
+fn mintables() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        mintables::execute,
+        mintables::instantiate,
+        mintables::query,
+    ))
+}
+
+fn character_collection() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        cw721_character_onchain::entry::execute,
+        cw721_character_onchain::entry::instantiate,
+        cw721_character_onchain::entry::query,
+    ))
+}
+
+fn trait_collection() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        cw721_trait_onchain::entry::execute,
+        cw721_trait_onchain::entry::instantiate,
+        cw721_trait_onchain::entry::query,
+    ))
+}
+
+fn cw20_token() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    ))
+}
+
+fn character_manager() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            crate::contract::execute,
+            crate::contract::instantiate,
+            crate::contract::query,
+        )
+        .with_reply(crate::contract::reply),
+    )
+}
+
+fn test_character() -> Character {
+    Character {
+        id: 1,
+        traits: vec![
+            ("ears".to_string(), "pointy".to_string()),
+            ("eyes".to_string(), "laser".to_string()),
+        ],
+        rarity: "rare".to_string(),
+        locked: false,
+        mint_price: Some(coin(100, NATIVE_DENOM)),
+        max_supply: None,
+        minted: 0,
+        per_address_limit: None,
+        sale_start: None,
+        sale_end: None,
+    }
+}
+
+//Registers every slot value the test characters are made of
+fn register_traits(app: &mut App, mintables_addr: &Addr) {
+    let trait_types = ["ears", "eyes", "mouth"]
+        .iter()
+        .map(|trait_type| TraitTypeInfo {
+            trait_type: trait_type.to_string(),
+            display_name: trait_type.to_string(),
+            image_key: trait_type.to_string(),
+        })
+        .collect();
+    let trait_values = [
+        ("ears", "pointy"),
+        ("ears", "round"),
+        ("ears", "golden"),
+        ("eyes", "laser"),
+        ("mouth", "smile"),
+    ]
+    .iter()
+    .map(|(trait_type, trait_value)| TraitValueInfo {
+        trait_type: trait_type.to_string(),
+        trait_value: trait_value.to_string(),
+        display_name: trait_value.to_string(),
+        image_key: format!("{trait_type}/{trait_value}"),
+    })
+    .collect();
+
+    for msg in [
+        mintables::msg::ExecuteMsg::SetTraitTypes { trait_types },
+        mintables::msg::ExecuteMsg::SetTraitValues { trait_values },
+    ] {
+        app.execute_contract(Addr::unchecked(OWNER), mintables_addr.clone(), &msg, &[])
+            .unwrap();
+    }
+}
+
+struct Suite {
+    app: App,
+    mintables: Addr,
+    manager: Addr,
+    collection: Addr,
+    traits: Addr,
+    proxy: Addr,
+}
+
+fn setup(beacon: bool) -> Suite {
+    let mut app = AppBuilder::new().build(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &Addr::unchecked(USER), coins(1000, NATIVE_DENOM))
+            .unwrap();
+    });
+    let owner = Addr::unchecked(OWNER);
+
+    let mintables_code_id = app.store_code(mintables());
+    let collection_code_id = app.store_code(character_collection());
+    let manager_code_id = app.store_code(character_manager());
+    let proxy_code_id = app.store_code(mock_proxy());
+    let traits_code_id = app.store_code(trait_collection());
+
+    let mintables_addr = app
+        .instantiate_contract(
+            mintables_code_id,
+            owner.clone(),
+            &mintables::msg::InstantiateMsg {
+                slots: [
+                    "ears",
+                    "eyes",
+                    "mouth",
+                    "fur_type",
+                    "fur_color",
+                    "tail_shape",
+                ]
+                .iter()
+                .map(|slot| slot.to_string())
+                .collect(),
+            },
+            &[],
+            "mintables",
+            None,
+        )
+        .unwrap();
+    register_traits(&mut app, &mintables_addr);
+    app.execute_contract(
+        owner.clone(),
+        mintables_addr.clone(),
+        &mintables::msg::ExecuteMsg::AddCharacterLootboxes {
+            new_character_lootboxes: vec![CharacterLootbox {
+                id: 1,
+                characters: vec![test_character()],
+                possibilities: vec![100],
+                mint_price: coin(100, NATIVE_DENOM),
+                max_supply: Some(1),
+                minted: 0,
+                per_address_limit: None,
+                sale_start: None,
+                sale_end: None,
+            }],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let proxy = app
+        .instantiate_contract(proxy_code_id, owner.clone(), &Empty {}, &[], "proxy", None)
+        .unwrap();
+
+    //The owner mints traits directly, as the trait manager would
+    let traits = app
+        .instantiate_contract(
+            traits_code_id,
+            owner.clone(),
+            &cw721_trait_onchain::InstantiateMsg {
+                name: "Traits".to_string(),
+                symbol: "TRAIT".to_string(),
+                minter: OWNER.to_string(),
+                collection_info: CollectionInfo {
+                    creator: "creator".to_string(),
+                    description: "description".to_string(),
+                    image: Some("https://example.com/image.png".to_string()),
+                    external_link: None,
+                    explicit_content: None,
+                    royalty_info: None,
+                },
+                trait_registry: None,
+                image_template: None,
+            },
+            &[],
+            "traits",
+            None,
+        )
+        .unwrap();
+
+    let randomness_source = if beacon {
+        RandomnessSource::Beacon {
+            proxy_addr: proxy.clone(),
+        }
+    } else {
+        RandomnessSource::Internal
+    };
+
+    let manager = app
+        .instantiate_contract(
+            manager_code_id,
+            owner.clone(),
+            &CreateCharacterManagerMsg {
+                init_msg: None::<Empty>,
+                collection_params: CollectionParams {
+                    code_id: collection_code_id,
+                    name: "Characters".to_string(),
+                    symbol: Some("CAT".to_string()),
+                    info: CollectionInfo::<RoyaltyInfoResponse> {
+                        creator: "creator".to_string(),
+                        description: "description".to_string(),
+                        image: Some("https://example.com/image.png".to_string()),
+                        external_link: None,
+                        explicit_content: None,
+                        royalty_info: None,
+                    },
+                    image_template: None,
+                },
+                manager_params: CharacterManagerParams {
+                    empty_character_mint_price: coin(100, NATIVE_DENOM),
+                    character_mint_prices: vec![],
+                    character_rarities: vec![],
+                    revenue_split: RevenueSplit {
+                        recipients: vec![(Addr::unchecked(TREASURY), 1)],
+                        burn_weight: 0,
+                        dust_recipient: Addr::unchecked(TREASURY),
+                        accrue: false,
+                    },
+                    mint_limits: MintLimits::default(),
+                    randomness_source,
+                    fusion: FusionConfig::default(),
+                    dismantle: DismantleConfig::default(),
+                    names: NameConfig::default(),
+                    trait_collection_addr: traits.clone(),
+                    mintable_collection_addr: mintables_addr.clone(),
+                },
+            },
+            &[],
+            "character-manager",
+            None,
+        )
+        .unwrap();
+
+    //The manager records its mints against the supply kept in mintables
+    app.execute_contract(
+        owner.clone(),
+        mintables_addr.clone(),
+        &mintables::msg::ExecuteMsg::AddManagers {
+            managers: vec![manager.to_string()],
+        },
+        &[],
+    )
+    .unwrap();
+    //and holds the equipped traits in escrow, as the trait manager would allow it to
+    app.execute_contract(
+        owner,
+        traits.clone(),
+        &cw721_trait_onchain::ExecuteMsg::<TraitMetadata, Empty>::UpdateEscrowAgents {
+            add: vec![manager.to_string()],
+            remove: vec![],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let config: CharacterManagerConfigResponse<Empty> = app
+        .wrap()
+        .query_wasm_smart(&manager, &CharacterManagerQueryMsg::Config {})
+        .unwrap();
+
+    Suite {
+        app,
+        mintables: mintables_addr,
+        manager,
+        collection: Addr::unchecked(config.collection_address),
+        traits,
+        proxy,
+    }
+}
+
+//Config set by `setup` when the manager uses internal randomness
+fn update_params() -> UpdateCharacterManagerParamsMsg {
+    UpdateCharacterManagerParamsMsg {
+        empty_character_mint_price: coin(100, NATIVE_DENOM),
+        character_mint_prices: vec![],
+        character_rarities: vec![],
+        revenue_split: RevenueSplit {
+            recipients: vec![(Addr::unchecked(TREASURY), 1)],
+            burn_weight: 0,
+            dust_recipient: Addr::unchecked(TREASURY),
+            accrue: false,
+        },
+        mint_limits: MintLimits::default(),
+        randomness_source: RandomnessSource::Internal,
+        fusion: FusionConfig::default(),
+        dismantle: DismantleConfig::default(),
+        names: NameConfig::default(),
+    }
+}
+
+fn user_tokens(suite: &Suite) -> Vec<String> {
+    let tokens: TokensResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.collection,
+            &cw721_character_onchain::QueryMsg::<Empty>::Tokens {
+                owner: USER.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    tokens.tokens
+}
+
+#[test]
+fn open_lootbox_with_beacon() {
+    let mut suite = setup(true);
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::OpenLootbox {
+                lootbox_id: 1,
+                receiver: None,
+                commitment: None,
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+
+    //The lootbox is paid but nothing is minted until the beacon answers
+    let pending: LootboxCommitmentResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.manager,
+            &CharacterManagerQueryMsg::LootboxCommitment { commitment_id: 1 },
+        )
+        .unwrap();
+    assert_eq!(pending.commitment, None);
+    assert!(user_tokens(&suite).is_empty());
+
+    //Only the proxy can deliver randomness
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::ReceiveRandomness {
+                job_id: "1".to_string(),
+                randomness: HexBinary::from(vec![1u8; 32]),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnauthorizedRandomnessSource {}
+    );
+
+    //Beacon jobs cannot be revealed with a secret
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::RevealLootbox {
+                commitment_id: 1,
+                secret: "secret".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AwaitingRandomness {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.proxy.clone(),
+            &MockProxyExecuteMsg::Publish {
+                randomness: HexBinary::from(vec![7u8; 32]),
+            },
+            &[],
+        )
+        .unwrap();
+
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+    assert!(suite
+        .app
+        .wrap()
+        .query_wasm_smart::<LootboxCommitmentResponse>(
+            &suite.manager,
+            &CharacterManagerQueryMsg::LootboxCommitment { commitment_id: 1 },
+        )
+        .is_err());
+}
+
+#[test]
+fn open_lootbox_with_internal_randomness() {
+    let mut suite = setup(false);
+    let secret = "secret";
+    let commitment = format!("{:x}", Sha256::digest(secret.as_bytes()));
+
+    //A commitment is required when there is no beacon
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::OpenLootbox {
+                lootbox_id: 1,
+                receiver: None,
+                commitment: None,
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidCommitment {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::OpenLootbox {
+                lootbox_id: 1,
+                receiver: None,
+                commitment: Some(commitment),
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+
+    //Randomness from anyone is rejected when the manager uses internal randomness
+    let err = suite
+        .app
+        .execute_contract(
+            suite.proxy.clone(),
+            suite.manager.clone(),
+            &ExecuteMsg::ReceiveRandomness {
+                job_id: "1".to_string(),
+                randomness: HexBinary::from(vec![1u8; 32]),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnauthorizedRandomnessSource {}
+    );
+
+    let reveal = ExecuteMsg::RevealLootbox {
+        commitment_id: 1,
+        secret: secret.to_string(),
+    };
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(USER), suite.manager.clone(), &reveal, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RevealTooEarly {}
+    );
+
+    suite.app.update_block(next_block);
+    suite
+        .app
+        .execute_contract(Addr::unchecked(USER), suite.manager.clone(), &reveal, &[])
+        .unwrap();
+
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+}
+
+#[test]
+fn beacon_jobs_after_source_change_and_timeout() {
+    let mut suite = setup(true);
+    let open = |suite: &mut Suite, lootbox_id: u32| {
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                suite.manager.clone(),
+                &ExecuteMsg::OpenLootbox {
+                    lootbox_id,
+                    receiver: None,
+                    commitment: None,
+                },
+                &coins(100, NATIVE_DENOM),
+            )
+            .unwrap();
+    };
+    let update_source = |randomness_source| ExecuteMsg::UpdateConfig {
+        new_config: UpdateCharacterManagerParamsMsg {
+            randomness_source,
+            ..update_params()
+        },
+    };
+    let balance = |suite: &Suite, address: &str| {
+        suite
+            .app
+            .wrap()
+            .query_balance(address, NATIVE_DENOM)
+            .unwrap()
+            .amount
+            .u128()
+    };
+
+    //The payment is held until the job is answered
+    open(&mut suite, 1);
+    assert_eq!(balance(&suite, USER), 900);
+    assert_eq!(balance(&suite, TREASURY), 0);
+
+    //Randomness is not taken while lootboxes are paused
+    let publish = MockProxyExecuteMsg::Publish {
+        randomness: HexBinary::from(vec![7u8; 32]),
+    };
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::Pause {
+                operations: Some(vec![Operation::Lootbox]),
+            },
+            &[],
+        )
+        .unwrap();
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(OWNER), suite.proxy.clone(), &publish, &[])
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "This operation is paused");
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::Unpause { operations: None },
+            &[],
+        )
+        .unwrap();
+
+    //Pending jobs are still answered by the proxy they were sent to after the source changes
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &update_source(RandomnessSource::Internal),
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.proxy.clone(),
+            &MockProxyExecuteMsg::Publish {
+                randomness: HexBinary::from(vec![7u8; 32]),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+    assert_eq!(balance(&suite, TREASURY), 100);
+
+    //Jobs the beacon never answers are refunded and their supply is given back
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddCharacterLootboxes {
+                new_character_lootboxes: vec![CharacterLootbox {
+                    id: 2,
+                    characters: vec![test_character()],
+                    possibilities: vec![100],
+                    mint_price: coin(100, NATIVE_DENOM),
+                    max_supply: Some(1),
+                    minted: 0,
+                    per_address_limit: None,
+                    sale_start: None,
+                    sale_end: None,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &update_source(RandomnessSource::Beacon {
+                proxy_addr: suite.proxy.clone(),
+            }),
+            &[],
+        )
+        .unwrap();
+    open(&mut suite, 2);
+    assert_eq!(balance(&suite, USER), 800);
+
+    suite
+        .app
+        .update_block(|block| block.height += REVEAL_TIMEOUT + 1);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::ResolveExpiredLootbox { commitment_id: 2 },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(balance(&suite, USER), 900);
+    assert_eq!(balance(&suite, TREASURY), 100);
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+    let supply: SupplyResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.mintables,
+            &mintables::msg::QueryMsg::Supply {
+                kind: MintableKind::CharacterLootbox,
+                id: 2,
+            },
+        )
+        .unwrap();
+    assert_eq!(supply.minted, 0);
+
+    //The refunded lootbox is no longer counted against the caps of the buyer
+    let allowance: MintAllowanceResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.manager,
+            &CharacterManagerQueryMsg::MintAllowance {
+                address: USER.to_string(),
+                item: Some(SaleItem::Lootbox { id: 2 }),
+            },
+        )
+        .unwrap();
+    assert_eq!(allowance.minted, 1);
+    assert_eq!(allowance.item_minted, 0);
+}
+
+#[test]
+fn resolve_expired_lootbox() {
+    let mut suite = setup(false);
+    let secret = "secret";
+    let commitment = format!("{:x}", Sha256::digest(secret.as_bytes()));
+
+    let golden = Character {
+        id: 2,
+        traits: vec![("ears".to_string(), "golden".to_string())],
+        ..test_character()
+    };
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddCharacterLootboxes {
+                new_character_lootboxes: vec![CharacterLootbox {
+                    id: 2,
+                    characters: vec![test_character(), golden],
+                    possibilities: vec![1, 99],
+                    mint_price: coin(100, NATIVE_DENOM),
+                    max_supply: None,
+                    minted: 0,
+                    per_address_limit: None,
+                    sale_start: None,
+                    sale_end: None,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::OpenLootbox {
+                lootbox_id: 2,
+                receiver: None,
+                commitment: Some(commitment),
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+
+    let resolve = ExecuteMsg::ResolveExpiredLootbox { commitment_id: 1 };
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(OWNER), suite.manager.clone(), &resolve, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CommitmentNotExpired {}
+    );
+
+    //The secret can no longer be revealed once the timeout is over
+    suite
+        .app
+        .update_block(|block| block.height += REVEAL_TIMEOUT + 1);
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::RevealLootbox {
+                commitment_id: 1,
+                secret: secret.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CommitmentExpired {}
+    );
+
+    //Anyone can resolve it, but the lootbox is forfeited without a character or a refund,
+    //so not revealing a known result is worse than any draw
+    let balance = |suite: &Suite| {
+        suite
+            .app
+            .wrap()
+            .query_balance(USER, NATIVE_DENOM)
+            .unwrap()
+            .amount
+    };
+    let paid = balance(&suite);
+    let res = suite
+        .app
+        .execute_contract(Addr::unchecked(OWNER), suite.manager.clone(), &resolve, &[])
+        .unwrap();
+    assert!(res.has_event(&Event::new("wasm").add_attribute("action", "forfeit_lootbox")));
+    assert!(user_tokens(&suite).is_empty());
+    assert_eq!(balance(&suite), paid);
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(OWNER), suite.manager.clone(), &resolve, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CommitmentNotFound {}
+    );
+
+    //The forfeited lootbox is not counted as sold, while revealing always gives one of its entries
+    let supply: SupplyResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.mintables,
+            &mintables::msg::QueryMsg::Supply {
+                kind: MintableKind::CharacterLootbox,
+                id: 2,
+            },
+        )
+        .unwrap();
+    assert_eq!(supply.minted, 0);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::OpenLootbox {
+                lootbox_id: 2,
+                receiver: None,
+                commitment: Some(format!("{:x}", Sha256::digest(secret.as_bytes()))),
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+    suite.app.update_block(next_block);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::RevealLootbox {
+                commitment_id: 2,
+                secret: secret.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+}
+
+#[test]
+fn unequip_and_swap_traits() {
+    let mut suite = setup(false);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddCharacters {
+                new_characters: vec![test_character()],
+            },
+            &[],
+        )
+        .unwrap();
+    let mint = |character_id| ExecuteMsg::Mint {
+        character_id,
+        token_info: Metadata {
+            name: None,
+            traits: vec![
+                ("ears".to_string(), "pointy".to_string()),
+                ("eyes".to_string(), "laser".to_string()),
+            ],
+            rarity: Some("rare".to_string()),
+            traits_equipped: None,
+            locked: false,
+        },
+    };
+
+    //Pre-made characters are looked up by their mintables id
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &mint(Some(2)),
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidCharacter {}
+    );
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &mint(Some(1)),
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+
+    for (token_id, trait_type, trait_value) in [("1", "ears", "round"), ("2", "ears", "golden")] {
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(OWNER),
+                suite.traits.clone(),
+                &cw721_trait_onchain::ExecuteMsg::<TraitMetadata, Empty>::Mint {
+                    token_id: token_id.to_string(),
+                    owner: USER.to_string(),
+                    token_uri: None,
+                    extension: TraitMetadata {
+                        trait_type: trait_type.to_string(),
+                        trait_value: trait_value.to_string(),
+                        trait_rarity: "common".to_string(),
+                    },
+                },
+                &[],
+            )
+            .unwrap();
+    }
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.traits.clone(),
+            &cw721_trait_onchain::ExecuteMsg::<TraitMetadata, Empty>::ApproveAll {
+                operator: suite.manager.to_string(),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+    let execute = |suite: &mut Suite, msg: ExecuteMsg| {
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), suite.manager.clone(), &msg, &[])
+    };
+    let character = |suite: &Suite| -> Metadata {
+        let res: CharacterInfoResponse<Metadata> = suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &suite.collection,
+                &cw721_character_onchain::QueryMsg::<Empty>::CharacterInfo {
+                    token_id: "1".to_string(),
+                },
+            )
+            .unwrap();
+        res.token_info
+    };
+    let escrow_holder = |suite: &Suite, token_id: &str| -> Option<String> {
+        let res: EscrowResponse = suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &suite.traits,
+                &cw721_trait_onchain::QueryMsg::<Empty>::Escrow {
+                    token_id: token_id.to_string(),
+                },
+            )
+            .unwrap();
+        res.holder
+    };
+
+    //Equipping the ears keeps the premade eyes
+    execute(
+        &mut suite,
+        ExecuteMsg::ModifyCharacter {
+            token_id: "1".to_string(),
+            trait_ids: vec!["1".to_string()],
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        character(&suite).traits,
+        vec![
+            ("ears".to_string(), "round".to_string()),
+            ("eyes".to_string(), "laser".to_string()),
+        ]
+    );
+    assert_eq!(escrow_holder(&suite, "1"), Some(suite.manager.to_string()));
+
+    let err = execute(
+        &mut suite,
+        ExecuteMsg::SwapTrait {
+            token_id: "1".to_string(),
+            trait_id: "1".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TraitAlreadyEquipped {}
+    );
+
+    //Swapping the ears gives the round ones back and leaves the eyes alone
+    execute(
+        &mut suite,
+        ExecuteMsg::SwapTrait {
+            token_id: "1".to_string(),
+            trait_id: "2".to_string(),
+        },
+    )
+    .unwrap();
+    let swapped = character(&suite);
+    assert_eq!(
+        swapped.traits,
+        vec![
+            ("ears".to_string(), "golden".to_string()),
+            ("eyes".to_string(), "laser".to_string()),
+        ]
+    );
+    assert_eq!(swapped.traits_equipped, Some(vec!["2".to_string()]));
+    assert_eq!(escrow_holder(&suite, "1"), None);
+    assert_eq!(escrow_holder(&suite, "2"), Some(suite.manager.to_string()));
+
+    //The eyes are not backed by a trait, so there is nothing to unequip
+    let err = execute(
+        &mut suite,
+        ExecuteMsg::UnequipTraits {
+            token_id: "1".to_string(),
+            trait_types: vec!["eyes".to_string()],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::TraitNotEquipped {}
+    );
+
+    execute(
+        &mut suite,
+        ExecuteMsg::UnequipTraits {
+            token_id: "1".to_string(),
+            trait_types: vec!["ears".to_string()],
+        },
+    )
+    .unwrap();
+    let unequipped = character(&suite);
+    assert_eq!(
+        unequipped.traits,
+        vec![("eyes".to_string(), "laser".to_string())]
+    );
+    assert_eq!(unequipped.traits_equipped, None);
+    assert_eq!(escrow_holder(&suite, "2"), None);
+
+    //Both traits are back with the owner and free to be used again
+    let owner: OwnerOfResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.traits,
+            &cw721_trait_onchain::QueryMsg::<Empty>::OwnerOf {
+                token_id: "2".to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(owner.owner, USER);
+}
+
+#[test]
+fn equipped_traits_follow_the_character() {
+    let mut suite = setup(false);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::Mint {
+                character_id: None,
+                token_info: Metadata {
+                    name: None,
+                    traits: vec![],
+                    rarity: None,
+                    traits_equipped: None,
+                    locked: false,
+                },
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.traits.clone(),
+            &cw721_trait_onchain::ExecuteMsg::<TraitMetadata, Empty>::Mint {
+                token_id: "1".to_string(),
+                owner: USER.to_string(),
+                token_uri: None,
+                extension: TraitMetadata {
+                    trait_type: "ears".to_string(),
+                    trait_value: "round".to_string(),
+                    trait_rarity: "common".to_string(),
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.traits.clone(),
+            &cw721_trait_onchain::ExecuteMsg::<TraitMetadata, Empty>::ApproveAll {
+                operator: suite.manager.to_string(),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+    //Other approved spenders cannot take the trait into escrow themselves
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.traits.clone(),
+            &cw721_trait_onchain::ExecuteMsg::<TraitMetadata, Empty>::Escrow {
+                token_ids: vec!["1".to_string()],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Only the minter and the escrow agents can escrow traits"
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::ModifyCharacter {
+                token_id: "1".to_string(),
+                trait_ids: vec!["1".to_string()],
+            },
+            &[],
+        )
+        .unwrap();
+
+    //Unlocked characters cannot change hands, so the trait stays with the owner that equipped it
+    let transfer = |suite: &mut Suite| {
+        suite.app.execute_contract(
+            Addr::unchecked(USER),
+            suite.collection.clone(),
+            &cw721_character_onchain::ExecuteMsg::<Metadata, Empty>::TransferNft {
+                recipient: "buyer".to_string(),
+                token_id: "1".to_string(),
+            },
+            &[],
+        )
+    };
+    let trait_owner = |suite: &Suite| -> Option<String> {
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart::<OwnerOfResponse>(
+                &suite.traits,
+                &cw721_trait_onchain::QueryMsg::<Empty>::OwnerOf {
+                    token_id: "1".to_string(),
+                    include_expired: None,
+                },
+            )
+            .ok()
+            .map(|res| res.owner)
+    };
+    let err = transfer(&mut suite).unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Character is not locked");
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::UnequipTraits {
+                token_id: "1".to_string(),
+                trait_types: vec!["ears".to_string()],
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(trait_owner(&suite), Some(USER.to_string()));
+
+    //Locking burns the equipped trait, so none is left in escrow once the character can be transferred
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.collection.clone(),
+            &cw721_character_onchain::ExecuteMsg::<Metadata, Empty>::ApproveAll {
+                operator: suite.manager.to_string(),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+    for msg in [
+        ExecuteMsg::ModifyCharacter {
+            token_id: "1".to_string(),
+            trait_ids: vec!["1".to_string()],
+        },
+        ExecuteMsg::LockCharacter {
+            token_id: "1".to_string(),
+        },
+    ] {
+        suite
+            .app
+            .execute_contract(Addr::unchecked(USER), suite.manager.clone(), &msg, &[])
+            .unwrap();
+    }
+    assert_eq!(trait_owner(&suite), None);
+    transfer(&mut suite).unwrap();
+    let character: CharacterInfoResponse<Metadata> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.collection,
+            &cw721_character_onchain::QueryMsg::<Empty>::CharacterInfo {
+                token_id: "1".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(character.owner, "buyer");
+    assert_eq!(
+        character.token_info.traits,
+        vec![("ears".to_string(), "round".to_string())]
+    );
+}
+
+#[test]
+fn presale_mint_with_proof() {
+    let mut suite = setup(false);
+
+    //Allowlist of two addresses, the proof of each one is the leaf of the other
+    let user_leaf = leaf_hash(USER);
+    let other_leaf = leaf_hash("other");
+    let (first, second) = if user_leaf <= other_leaf {
+        (user_leaf, other_leaf)
+    } else {
+        (other_leaf, user_leaf)
+    };
+    let root = Sha256::new()
+        .chain_update(first)
+        .chain_update(second)
+        .finalize();
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::SetPresalePhase {
+                phase_id: 1,
+                phase: Some(PresalePhase {
+                    merkle_root: format!("{:x}", root),
+                    per_address_limit: 1,
+                    discount_percent: 40,
+                }),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let mint = |proof: Vec<String>| ExecuteMsg::MintWithProof {
+        phase_id: 1,
+        proof,
+        character_id: None,
+        token_info: Metadata {
+            name: None,
+            traits: vec![],
+            rarity: None,
+            traits_equipped: None,
+            locked: false,
+        },
+        receiver: None,
+    };
+    let user = Addr::unchecked(USER);
+    let proof = vec![HexBinary::from(other_leaf.to_vec()).to_hex()];
+
+    let err = suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.manager.clone(),
+            &mint(vec![]),
+            &coins(60, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NotAllowlisted {}
+    );
+
+    //The price of an empty character is discounted too
+    let err = suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.manager.clone(),
+            &mint(proof.clone()),
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::IncorrectMintFunds {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.manager.clone(),
+            &mint(proof.clone()),
+            &coins(60, NATIVE_DENOM),
+        )
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+
+    let mints: PresaleMintsResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.manager,
+            &CharacterManagerQueryMsg::PresaleMints {
+                phase_id: 1,
+                address: USER.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        mints,
+        PresaleMintsResponse {
+            minted: 1,
+            remaining: 0
+        }
+    );
+
+    let err = suite
+        .app
+        .execute_contract(
+            user,
+            suite.manager.clone(),
+            &mint(proof),
+            &coins(60, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PresaleLimitReached {}
+    );
+}
+
+#[test]
+fn mint_paid_with_cw20() {
+    let mut suite = setup(false);
+    let token_code_id = suite.app.store_code(cw20_token());
+    let token = suite
+        .app
+        .instantiate_contract(
+            token_code_id,
+            Addr::unchecked(OWNER),
+            &cw20_base::msg::InstantiateMsg {
+                name: "Partner".to_string(),
+                symbol: "PART".to_string(),
+                decimals: 6,
+                initial_balances: vec![Cw20Coin {
+                    address: USER.to_string(),
+                    amount: 1000u128.into(),
+                }],
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            "partner-token",
+            None,
+        )
+        .unwrap();
+
+    //The price names the token contract instead of a native denom
+    let partner_character = Character {
+        mint_price: Some(coin(250, token.as_str())),
+        ..test_character()
+    };
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddCharacters {
+                new_characters: vec![partner_character.clone()],
+            },
+            &[],
+        )
+        .unwrap();
+
+    let token_info = Metadata {
+        name: None,
+        traits: partner_character.traits,
+        rarity: Some(partner_character.rarity),
+        traits_equipped: None,
+        locked: false,
+    };
+    let send = |amount: u128| Cw20ExecuteMsg::Send {
+        contract: suite.manager.to_string(),
+        amount: amount.into(),
+        msg: to_binary(&ReceiveMsg::Mint {
+            character_id: Some(1),
+            token_info: token_info.clone(),
+        })
+        .unwrap(),
+    };
+
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(USER), token.clone(), &send(100), &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::IncorrectMintFunds {}
+    );
+
+    //Native funds are not accepted for a price in a CW20 token
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::Mint {
+                character_id: Some(1),
+                token_info: token_info.clone(),
+            },
+            &coins(250, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::IncorrectMintFunds {}
+    );
+
+    suite
+        .app
+        .execute_contract(Addr::unchecked(USER), token.clone(), &send(250), &[])
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+
+    //Tokens other than the native denom are not burnt and go to the recipients in full
+    let balance: BalanceResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &token,
+            &Cw20QueryMsg::Balance {
+                address: TREASURY.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance.balance.u128(), 250);
+}
+
+#[test]
+fn mint_with_revenue_split() {
+    let mut suite = setup(false);
+    let update_config = |revenue_split| ExecuteMsg::UpdateConfig {
+        new_config: UpdateCharacterManagerParamsMsg {
+            revenue_split,
+            ..update_params()
+        },
+    };
+
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &update_config(RevenueSplit {
+                recipients: vec![(Addr::unchecked(TREASURY), 0)],
+                burn_weight: 0,
+                dust_recipient: Addr::unchecked(TREASURY),
+                accrue: false,
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidRevenueSplit {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &update_config(RevenueSplit {
+                recipients: vec![
+                    (Addr::unchecked("artist"), 2),
+                    (Addr::unchecked(TREASURY), 1),
+                ],
+                burn_weight: 1,
+                dust_recipient: Addr::unchecked(TREASURY),
+                accrue: false,
+            }),
+            &[],
+        )
+        .unwrap();
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::Mint {
+                character_id: None,
+                token_info: Metadata {
+                    name: None,
+                    traits: vec![],
+                    rarity: None,
+                    traits_equipped: None,
+                    locked: false,
+                },
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+
+    let balance = |address: &str| {
+        suite
+            .app
+            .wrap()
+            .query_balance(address, NATIVE_DENOM)
+            .unwrap()
+            .amount
+            .u128()
+    };
+    assert_eq!(balance("artist"), 50);
+    assert_eq!(balance(TREASURY), 25);
+    assert_eq!(balance(suite.manager.as_str()), 0);
+    //The remaining quarter is burnt
+    assert_eq!(balance(USER), 900);
+}
+
+#[test]
+fn accrue_and_withdraw_revenue() {
+    let mut suite = setup(false);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::UpdateConfig {
+                new_config: UpdateCharacterManagerParamsMsg {
+                    revenue_split: RevenueSplit {
+                        recipients: vec![
+                            (Addr::unchecked("artist"), 1),
+                            (Addr::unchecked(TREASURY), 1),
+                        ],
+                        burn_weight: 2,
+                        dust_recipient: Addr::unchecked(TREASURY),
+                        accrue: true,
+                    },
+                    ..update_params()
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+    let mint = ExecuteMsg::Mint {
+        character_id: None,
+        token_info: Metadata {
+            name: None,
+            traits: vec![],
+            rarity: None,
+            traits_equipped: None,
+            locked: false,
+        },
+    };
+    for _ in 0..2 {
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                suite.manager.clone(),
+                &mint,
+                &coins(100, NATIVE_DENOM),
+            )
+            .unwrap();
+    }
+
+    //Half of each mint is burnt and the shares of both recipients stay in the manager
+    let revenue: RevenueResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.manager,
+            &CharacterManagerQueryMsg::Revenue {
+                denom: NATIVE_DENOM.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(revenue.lifetime.u128(), 200);
+    assert_eq!(revenue.burned.u128(), 100);
+    assert_eq!(revenue.accrued.u128(), 100);
+    let balance = |suite: &Suite, address: &str| {
+        suite
+            .app
+            .wrap()
+            .query_balance(address, NATIVE_DENOM)
+            .unwrap()
+            .amount
+            .u128()
+    };
+    assert_eq!(balance(&suite, suite.manager.as_str()), 100);
+    assert_eq!(balance(&suite, TREASURY), 0);
+
+    let withdraw = |amount: u128, to: &str| ExecuteMsg::Withdraw {
+        denom: NATIVE_DENOM.to_string(),
+        amount: amount.into(),
+        to: to.to_string(),
+    };
+    //Each recipient is owed 50, none of them can take out more
+    for sender in [TREASURY, "artist"] {
+        let err = suite
+            .app
+            .execute_contract(
+                Addr::unchecked(sender),
+                suite.manager.clone(),
+                &withdraw(100, sender),
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Ownership(cw_ownable::OwnershipError::NotOwner)
+        );
+    }
+
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &withdraw(101, TREASURY),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidWithdrawAmount {}
+    );
+
+    for recipient in [TREASURY, "artist"] {
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(OWNER),
+                suite.manager.clone(),
+                &withdraw(50, recipient),
+                &[],
+            )
+            .unwrap();
+        assert_eq!(balance(&suite, recipient), 50);
+    }
+
+    let revenue: Vec<RevenueResponse> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&suite.manager, &CharacterManagerQueryMsg::AllRevenue {})
+        .unwrap();
+    assert_eq!(revenue.len(), 1);
+    assert_eq!(revenue[0].accrued.u128(), 0);
+    assert_eq!(revenue[0].lifetime.u128(), 200);
+}
+
+#[test]
+fn mint_limits_per_address_and_block() {
+    let mut suite = setup(false);
+    let capped_character = Character {
+        per_address_limit: Some(1),
+        ..test_character()
+    };
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddCharacters {
+                new_characters: vec![capped_character.clone()],
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::UpdateConfig {
+                new_config: UpdateCharacterManagerParamsMsg {
+                    mint_limits: MintLimits {
+                        per_address: Some(3),
+                        per_block: Some(2),
+                        block_ceiling: Some(3),
+                    },
+                    ..update_params()
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .send_tokens(
+            Addr::unchecked(USER),
+            Addr::unchecked("other"),
+            &coins(200, NATIVE_DENOM),
+        )
+        .unwrap();
+
+    let empty = ExecuteMsg::Mint {
+        character_id: None,
+        token_info: Metadata {
+            name: None,
+            traits: vec![],
+            rarity: None,
+            traits_equipped: None,
+            locked: false,
+        },
+    };
+    let capped = ExecuteMsg::Mint {
+        character_id: Some(capped_character.id),
+        token_info: Metadata {
+            name: None,
+            traits: capped_character.traits,
+            rarity: Some(capped_character.rarity),
+            traits_equipped: None,
+            locked: false,
+        },
+    };
+    let execute = |suite: &mut Suite, sender: &str, msg: &ExecuteMsg| {
+        suite.app.execute_contract(
+            Addr::unchecked(sender),
+            suite.manager.clone(),
+            msg,
+            &coins(100, NATIVE_DENOM),
+        )
+    };
+
+    execute(&mut suite, USER, &empty).unwrap();
+    execute(&mut suite, USER, &empty).unwrap();
+    let err = execute(&mut suite, USER, &empty).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BlockMintLimitReached {}
+    );
+
+    //The ceiling counts the purchases of every address in the block
+    execute(&mut suite, "other", &empty).unwrap();
+    let err = execute(&mut suite, "other", &empty).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BlockCeilingReached {}
+    );
+    suite.app.update_block(next_block);
+    execute(&mut suite, "other", &empty).unwrap();
+
+    let allowance = |suite: &Suite, item: Option<SaleItem>| -> MintAllowanceResponse {
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &suite.manager,
+                &CharacterManagerQueryMsg::MintAllowance {
+                    address: USER.to_string(),
+                    item,
+                },
+            )
+            .unwrap()
+    };
+    assert_eq!(
+        allowance(&suite, Some(SaleItem::Single { id: 1 })),
+        MintAllowanceResponse {
+            minted: 2,
+            item_minted: 0,
+            remaining: Some(1),
+        }
+    );
+
+    execute(&mut suite, USER, &capped).unwrap();
+    suite.app.update_block(next_block);
+    let err = execute(&mut suite, USER, &capped).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MintLimitReached {}
+    );
+    let err = execute(&mut suite, USER, &empty).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MintLimitReached {}
+    );
+    assert_eq!(allowance(&suite, None).remaining, Some(0));
+}
+
+#[test]
+fn pause_operations_and_transfers() {
+    let mut suite = setup(false);
+    let pause = ExecuteMsg::Pause {
+        operations: Some(vec![Operation::Mint, Operation::Transfer]),
+    };
+
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(USER), suite.manager.clone(), &pause, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Ownership(cw_ownable::OwnershipError::NotOwner)
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::SetGuardian {
+                guardian: Some("guardian".to_string()),
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked("guardian"),
+            suite.manager.clone(),
+            &pause,
+            &[],
+        )
+        .unwrap();
+
+    let status: PauseStatusResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&suite.manager, &CharacterManagerQueryMsg::PauseStatus {})
+        .unwrap();
+    assert_eq!(
+        status,
+        PauseStatusResponse {
+            paused: vec![Operation::Mint, Operation::Transfer],
+            guardian: Some("guardian".to_string()),
+        }
+    );
+    let transfers_paused = |suite: &Suite| {
+        let status: cw721_character_onchain::msg::PauseStatusResponse = suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &suite.collection,
+                &cw721_character_onchain::QueryMsg::<Empty>::PauseStatus {},
+            )
+            .unwrap();
+        status.transfers_paused
+    };
+    assert!(transfers_paused(&suite));
+
+    let mint = ExecuteMsg::Mint {
+        character_id: None,
+        token_info: Metadata {
+            name: None,
+            traits: vec![],
+            rarity: None,
+            traits_equipped: None,
+            locked: false,
+        },
+    };
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &mint,
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OperationPaused {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked("guardian"),
+            suite.manager.clone(),
+            &ExecuteMsg::Unpause { operations: None },
+            &[],
+        )
+        .unwrap();
+    assert!(!transfers_paused(&suite));
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &mint,
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+}
+
+fn mint_character(suite: &mut Suite, character: Character) {
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::Mint {
+                character_id: Some(character.id),
+                token_info: Metadata {
+                    name: None,
+                    traits: character.traits,
+                    rarity: Some(character.rarity),
+                    traits_equipped: None,
+                    locked: false,
+                },
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+}
+
+fn approve_manager(suite: &mut Suite) {
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.collection.clone(),
+            &cw721_character_onchain::ExecuteMsg::<Metadata, Empty>::ApproveAll {
+                operator: suite.manager.to_string(),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+}
+
+fn fuse(
+    suite: &mut Suite,
+    parent_a: &str,
+    parent_b: &str,
+    commitment: Option<String>,
+) -> Result<AppResponse, ContractError> {
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::Fuse {
+                parent_a: parent_a.to_string(),
+                parent_b: parent_b.to_string(),
+                commitment,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+}
+
+fn reveal_fusion(suite: &mut Suite, commitment_id: u64, secret: &str) {
+    suite.app.update_block(next_block);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::RevealFusion {
+                commitment_id,
+                secret: secret.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+}
+
+fn dismantle(
+    suite: &mut Suite,
+    token_id: &str,
+    commitment: Option<String>,
+) -> Result<AppResponse, ContractError> {
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::Dismantle {
+                token_id: token_id.to_string(),
+                commitment,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+}
+
+fn reveal_dismantle(suite: &mut Suite, commitment_id: u64, secret: &str) {
+    suite.app.update_block(next_block);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::RevealDismantle {
+                commitment_id,
+                secret: secret.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+}
+
+fn round_character() -> Character {
+    Character {
+        id: 2,
+        traits: vec![
+            ("ears".to_string(), "round".to_string()),
+            ("eyes".to_string(), "laser".to_string()),
+            ("mouth".to_string(), "smile".to_string()),
+        ],
+        ..test_character()
+    }
+}
+
+//Registers the test character and the round one in mintables and mints both to the user
+fn setup_fusion(beacon: bool) -> Suite {
+    let mut suite = setup(beacon);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddCharacters {
+                new_characters: vec![test_character(), round_character()],
+            },
+            &[],
+        )
+        .unwrap();
+    mint_character(&mut suite, test_character());
+    mint_character(&mut suite, round_character());
+    suite
+}
+
+#[test]
+fn fuse_characters() {
+    let mut suite = setup_fusion(false);
+    let secret = "secret";
+    let commitment = Some(format!("{:x}", Sha256::digest(secret.as_bytes())));
+
+    let character = |suite: &Suite, token_id: &str| -> CharacterInfoResponse<Metadata> {
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &suite.collection,
+                &cw721_character_onchain::QueryMsg::<Empty>::CharacterInfo {
+                    token_id: token_id.to_string(),
+                },
+            )
+            .unwrap()
+    };
+    let lineage = |suite: &Suite, token_id: &str| -> LineageResponse {
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &suite.manager,
+                &CharacterManagerQueryMsg::Lineage {
+                    token_id: token_id.to_string(),
+                },
+            )
+            .unwrap()
+    };
+
+    assert_eq!(
+        fuse(&mut suite, "1", "1", commitment.clone()).unwrap_err(),
+        ContractError::SameParents {}
+    );
+    //A fusion needs a commitment when there is no beacon
+    assert_eq!(
+        fuse(&mut suite, "1", "2", None).unwrap_err(),
+        ContractError::InvalidCommitment {}
+    );
+
+    //The parents are kept as they are until the child is drawn
+    let res = fuse(&mut suite, "1", "2", commitment.clone()).unwrap();
+    assert!(res.has_event(&Event::new("wasm").add_attribute("commitment_id", "1")));
+    assert_eq!(
+        fuse(&mut suite, "2", "1", commitment.clone()).unwrap_err(),
+        ContractError::CharacterPending {}
+    );
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::LockCharacter {
+                token_id: "1".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CharacterPending {}
+    );
+    assert_eq!(user_tokens(&suite), vec!["1".to_string(), "2".to_string()]);
+
+    let reveal = ExecuteMsg::RevealFusion {
+        commitment_id: 1,
+        secret: secret.to_string(),
+    };
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(USER), suite.manager.clone(), &reveal, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RevealTooEarly {}
+    );
+
+    //The manager burns or locks the parents on behalf of the user
+    suite.app.update_block(next_block);
+    suite
+        .app
+        .execute_contract(Addr::unchecked(USER), suite.manager.clone(), &reveal, &[])
+        .unwrap_err();
+    approve_manager(&mut suite);
+    reveal_fusion(&mut suite, 1, secret);
+
+    assert_eq!(user_tokens(&suite), vec!["3".to_string()]);
+    let child = character(&suite, "3").token_info;
+    assert!(["pointy", "round"].contains(&child.trait_value("ears").unwrap().as_str()));
+    assert_eq!(child.trait_value("eyes").unwrap(), "laser");
+    assert!(!child.locked);
+    assert_eq!(
+        lineage(&suite, "3"),
+        LineageResponse {
+            parents: vec!["1".to_string(), "2".to_string()],
+            generation: 1,
+        }
+    );
+    assert_eq!(lineage(&suite, "1").generation, 0);
+
+    //Every slot with a mintables trait of its type mutates and the parents are kept locked
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddTraits {
+                new_traits: vec![Trait {
+                    id: 1,
+                    trait_type: "ears".to_string(),
+                    trait_value: "golden".to_string(),
+                    trait_rarity: "rare".to_string(),
+                    mint_price: None,
+                    max_supply: None,
+                    minted: 0,
+                    per_address_limit: None,
+                    sale_start: None,
+                    sale_end: None,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::UpdateConfig {
+                new_config: UpdateCharacterManagerParamsMsg {
+                    fusion: FusionConfig {
+                        mutation_percent: 100,
+                        burn_parents: false,
+                        ..FusionConfig::default()
+                    },
+                    ..update_params()
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    mint_character(&mut suite, test_character());
+
+    fuse(&mut suite, "3", "4", commitment.clone()).unwrap();
+    reveal_fusion(&mut suite, 2, secret);
+    assert_eq!(
+        user_tokens(&suite),
+        vec!["3".to_string(), "4".to_string(), "5".to_string()]
+    );
+    assert!(character(&suite, "3").token_info.locked);
+    assert!(character(&suite, "4").token_info.locked);
+    let child = character(&suite, "5").token_info;
+    assert_eq!(child.trait_value("ears").unwrap(), "golden");
+    assert_eq!(child.trait_value("eyes").unwrap(), "laser");
+    assert_eq!(lineage(&suite, "5").generation, 2);
+
+    assert_eq!(
+        fuse(&mut suite, "3", "5", commitment).unwrap_err(),
+        ContractError::CharacterAlreadyLocked {}
+    );
+}
+
+#[test]
+fn fuse_with_beacon() {
+    let mut suite = setup_fusion(true);
+    approve_manager(&mut suite);
+
+    fuse(&mut suite, "1", "2", None).unwrap();
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::ReceiveRandomness {
+                job_id: "1".to_string(),
+                randomness: HexBinary::from(vec![1u8; 32]),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnauthorizedRandomnessSource {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.proxy.clone(),
+            &MockProxyExecuteMsg::Publish {
+                randomness: HexBinary::from(vec![7u8; 32]),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["3".to_string()]);
+}
+
+#[test]
+fn resolve_expired_fusions() {
+    //Beacon jobs that are never answered give the parents back untouched
+    let mut suite = setup_fusion(true);
+    approve_manager(&mut suite);
+    fuse(&mut suite, "1", "2", None).unwrap();
+
+    let resolve = |suite: &mut Suite, commitment_id: u64| {
+        suite.app.execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::ResolveExpiredFusion { commitment_id },
+            &[],
+        )
+    };
+    let err = resolve(&mut suite, 1).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CommitmentNotExpired {}
+    );
+
+    suite
+        .app
+        .update_block(|block| block.height += REVEAL_TIMEOUT + 1);
+    resolve(&mut suite, 1).unwrap();
+    assert_eq!(user_tokens(&suite), vec!["1".to_string(), "2".to_string()]);
+    fuse(&mut suite, "1", "2", None).unwrap();
+
+    //Unrevealed secrets forfeit the parents without minting a child
+    let mut suite = setup_fusion(false);
+    approve_manager(&mut suite);
+    let commitment = format!("{:x}", Sha256::digest("secret".as_bytes()));
+    fuse(&mut suite, "1", "2", Some(commitment)).unwrap();
+
+    suite
+        .app
+        .update_block(|block| block.height += REVEAL_TIMEOUT + 1);
+    resolve(&mut suite, 1).unwrap();
+    assert!(user_tokens(&suite).is_empty());
+}
+
+#[test]
+fn dismantling_fused_characters_creates_no_traits() {
+    let mut suite = setup_fusion(false);
+    let trait_manager_code_id = suite.app.store_code(mock_trait_manager());
+    let trait_manager = suite
+        .app
+        .instantiate_contract(
+            trait_manager_code_id,
+            Addr::unchecked(OWNER),
+            &Empty {},
+            &[],
+            "trait-manager",
+            None,
+        )
+        .unwrap();
+    let catalog_trait = |id: u32, trait_type: &str, trait_value: &str| Trait {
+        id,
+        trait_type: trait_type.to_string(),
+        trait_value: trait_value.to_string(),
+        trait_rarity: "common".to_string(),
+        mint_price: None,
+        max_supply: None,
+        minted: 0,
+        per_address_limit: None,
+        sale_start: None,
+        sale_end: None,
+    };
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddTraits {
+                new_traits: vec![
+                    catalog_trait(1, "ears", "pointy"),
+                    catalog_trait(2, "ears", "round"),
+                    catalog_trait(3, "eyes", "laser"),
+                    catalog_trait(4, "mouth", "smile"),
+                ],
+            },
+            &[],
+        )
+        .unwrap();
+    //The parents are kept and no slot is ever lost to the dismantling odds
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::UpdateConfig {
+                new_config: UpdateCharacterManagerParamsMsg {
+                    fusion: FusionConfig {
+                        burn_parents: false,
+                        ..FusionConfig::default()
+                    },
+                    dismantle: DismantleConfig {
+                        trait_manager: Some(trait_manager.clone()),
+                        loss_percents: vec![],
+                    },
+                    ..update_params()
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    approve_manager(&mut suite);
+
+    let trait_supply = |suite: &Suite| {
+        let minted: Vec<(String, TraitMetadata)> = suite
+            .app
+            .wrap()
+            .query_wasm_smart(&trait_manager, &Empty {})
+            .unwrap();
+        let tokens: cw721::NumTokensResponse = suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &suite.traits,
+                &cw721_trait_onchain::QueryMsg::<Empty>::NumTokens {},
+            )
+            .unwrap();
+        minted.len() as u64 + tokens.count
+    };
+    let supply = trait_supply(&suite);
+
+    let secret = "secret";
+    let commitment = format!("{:x}", Sha256::digest(secret.as_bytes()));
+    fuse(&mut suite, "1", "2", Some(commitment.clone())).unwrap();
+    reveal_fusion(&mut suite, 1, secret);
+    dismantle(&mut suite, "3", Some(commitment)).unwrap();
+    reveal_dismantle(&mut suite, 2, secret);
+
+    assert_eq!(user_tokens(&suite), vec!["1".to_string(), "2".to_string()]);
+    assert_eq!(trait_supply(&suite), supply);
+}
+
+#[test]
+fn dismantle_character() {
+    let mut suite = setup(false);
+    let trait_manager_code_id = suite.app.store_code(mock_trait_manager());
+    let trait_manager = suite
+        .app
+        .instantiate_contract(
+            trait_manager_code_id,
+            Addr::unchecked(OWNER),
+            &Empty {},
+            &[],
+            "trait-manager",
+            None,
+        )
+        .unwrap();
+
+    let catalog_trait = |id: u32, trait_type: &str, trait_value: &str, rarity: &str| Trait {
+        id,
+        trait_type: trait_type.to_string(),
+        trait_value: trait_value.to_string(),
+        trait_rarity: rarity.to_string(),
+        mint_price: None,
+        max_supply: None,
+        minted: 0,
+        per_address_limit: None,
+        sale_start: None,
+        sale_end: None,
+    };
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddTraits {
+                new_traits: vec![
+                    catalog_trait(1, "ears", "pointy", "common"),
+                    catalog_trait(2, "eyes", "laser", "legendary"),
+                ],
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddCharacters {
+                new_characters: vec![test_character()],
+            },
+            &[],
+        )
+        .unwrap();
+    for _ in 0..2 {
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                suite.manager.clone(),
+                &ExecuteMsg::Mint {
+                    character_id: Some(1),
+                    token_info: Metadata {
+                        name: None,
+                        traits: vec![
+                            ("ears".to_string(), "pointy".to_string()),
+                            ("eyes".to_string(), "laser".to_string()),
+                        ],
+                        rarity: Some("rare".to_string()),
+                        traits_equipped: None,
+                        locked: false,
+                    },
+                },
+                &coins(100, NATIVE_DENOM),
+            )
+            .unwrap();
+    }
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.collection.clone(),
+            &cw721_character_onchain::ExecuteMsg::<Metadata, Empty>::ApproveAll {
+                operator: suite.manager.to_string(),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+    let secret = "secret";
+    let commitment = Some(format!("{:x}", Sha256::digest(secret.as_bytes())));
+
+    assert_eq!(
+        dismantle(&mut suite, "1", commitment.clone()).unwrap_err(),
+        ContractError::DismantleDisabled {}
+    );
+
+    //Legendary traits are always lost
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::UpdateConfig {
+                new_config: UpdateCharacterManagerParamsMsg {
+                    dismantle: DismantleConfig {
+                        trait_manager: Some(trait_manager.clone()),
+                        loss_percents: vec![("legendary".to_string(), 100)],
+                    },
+                    ..update_params()
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::LockCharacter {
+                token_id: "2".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        dismantle(&mut suite, "2", commitment.clone()).unwrap_err(),
+        ContractError::CharacterAlreadyLocked {}
+    );
+    assert_eq!(
+        dismantle(&mut suite, "1", None).unwrap_err(),
+        ContractError::InvalidCommitment {}
+    );
+
+    //The character is kept as it is until its slots are drawn
+    dismantle(&mut suite, "1", commitment.clone()).unwrap();
+    assert_eq!(
+        dismantle(&mut suite, "1", commitment).unwrap_err(),
+        ContractError::CharacterPending {}
+    );
+    assert_eq!(user_tokens(&suite), vec!["1".to_string(), "2".to_string()]);
+
+    reveal_dismantle(&mut suite, 1, secret);
+    assert_eq!(user_tokens(&suite), vec!["2".to_string()]);
+    let trait_mints: Vec<(String, TraitMetadata)> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&trait_manager, &Empty {})
+        .unwrap();
+    assert_eq!(
+        trait_mints,
+        vec![(
+            USER.to_string(),
+            TraitMetadata {
+                trait_type: "ears".to_string(),
+                trait_value: "pointy".to_string(),
+                trait_rarity: "common".to_string(),
+            }
+        )]
+    );
+}
+
+#[test]
+fn dismantle_with_beacon_and_expiry() {
+    let mut suite = setup_fusion(true);
+    let trait_manager_code_id = suite.app.store_code(mock_trait_manager());
+    let trait_manager = suite
+        .app
+        .instantiate_contract(
+            trait_manager_code_id,
+            Addr::unchecked(OWNER),
+            &Empty {},
+            &[],
+            "trait-manager",
+            None,
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddTraits {
+                new_traits: vec![Trait {
+                    id: 1,
+                    trait_type: "ears".to_string(),
+                    trait_value: "pointy".to_string(),
+                    trait_rarity: "common".to_string(),
+                    mint_price: None,
+                    max_supply: None,
+                    minted: 0,
+                    per_address_limit: None,
+                    sale_start: None,
+                    sale_end: None,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::UpdateConfig {
+                new_config: UpdateCharacterManagerParamsMsg {
+                    randomness_source: RandomnessSource::Beacon {
+                        proxy_addr: suite.proxy.clone(),
+                    },
+                    dismantle: DismantleConfig {
+                        trait_manager: Some(trait_manager.clone()),
+                        loss_percents: vec![],
+                    },
+                    ..update_params()
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    approve_manager(&mut suite);
+    let trait_mints = |suite: &Suite| -> Vec<(String, TraitMetadata)> {
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart(&trait_manager, &Empty {})
+            .unwrap()
+    };
+
+    dismantle(&mut suite, "1", None).unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.proxy.clone(),
+            &MockProxyExecuteMsg::Publish {
+                randomness: HexBinary::from(vec![7u8; 32]),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["2".to_string()]);
+    assert_eq!(trait_mints(&suite).len(), 1);
+
+    //Beacon jobs that are never answered leave the character as it was
+    dismantle(&mut suite, "2", None).unwrap();
+    suite
+        .app
+        .update_block(|block| block.height += REVEAL_TIMEOUT + 1);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::ResolveExpiredDismantle { commitment_id: 2 },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["2".to_string()]);
+
+    //Unrevealed secrets forfeit the character without giving anything back
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::UpdateConfig {
+                new_config: UpdateCharacterManagerParamsMsg {
+                    dismantle: DismantleConfig {
+                        trait_manager: Some(trait_manager.clone()),
+                        loss_percents: vec![],
+                    },
+                    ..update_params()
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    let commitment = format!("{:x}", Sha256::digest("secret".as_bytes()));
+    dismantle(&mut suite, "2", Some(commitment)).unwrap();
+    suite
+        .app
+        .update_block(|block| block.height += REVEAL_TIMEOUT + 1);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::ResolveExpiredDismantle { commitment_id: 3 },
+            &[],
+        )
+        .unwrap();
+    assert!(user_tokens(&suite).is_empty());
+    assert_eq!(trait_mints(&suite).len(), 1);
+}
+
+#[test]
+fn rename_characters() {
+    let mut suite = setup(false);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::UpdateConfig {
+                new_config: UpdateCharacterManagerParamsMsg {
+                    names: NameConfig {
+                        min_length: 3,
+                        max_length: 12,
+                        allowed_chars: Some(
+                            "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 "
+                                .to_string(),
+                        ),
+                        reserved_words: vec!["Admin".to_string()],
+                        rename_fee: Some(coin(10, NATIVE_DENOM)),
+                        rename_cooldown: Some(60),
+                    },
+                    ..update_params()
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+    let mint = |suite: &mut Suite, name: Option<&str>| {
+        suite.app.execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::Mint {
+                character_id: None,
+                token_info: Metadata {
+                    name: name.map(|name| name.to_string()),
+                    traits: vec![],
+                    rarity: None,
+                    traits_equipped: None,
+                    locked: false,
+                },
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+    };
+    let rename = |suite: &mut Suite, token_id: &str, name: &str, fee: u128| {
+        suite.app.execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::ChangeName {
+                token_id: token_id.to_string(),
+                new_name: name.to_string(),
+            },
+            &if fee == 0 {
+                vec![]
+            } else {
+                coins(fee, NATIVE_DENOM)
+            },
+        )
+    };
+    let resolve = |suite: &Suite, name: &str| -> Option<String> {
+        let res: ResolveNameResponse = suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &suite.manager,
+                &CharacterManagerQueryMsg::ResolveName {
+                    name: name.to_string(),
+                },
+            )
+            .unwrap();
+        res.token_id
+    };
+
+    //Names given at mint are checked and indexed too
+    let err = mint(&mut suite, Some("admin")).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ReservedName {}
+    );
+    mint(&mut suite, Some("Whiskers")).unwrap();
+    mint(&mut suite, None).unwrap();
+    assert_eq!(resolve(&suite, "WHISKERS"), Some("1".to_string()));
+
+    for (name, fee, expected) in [
+        ("whiskers", 10, ContractError::NameTaken {}),
+        ("ab", 10, ContractError::InvalidName {}),
+        ("mr_cat", 10, ContractError::InvalidName {}),
+        (" Mittens", 10, ContractError::InvalidName {}),
+        ("ADMIN", 10, ContractError::ReservedName {}),
+        ("Mittens", 0, ContractError::IncorrectRenameFee {}),
+        ("Mittens", 20, ContractError::IncorrectRenameFee {}),
+    ] {
+        let err = rename(&mut suite, "2", name, fee).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), expected);
+    }
+
+    //The fee goes through the revenue split like the mints
+    rename(&mut suite, "2", "Mittens", 10).unwrap();
+    assert_eq!(resolve(&suite, "mittens"), Some("2".to_string()));
+    let treasury = suite
+        .app
+        .wrap()
+        .query_balance(TREASURY, NATIVE_DENOM)
+        .unwrap();
+    assert_eq!(treasury.amount.u128(), 210);
+
+    let err = rename(&mut suite, "2", "Socks", 10).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RenameCooldown {}
+    );
+    suite
+        .app
+        .update_block(|block| block.time = block.time.plus_seconds(60));
+    rename(&mut suite, "2", "Socks", 10).unwrap();
+
+    //The previous name is released
+    assert_eq!(resolve(&suite, "mittens"), None);
+    rename(&mut suite, "1", "Mittens", 10).unwrap();
+    assert_eq!(resolve(&suite, "mittens"), Some("1".to_string()));
+    assert_eq!(resolve(&suite, "whiskers"), None);
+    let character: CharacterInfoResponse<Metadata> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.collection,
+            &cw721_character_onchain::QueryMsg::<Empty>::CharacterInfo {
+                token_id: "1".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(character.token_info.name, Some("Mittens".to_string()));
+}
+
+#[test]
+fn index_names_given_before_the_index() {
+    let mut suite = setup(false);
+
+    //Characters named before the index existed are not in it
+    for (token_id, name) in [("10", "Whiskers"), ("11", "whiskers"), ("12", "Mittens")] {
+        suite
+            .app
+            .execute_contract(
+                suite.manager.clone(),
+                suite.collection.clone(),
+                &cw721_character_onchain::ExecuteMsg::<Metadata, Empty>::Mint {
+                    token_id: token_id.to_string(),
+                    owner: USER.to_string(),
+                    token_uri: None,
+                    extension: Metadata {
+                        name: Some(name.to_string()),
+                        traits: vec![],
+                        rarity: None,
+                        traits_equipped: None,
+                        locked: false,
+                    },
+                },
+                &[],
+            )
+            .unwrap();
+    }
+    let resolve = |suite: &Suite, name: &str| -> Option<String> {
+        let res: ResolveNameResponse = suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &suite.manager,
+                &CharacterManagerQueryMsg::ResolveName {
+                    name: name.to_string(),
+                },
+            )
+            .unwrap();
+        res.token_id
+    };
+    let rename = |suite: &mut Suite, token_id: &str, name: &str| {
+        suite.app.execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::ChangeName {
+                token_id: token_id.to_string(),
+                new_name: name.to_string(),
+            },
+            &[],
+        )
+    };
+    assert_eq!(resolve(&suite, "whiskers"), None);
+
+    let index_msg = |start_after: Option<&str>| ExecuteMsg::IndexNames {
+        start_after: start_after.map(|token_id| token_id.to_string()),
+        limit: Some(2),
+    };
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &index_msg(None),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Ownership(cw_ownable::OwnershipError::NotOwner)
+    );
+
+    //The first character indexed keeps a shared name
+    let indexed = |res: cw_multi_test::AppResponse, count: &str| {
+        res.events.iter().any(|event| {
+            event
+                .attributes
+                .iter()
+                .any(|attr| attr.key == "indexed" && attr.value == count)
+        })
+    };
+    let res = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &index_msg(None),
+            &[],
+        )
+        .unwrap();
+    assert!(indexed(res, "1"));
+    let res = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &index_msg(Some("11")),
+            &[],
+        )
+        .unwrap();
+    assert!(indexed(res, "1"));
+    assert_eq!(resolve(&suite, "WHISKERS"), Some("10".to_string()));
+    assert_eq!(resolve(&suite, "mittens"), Some("12".to_string()));
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::Mint {
+                character_id: None,
+                token_info: Metadata {
+                    name: None,
+                    traits: vec![],
+                    rarity: None,
+                    traits_equipped: None,
+                    locked: false,
+                },
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+    let err = rename(&mut suite, "1", "Mittens").unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NameTaken {}
+    );
+
+    //Burning through the collection frees the name
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.collection.clone(),
+            &cw721_character_onchain::ExecuteMsg::<Metadata, Empty>::Burn {
+                token_id: "12".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(resolve(&suite, "mittens"), None);
+    rename(&mut suite, "1", "Mittens").unwrap();
+    assert_eq!(resolve(&suite, "mittens"), Some("1".to_string()));
+}
+
+#[test]
+fn moderate_names() {
+    let mut suite = setup(false);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::UpdateConfig {
+                new_config: UpdateCharacterManagerParamsMsg {
+                    names: NameConfig {
+                        rename_cooldown: Some(60),
+                        ..NameConfig::default()
+                    },
+                    ..update_params()
+                },
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::Mint {
+                character_id: None,
+                token_info: Metadata {
+                    name: Some("Badcat".to_string()),
+                    traits: vec![],
+                    rarity: None,
+                    traits_equipped: None,
+                    locked: false,
+                },
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+
+    let reset = |suite: &mut Suite, sender: &str, reason: &str| {
+        suite.app.execute_contract(
+            Addr::unchecked(sender),
+            suite.manager.clone(),
+            &ExecuteMsg::ForceResetName {
+                token_id: "1".to_string(),
+                reason: reason.to_string(),
+            },
+            &[],
+        )
+    };
+
+    let err = reset(&mut suite, "moderator", "offensive").unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NotModerator {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::AddModerators {
+                moderators: vec!["moderator".to_string()],
+            },
+            &[],
+        )
+        .unwrap();
+    let moderators: Vec<String> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&suite.manager, &CharacterManagerQueryMsg::Moderators {})
+        .unwrap();
+    assert_eq!(moderators, vec!["moderator".to_string()]);
+
+    let err = reset(&mut suite, "moderator", " ").unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MissingReason {}
+    );
+
+    let res = reset(&mut suite, "moderator", "offensive").unwrap();
+    assert!(res.has_event(
+        &Event::new("wasm-force_reset_name")
+            .add_attribute("moderator", "moderator")
+            .add_attribute("previous_name", "Badcat")
+            .add_attribute("reason", "offensive")
+    ));
+
+    let character: CharacterInfoResponse<Metadata> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.collection,
+            &cw721_character_onchain::QueryMsg::<Empty>::CharacterInfo {
+                token_id: "1".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(character.token_info.name, None);
+    let resolved: ResolveNameResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.manager,
+            &CharacterManagerQueryMsg::ResolveName {
+                name: "badcat".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(resolved.token_id, None);
+
+    //The reset counts as a rename for the cooldown and the name stays blocked
+    let rename = |suite: &mut Suite, name: &str| {
+        suite.app.execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::ChangeName {
+                token_id: "1".to_string(),
+                new_name: name.to_string(),
+            },
+            &[],
+        )
+    };
+    let err = rename(&mut suite, "Goodcat").unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RenameCooldown {}
+    );
+    suite
+        .app
+        .update_block(|block| block.time = block.time.plus_seconds(60));
+    let err = rename(&mut suite, "BADCAT").unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NameBlocked {}
+    );
+    rename(&mut suite, "Goodcat").unwrap();
+
+    let history: Vec<ModerationResponse> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.manager,
+            &CharacterManagerQueryMsg::ModerationHistory {
+                token_id: "1".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        history,
+        vec![ModerationResponse {
+            moderator: "moderator".to_string(),
+            previous_name: Some("Badcat".to_string()),
+            reason: "offensive".to_string(),
+            height: suite.app.block_info().height,
+        }]
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::RemoveModerators {
+                moderators: vec!["moderator".to_string()],
+            },
+            &[],
+        )
+        .unwrap();
+    let err = reset(&mut suite, "moderator", "offensive").unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NotModerator {}
+    );
+
+    let unblock = |suite: &mut Suite, sender: &str| {
+        suite.app.execute_contract(
+            Addr::unchecked(sender),
+            suite.manager.clone(),
+            &ExecuteMsg::UnblockName {
+                name: "Badcat".to_string(),
+            },
+            &[],
+        )
+    };
+    let err = unblock(&mut suite, "moderator").unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NotModerator {}
+    );
+    unblock(&mut suite, OWNER).unwrap();
+    suite
+        .app
+        .update_block(|block| block.time = block.time.plus_seconds(60));
+    rename(&mut suite, "Badcat").unwrap();
+}
+
+#[test]
+fn migrate_legacy_config() {
+    let mut deps = mock_dependencies();
+    cw2::set_contract_version(deps.as_mut().storage, "character-manager", "0.1.0").unwrap();
+    cw_ownable::initialize_owner(&mut deps.storage, &deps.api, Some(OWNER)).unwrap();
+    //Config stored by 0.1.0, nothing was burnt
+    LEGACY_CONFIG
+        .save(
+            deps.as_mut().storage,
+            &LegacyConfig {
+                collection_code_id: 3,
+                empty_character_mint_price: coin(100, NATIVE_DENOM),
+                burn_ratio: 0,
+                destination: Some(Addr::unchecked(TREASURY)),
+                extension: Empty {},
+            },
+        )
+        .unwrap();
+    //Pending lootboxes copied their characters with one field per slot
+    let legacy_character = LegacyCharacter {
+        id: 1,
+        ears: None,
+        eyes: Some("laser".to_string()),
+        mouth: None,
+        fur_type: None,
+        fur_color: None,
+        tail_shape: Some("curly".to_string()),
+        rarity: "rare".to_string(),
+        locked: false,
+        mint_price: None,
+        max_supply: None,
+        minted: 0,
+        per_address_limit: None,
+        sale_start: None,
+        sale_end: None,
+    };
+    LEGACY_LOOTBOX_COMMITMENTS
+        .save(
+            deps.as_mut().storage,
+            1,
+            &LegacyLootboxCommitment {
+                owner: Addr::unchecked(USER),
+                receiver: Addr::unchecked(USER),
+                lootbox: LegacyCharacterLootbox {
+                    id: 1,
+                    characters: vec![legacy_character],
+                    possibilities: vec![1],
+                    mint_price: coin(100, NATIVE_DENOM),
+                    max_supply: None,
+                    minted: 0,
+                    per_address_limit: None,
+                    sale_start: None,
+                    sale_end: None,
+                },
+                commitment: None,
+                committed_at: 12345,
+            },
+        )
+        .unwrap();
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|attr| attr.key == "from_version" && attr.value == "0.1.0"));
+
+    let config = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(config.empty_character_mint_price, coin(100, NATIVE_DENOM));
+    assert_eq!(
+        config.revenue_split.recipients,
+        vec![(Addr::unchecked(TREASURY), 100)]
+    );
+    assert_eq!(config.revenue_split.burn_weight, 0);
+    assert_eq!(config.randomness_source, RandomnessSource::Internal);
+    let commitment = LOOTBOX_COMMITMENTS.load(&deps.storage, 1).unwrap();
+    assert_eq!(
+        commitment.lootbox.characters[0].traits,
+        vec![
+            ("eyes".to_string(), "laser".to_string()),
+            ("tail_shape".to_string(), "curly".to_string()),
+        ]
+    );
+
+    //Only the character manager can be migrated, and never to an older version
+    cw2::set_contract_version(deps.as_mut().storage, "trait-manager", "0.1.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::WrongMigrateContract("trait-manager".to_string())
+    );
+    cw2::set_contract_version(deps.as_mut().storage, "character-manager", "9.0.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+    assert_eq!(err, ContractError::WrongMigrateVersion("9.0.0".to_string()));
+}
@@ -23,27 +23,90 @@ pub enum ContractError {
     #[error("Mint prices array must be the same length as rarities array")]
     NotSameLength {},
 
-    #[error("Burn ratio must be between 0 and 100")]
-    InvalidBurnRatio {},
+    #[error("Revenue split must have a positive total weight")]
+    InvalidRevenueSplit {},
+
+    #[error("Withdraw amount must be positive and at most the accrued revenue")]
+    InvalidWithdrawAmount {},
+
+    #[error("Mint limit of this address reached")]
+    MintLimitReached {},
+
+    #[error("Mint limit of this address for the current block reached")]
+    BlockMintLimitReached {},
+
+    #[error("Mint ceiling of the current block reached, try again in a later block")]
+    BlockCeilingReached {},
+
+    #[error("This operation is paused")]
+    OperationPaused {},
 
     #[error("Incorrect mind funds sent")]
     IncorrectMintFunds {},
 
-    #[error("If mint price is not fully burned or is not the native currency, destination address of mint funds cannot be empty")]
-    NoMintDestination {},
-
     #[error("Sender is not the character owner")]
     NotCharacterOwner {},
 
     #[error("Character is already locked, cannot be modified")]
     CharacterAlreadyLocked {},
 
+    #[error("Character is waiting for a draw, cannot be modified")]
+    CharacterPending {},
+
     #[error("Sender is not the trait owner")]
     NotTraitOwner {},
 
     #[error("Trait type not found")]
     InvalidTrait {},
 
+    #[error("Cannot equip more than one trait of the same type")]
+    DuplicateTraitType {},
+
+    #[error("No trait equipped for this trait type")]
+    TraitNotEquipped {},
+
+    #[error("Trait is already equipped on this character")]
+    TraitAlreadyEquipped {},
+
+    #[error("A character cannot be fused with itself")]
+    SameParents {},
+
+    #[error("Fusion needs a positive parent weight and a mutation chance of at most 100 percent")]
+    InvalidFusionConfig {},
+
+    #[error("Dismantling is disabled until a trait manager is configured")]
+    DismantleDisabled {},
+
+    #[error("Trait loss chances cannot be over 100 percent")]
+    InvalidDismantleConfig {},
+
+    #[error("Name length bounds must be positive and in order")]
+    InvalidNameConfig {},
+
+    #[error("Name does not follow the naming rules")]
+    InvalidName {},
+
+    #[error("Name is reserved")]
+    ReservedName {},
+
+    #[error("Name is already taken by another character")]
+    NameTaken {},
+
+    #[error("Name was blocked by a moderator")]
+    NameBlocked {},
+
+    #[error("Funds sent do not match the rename fee")]
+    IncorrectRenameFee {},
+
+    #[error("Character was renamed too recently")]
+    RenameCooldown {},
+
+    #[error("Only the owner and the moderators added by the owner can reset names")]
+    NotModerator {},
+
+    #[error("A reason is needed to reset a name")]
+    MissingReason {},
+
     #[error("Character does not exist in mintables contract")]
     InvalidCharacter {},
 
@@ -61,4 +124,67 @@ pub enum ContractError {
 
     #[error("Lootbox does not exist in mintables contract")]
     InvalidLootbox {},
+
+    #[error("Commitment must be a hex encoded sha256 hash")]
+    InvalidCommitment {},
+
+    #[error("Commitment not found")]
+    CommitmentNotFound {},
+
+    #[error("Secret does not match the commitment")]
+    InvalidSecret {},
+
+    #[error("Commitment can only be revealed in a later block")]
+    RevealTooEarly {},
+
+    #[error("Commitment has expired and can only be resolved")]
+    CommitmentExpired {},
+
+    #[error("Commitment has not expired yet")]
+    CommitmentNotExpired {},
+
+    #[error("Commitment is waiting for randomness from the beacon")]
+    AwaitingRandomness {},
+
+    #[error("Randomness job not found")]
+    JobNotFound {},
+
+    #[error("Only the configured randomness beacon can deliver randomness")]
+    UnauthorizedRandomnessSource {},
+
+    #[error("Sold out")]
+    SoldOut {},
+
+    #[error("Not on sale at this time")]
+    SaleNotActive {},
+
+    #[error("No mint price listed for the entry or its rarity")]
+    NoMintPrice {},
+
+    #[error("Presale phase does not exist")]
+    PresalePhaseNotFound {},
+
+    #[error("Sender is not in the presale allowlist")]
+    NotAllowlisted {},
+
+    #[error("Presale mint limit reached")]
+    PresaleLimitReached {},
+
+    #[error("Presale discount cannot be over 100 percent")]
+    InvalidDiscount {},
+
+    #[error("found version ({0}) which is newer than the code being migrated to")]
+    WrongMigrateVersion(String),
+
+    #[error("found contract ({0}) while attempting to migrate")]
+    WrongMigrateContract(String),
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
 }
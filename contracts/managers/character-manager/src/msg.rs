@@ -1,18 +1,36 @@
 use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{HexBinary, Uint128};
+use cw20::Cw20ReceiveMsg;
 use cw721_character_onchain::msg::Metadata;
 use cw_ownable::cw_ownable_execute;
-use utils::msg::UpdateCharacterManagerParamsMsg;
+use utils::{msg::UpdateCharacterManagerParamsMsg, Operation, PresalePhase};
+
+#[cw_serde]
+pub struct MigrateMsg {}
 
 #[cw_ownable_execute]
 #[cw_serde]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
     Mint {
+        //Mintables id of the pre-made character to mint, none for empty characters
+        character_id: Option<u32>,
         token_info: Metadata,
     },
     MintTo {
+        character_id: Option<u32>,
         token_info: Metadata,
         receiver: String,
     },
+    //Presale mint at the phase's discounted price, only for addresses in its allowlist
+    MintWithProof {
+        phase_id: u32,
+        //Hex encoded sibling hashes from the sender's leaf up to the root
+        proof: Vec<String>,
+        character_id: Option<u32>,
+        token_info: Metadata,
+        receiver: Option<String>,
+    },
     MintBundle {
         bundle_id: u32,
         receiver: Option<String>,
@@ -20,8 +38,24 @@ pub enum ExecuteMsg {
     OpenLootbox {
         lootbox_id: u32,
         receiver: Option<String>,
+        //Hex encoded sha256 hash of a secret that will be revealed in a later block.
+        //Only used with internal randomness, ignored when a beacon is configured
+        commitment: Option<String>,
+    },
+    RevealLootbox {
+        commitment_id: u64,
+        secret: String,
+    },
+    ResolveExpiredLootbox {
+        commitment_id: u64,
     },
-    ChangeName{
+    //Callback from the randomness beacon proxy, the job id is the lootbox, fusion or dismantle commitment id
+    ReceiveRandomness {
+        job_id: String,
+        randomness: HexBinary,
+    },
+    //Pays the rename fee, if any, in native funds
+    ChangeName {
         token_id: String,
         new_name: String,
     },
@@ -29,10 +63,127 @@ pub enum ExecuteMsg {
         token_id: String,
         trait_ids: Vec<String>,
     },
+    UnequipTraits {
+        token_id: String,
+        trait_types: Vec<String>,
+    },
+    SwapTrait {
+        token_id: String,
+        trait_id: String,
+    },
     LockCharacter {
         token_id: String,
     },
+    //Mints a character whose slots are inherited from two unlocked characters of the sender,
+    //which are burnt or locked. The manager must be approved on the character collection
+    Fuse {
+        parent_a: String,
+        parent_b: String,
+        //Hex encoded sha256 hash of a secret that will be revealed in a later block.
+        //Only used with internal randomness, ignored when a beacon is configured
+        commitment: Option<String>,
+    },
+    RevealFusion {
+        commitment_id: u64,
+        secret: String,
+    },
+    ResolveExpiredFusion {
+        commitment_id: u64,
+    },
+    //Burns an unlocked character of the sender and gives its slots back as traits.
+    //The manager must be approved on the character collection
+    Dismantle {
+        token_id: String,
+        //Hex encoded sha256 hash of a secret that will be revealed in a later block.
+        //Only used with internal randomness, ignored when a beacon is configured
+        commitment: Option<String>,
+    },
+    RevealDismantle {
+        commitment_id: u64,
+        secret: String,
+    },
+    ResolveExpiredDismantle {
+        commitment_id: u64,
+    },
+    //Mints paid with a CW20 token, the message is a `ReceiveMsg`
+    Receive(Cw20ReceiveMsg),
     UpdateConfig {
         new_config: UpdateCharacterManagerParamsMsg,
     },
+    //Sets or, when no phase is given, removes a presale phase
+    SetPresalePhase {
+        phase_id: u32,
+        phase: Option<PresalePhase>,
+    },
+    //Owner only, sends out revenue accrued by the manager
+    Withdraw {
+        denom: String,
+        amount: Uint128,
+        to: String,
+    },
+    //Owner or guardian, without operations every operation is paused or unpaused
+    Pause {
+        operations: Option<Vec<Operation>>,
+    },
+    Unpause {
+        operations: Option<Vec<Operation>>,
+    },
+    SetGuardian {
+        guardian: Option<String>,
+    },
+    //Owner only, moderators allowed to send `ForceResetName`
+    AddModerators {
+        moderators: Vec<String>,
+    },
+    RemoveModerators {
+        moderators: Vec<String>,
+    },
+    //Owner or moderator, clears the name of a character and records why in its moderation history
+    ForceResetName {
+        token_id: String,
+        reason: String,
+    },
+    //Owner or moderator, lets characters take a name blocked by `ForceResetName` again
+    UnblockName {
+        name: String,
+    },
+    //Owner only, adds the names of a page of characters to the name index, for characters named before it existed
+    IndexNames {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+/// Mints that can be paid by sending CW20 tokens to the manager
+#[cw_serde]
+pub enum ReceiveMsg {
+    Mint {
+        character_id: Option<u32>,
+        token_info: Metadata,
+    },
+    MintTo {
+        character_id: Option<u32>,
+        token_info: Metadata,
+        receiver: String,
+    },
+    MintWithProof {
+        phase_id: u32,
+        proof: Vec<String>,
+        character_id: Option<u32>,
+        token_info: Metadata,
+        receiver: Option<String>,
+    },
+    MintBundle {
+        bundle_id: u32,
+        receiver: Option<String>,
+    },
+    OpenLootbox {
+        lootbox_id: u32,
+        receiver: Option<String>,
+        commitment: Option<String>,
+    },
+    ChangeName {
+        token_id: String,
+        new_name: String,
+    },
 }
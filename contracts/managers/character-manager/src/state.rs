@@ -1,12 +1,28 @@
-use cosmwasm_std::{Addr, Empty, StdResult, Storage};
-use cw_storage_plus::Item;
-use utils::CharacterManagerConfig;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, BlockInfo, Coin, Empty, StdResult, Storage};
+use cw_storage_plus::{Item, Map};
+use mintables::state::{CharacterLootbox, LegacyCharacterLootbox};
+use utils::{
+    random::block_entropy, CharacterManagerConfig, Operation, Payment, PresalePhase, RevenueTotals,
+};
 
 pub type Config = CharacterManagerConfig<Empty>;
 
 /// Initial configuration of the minter
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// Config stored by 0.1.0, before the revenue split replaced the burn ratio and destination
+#[cw_serde]
+pub struct LegacyConfig {
+    pub collection_code_id: u64,
+    pub empty_character_mint_price: Coin,
+    pub burn_ratio: u64,
+    pub destination: Option<Addr>,
+    pub extension: Empty,
+}
+
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+
 /// This is saved after handling a reply in instantiation. Therefore it's not in `Config`.
 pub const COLLECTION_ADDRESS: Item<Addr> = Item::new("collection_address");
 
@@ -24,3 +40,157 @@ pub fn increment_token_index(store: &mut dyn Storage) -> StdResult<u64> {
     TOKEN_INDEX.save(store, &val)?;
     Ok(val)
 }
+
+/// A paid lootbox opening waiting for its secret to be revealed or for beacon randomness
+#[cw_serde]
+pub struct LootboxCommitment {
+    pub owner: Addr,
+    pub receiver: Addr,
+    //Lootbox contents at the time of payment, so later catalog changes do not affect the result
+    pub lootbox: CharacterLootbox,
+    //Hex encoded sha256 hash of the secret, none when the randomness comes from a beacon
+    pub commitment: Option<String>,
+    pub committed_at: u64,
+    //Hash of the commitment block, only set by commitments made before the draws used the entropy of a later block
+    #[serde(default)]
+    pub entropy: String,
+    //Beacon proxy the job was sent to, the only one allowed to answer it
+    #[serde(default)]
+    pub proxy: Option<Addr>,
+    //Payment held until the beacon answers, refunded if it never does
+    #[serde(default)]
+    pub payment: Option<Payment>,
+}
+
+pub const LOOTBOX_COMMITMENTS: Map<u64, LootboxCommitment> = Map::new("lootbox_commitments");
+
+/// Commitment stored up to 0.2.0, when the lootbox characters had one field per slot
+#[cw_serde]
+pub struct LegacyLootboxCommitment {
+    pub owner: Addr,
+    pub receiver: Addr,
+    pub lootbox: LegacyCharacterLootbox,
+    pub commitment: Option<String>,
+    pub committed_at: u64,
+}
+
+pub const LEGACY_LOOTBOX_COMMITMENTS: Map<u64, LegacyLootboxCommitment> =
+    Map::new("lootbox_commitments");
+
+/// A fusion waiting for its secret to be revealed or for beacon randomness
+#[cw_serde]
+pub struct FusionCommitment {
+    pub owner: Addr,
+    pub parent_a: String,
+    pub parent_b: String,
+    //Hex encoded sha256 hash of the secret, none when the randomness comes from a beacon
+    pub commitment: Option<String>,
+    pub committed_at: u64,
+    //Beacon proxy the job was sent to, the only one allowed to answer it
+    pub proxy: Option<Addr>,
+}
+
+pub const FUSION_COMMITMENTS: Map<u64, FusionCommitment> = Map::new("fusion_commitments");
+
+/// A dismantling waiting for its secret to be revealed or for beacon randomness
+#[cw_serde]
+pub struct DismantleCommitment {
+    pub owner: Addr,
+    pub token_id: String,
+    //Hex encoded sha256 hash of the secret, none when the randomness comes from a beacon
+    pub commitment: Option<String>,
+    pub committed_at: u64,
+    //Beacon proxy the job was sent to, the only one allowed to answer it
+    pub proxy: Option<Addr>,
+}
+
+pub const DISMANTLE_COMMITMENTS: Map<u64, DismantleCommitment> = Map::new("dismantle_commitments");
+
+/// Characters waiting for a draw, by token id to the commitment id. They cannot be modified,
+/// locked, fused or dismantled until the draw is resolved
+pub const PENDING_CHARACTERS: Map<&str, u64> = Map::new("pending_characters");
+
+/// This keeps track of the ids given to lootbox, fusion and dismantle commitments, which are also the beacon job ids
+pub const COMMITMENT_INDEX: Item<u64> = Item::new("commitment_index");
+
+pub fn increment_commitment_index(store: &mut dyn Storage) -> StdResult<u64> {
+    let val = COMMITMENT_INDEX.may_load(store)?.unwrap_or_default() + 1;
+    COMMITMENT_INDEX.save(store, &val)?;
+    Ok(val)
+}
+
+/// Height of the last block with commitments waiting for the entropy of a later block
+pub const PENDING_ENTROPY: Item<u64> = Item::new("pending_entropy");
+
+/// Entropy of the first block after each commitment block in which the manager was executed, by commitment height
+pub const BLOCK_ENTROPY: Map<u64, String> = Map::new("block_entropy");
+
+/// Stores the entropy of the current block for the commitments made in an earlier block
+pub fn record_block_entropy(store: &mut dyn Storage, block: &BlockInfo) -> StdResult<()> {
+    if let Some(height) = PENDING_ENTROPY.may_load(store)? {
+        if height < block.height {
+            BLOCK_ENTROPY.save(store, height, &block_entropy(block))?;
+            PENDING_ENTROPY.remove(store);
+        }
+    }
+    Ok(())
+}
+
+pub const PRESALE_PHASES: Map<u32, PresalePhase> = Map::new("presale_phases");
+
+/// Presale mints made by each address in each phase
+pub const PRESALE_MINTS: Map<(u32, &Addr), u32> = Map::new("presale_mints");
+
+/// Revenue accounting per denom, CW20 denoms are the token contract address
+pub const REVENUE: Map<&str, RevenueTotals> = Map::new("revenue");
+
+/// Purchases of each address across every item sold by the manager
+pub const ADDRESS_MINTS: Map<&Addr, u32> = Map::new("address_mints");
+
+/// Height of the last block in which each address bought something and its purchases in that block
+pub const BLOCK_MINTS: Map<&Addr, (u64, u32)> = Map::new("block_mints");
+
+/// Height of the last block with a purchase and the purchases of every address in that block
+pub const BLOCK_TOTAL_MINTS: Item<(u64, u32)> = Item::new("block_total_mints");
+
+/// Purchases of each address per catalog entry, by mintables id
+pub const CHARACTER_MINTS: Map<(&Addr, u32), u32> = Map::new("character_mints");
+pub const BUNDLE_MINTS: Map<(&Addr, u32), u32> = Map::new("bundle_mints");
+pub const LOOTBOX_MINTS: Map<(&Addr, u32), u32> = Map::new("lootbox_mints");
+
+/// Address allowed to pause and unpause operations besides the owner
+pub const GUARDIAN: Item<Addr> = Item::new("guardian");
+
+pub const PAUSED: Item<Vec<Operation>> = Item::new("paused");
+
+/// Parents of the characters minted by fusion, kept after the parents are burnt
+#[cw_serde]
+pub struct Lineage {
+    pub parents: Vec<String>,
+    pub generation: u32,
+}
+
+pub const LINEAGE: Map<&str, Lineage> = Map::new("lineage");
+
+/// Character holding each name, by lowercase name so names differing only in case are the same
+pub const NAMES: Map<&str, String> = Map::new("names");
+
+/// Block time, in seconds, of the last rename of each character
+pub const LAST_RENAMES: Map<&str, u64> = Map::new("last_renames");
+
+/// Lowercase names reset by a moderator, which no character can take until they are unblocked
+pub const BLOCKED_NAMES: Map<&str, Empty> = Map::new("blocked_names");
+
+/// Addresses allowed to reset character names besides the owner
+pub const MODERATORS: Item<Vec<Addr>> = Item::new("moderators");
+
+/// Name reset by a moderator, kept in the history of the character
+#[cw_serde]
+pub struct Moderation {
+    pub moderator: Addr,
+    pub previous_name: Option<String>,
+    pub reason: String,
+    pub height: u64,
+}
+
+pub const MODERATION_HISTORY: Map<&str, Vec<Moderation>> = Map::new("moderation_history");
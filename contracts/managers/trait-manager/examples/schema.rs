@@ -1,6 +1,6 @@
 use cosmwasm_schema::write_api;
 use cosmwasm_std::Empty;
-use trait_manager::msg::ExecuteMsg;
+use trait_manager::msg::{ExecuteMsg, MigrateMsg};
 use utils::msg::BaseTraitManagerCreateMsg as InstantiateMsg;
 use utils::query::TraitManagerQueryMsg as QueryMsg;
 
@@ -9,5 +9,6 @@ fn main() {
         instantiate: InstantiateMsg<Empty>,
         execute: ExecuteMsg,
         query: QueryMsg,
+        migrate: MigrateMsg,
     }
 }
\ No newline at end of file
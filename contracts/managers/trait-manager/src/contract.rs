@@ -1,30 +1,54 @@
 use crate::{
-    msg::ExecuteMsg,
+    msg::{ExecuteMsg, MigrateMsg, ReceiveMsg},
     state::{
-        increment_token_index, Config, COLLECTION_ADDRESS, CONFIG, MINTABLE_COLLECTION_ADDRESS,
+        increment_commitment_index, increment_token_index, record_block_entropy, Config,
+        CraftCommitment, LootboxCommitment, ADDRESS_MINTS, BLOCK_ENTROPY, BLOCK_MINTS,
+        BLOCK_TOTAL_MINTS, BUNDLE_MINTS, COLLECTION_ADDRESS, CONFIG, CRAFT_COMMITMENTS, GUARDIAN,
+        LEGACY_CONFIG, LOOTBOX_COMMITMENTS, LOOTBOX_MINTS, MANAGERS, MINTABLE_COLLECTION_ADDRESS,
+        PAUSED, PENDING_ENTROPY, PRESALE_MINTS, PRESALE_PHASES, REVENUE, TRAIT_MINTS,
     },
     ContractError,
 };
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    coins, to_binary, Addr, BankMsg, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo,
-    Reply, Response, StdResult, SubMsg, WasmMsg,
+    coin, from_binary, to_binary, Addr, Api, Binary, BlockInfo, CosmosMsg, Deps, DepsMut, Empty,
+    Env, HexBinary, MessageInfo, Order, Reply, Response, StdResult, Storage, SubMsg, Uint128,
+    WasmMsg,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::Cw20ReceiveMsg;
+use cw721::{AllNftInfoResponse, Cw721QueryMsg};
 use cw721_trait_onchain::{msg::Extension, InstantiateMsg};
+use cw_storage_plus::Map;
 use cw_utils::{one_coin, parse_reply_instantiate_data};
-use mintables::msg::{QueryMsg, TraitBundlesResp, TraitLootboxesResp, TraitsResp};
+use mintables::msg::{ExecuteMsg as MintablesExecuteMsg, MintableKind, QueryMsg, TraitsResp};
+use mintables::state::{
+    Recipe, RecipeInput, RecipeOutput, SaleWindow, Supply, Trait, TraitBundle, TraitLootbox,
+    TraitValueInfo,
+};
+use semver::Version;
 use sha2::{Digest, Sha256};
 use utils::{
-    msg::{BaseTraitManagerCreateMsg, UpdateTraitManagerParamsMsg},
-    query::{AllowedCollectionCodeIdResponse, TraitManagerQueryMsg, TraitManagerConfigResponse},
-    U64Ext, NATIVE_DENOM,
+    block_mints,
+    merkle::parse_hash,
+    msg::{BaseTraitManagerCreateMsg, RandomnessProxyExecuteMsg, UpdateTraitManagerParamsMsg},
+    query::{
+        AllowedCollectionCodeIdResponse, LootboxCommitmentResponse, MintAllowanceResponse,
+        PauseStatusResponse, PresaleMintsResponse, RevenueResponse, SaleItem,
+        TraitManagerConfigResponse, TraitManagerQueryMsg,
+    },
+    random::{drawn_position, random_number_1_to_x},
+    MintLimits, Operation, Payment, PresalePhase, RandomnessSource, RevenueSplit, RevenueTotals,
 };
 
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 const INSTANTIATE_CW721_REPLY_ID: u64 = 1;
+/// Number of blocks after a lootbox or craft commitment in which its secret can be revealed
+pub const REVEAL_TIMEOUT: u64 = 100;
+/// Page size used when scanning the mintables catalog
+const CATALOG_PAGE_LIMIT: u32 = 100;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -41,36 +65,7 @@ pub fn instantiate(
         Some(&info.sender.clone().into_string()),
     )?;
 
-    let range = 0..100;
-    if !range.contains(&msg.manager_params.burn_ratio) {
-        return Err(ContractError::InvalidBurnRatio {});
-    }
-
-    //If mint price is not fully burned then there must be a valid destination address to send funds to
-    if msg.manager_params.burn_ratio != 100 && msg.manager_params.destination.is_none() {
-        return Err(ContractError::NoMintDestination {});
-    }
-
-    //If we are selling anything using a non native denom, we need a destination address as we will not burn those non native tokens.
-    if msg
-        .manager_params
-        .mint_prices
-        .iter()
-        .any(|coin| coin.denom != NATIVE_DENOM)
-        && msg.manager_params.destination.is_none()
-    {
-        return Err(ContractError::NoMintDestination {});
-    }
-
-    if msg.manager_params.destination.is_some() {
-        deps.api.addr_validate(
-            &msg.manager_params
-                .destination
-                .clone()
-                .unwrap()
-                .into_string(),
-        )?;
-    }
+    validate_revenue_split(deps.api, &msg.manager_params.revenue_split)?;
 
     //The mint prices and rarities arrays must be same length, 1-to-1 price/rarity
     if msg.manager_params.mint_prices.len() != msg.manager_params.rarities.len() {
@@ -87,10 +82,18 @@ pub fn instantiate(
 
     MINTABLE_COLLECTION_ADDRESS.save(deps.storage, &msg.manager_params.mintable_collection_addr)?;
 
+    //The randomness beacon proxy must be a valid address as it is the only one allowed to deliver randomness
+    if let RandomnessSource::Beacon { proxy_addr } = &msg.manager_params.randomness_source {
+        deps.api.addr_validate(proxy_addr.as_str())?;
+    }
+
     let config = Config {
         collection_code_id: msg.collection_params.code_id,
-        burn_ratio: msg.manager_params.burn_ratio,
-        destination: msg.manager_params.destination,
+        mint_prices: msg.manager_params.mint_prices,
+        rarities: msg.manager_params.rarities,
+        revenue_split: msg.manager_params.revenue_split,
+        mint_limits: msg.manager_params.mint_limits,
+        randomness_source: msg.manager_params.randomness_source,
         extension: Empty {},
     };
 
@@ -105,6 +108,8 @@ pub fn instantiate(
             symbol: msg.collection_params.symbol.unwrap(),
             minter: env.contract.address.to_string(),
             collection_info,
+            trait_registry: Some(msg.manager_params.mintable_collection_addr.to_string()),
+            image_template: msg.collection_params.image_template.clone(),
         })?,
         funds: info.funds,
         admin: None,
@@ -131,84 +136,186 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    record_block_entropy(deps.storage, &env.block)?;
     match msg {
         ExecuteMsg::Mint { token_info } => {
-            mint(deps, info.clone(), token_info, info.sender.into_string())
+            let payment = Payment::Native(one_coin(&info)?);
+            let receiver = info.sender.to_string();
+            mint(deps, info, env, payment, token_info, receiver, None)
         }
         ExecuteMsg::MintTo {
             token_info,
             receiver,
-        } => mint(deps, info, token_info, receiver),
+        } => {
+            let payment = Payment::Native(one_coin(&info)?);
+            mint(deps, info, env, payment, token_info, receiver, None)
+        }
+        ExecuteMsg::MintWithProof {
+            phase_id,
+            proof,
+            token_info,
+            receiver,
+        } => {
+            let payment = Payment::Native(one_coin(&info)?);
+            let receiver = receiver.unwrap_or(info.sender.to_string());
+            let presale = Some((phase_id, proof));
+            mint(deps, info, env, payment, token_info, receiver, presale)
+        }
         ExecuteMsg::MintBundle {
             bundle_id,
             receiver,
-        } => mint_bundle(deps, info, bundle_id, receiver),
+        } => {
+            let payment = Payment::Native(one_coin(&info)?);
+            mint_bundle(deps, info, env, payment, bundle_id, receiver)
+        }
         ExecuteMsg::OpenLootbox {
             lootbox_id,
             receiver,
-        } => open_lootbox(deps, info, env, lootbox_id, receiver),
+            commitment,
+        } => {
+            let payment = Payment::Native(one_coin(&info)?);
+            open_lootbox(deps, info, env, payment, lootbox_id, receiver, commitment)
+        }
+        ExecuteMsg::Receive(wrapper) => receive_cw20(deps, env, info, wrapper),
+        ExecuteMsg::RevealLootbox {
+            commitment_id,
+            secret,
+        } => reveal_lootbox(deps, env, commitment_id, secret),
+        ExecuteMsg::ResolveExpiredLootbox { commitment_id } => {
+            resolve_expired_lootbox(deps, env, commitment_id)
+        }
+        ExecuteMsg::Craft {
+            trait_ids,
+            recipe_id,
+            commitment,
+        } => craft(deps, info, env, trait_ids, recipe_id, commitment),
+        ExecuteMsg::RevealCraft {
+            commitment_id,
+            secret,
+        } => reveal_craft(deps, env, commitment_id, secret),
+        ExecuteMsg::ResolveExpiredCraft { commitment_id } => {
+            resolve_expired_craft(deps, env, commitment_id)
+        }
+        ExecuteMsg::AddManagers { managers } => add_managers(deps, info, managers),
+        ExecuteMsg::RemoveManagers { managers } => remove_managers(deps, info, managers),
+        ExecuteMsg::MintTraits {
+            receiver,
+            token_infos,
+        } => mint_traits(deps, info, receiver, token_infos),
+        ExecuteMsg::ReceiveRandomness { job_id, randomness } => {
+            receive_randomness(deps, info, job_id, randomness)
+        }
         ExecuteMsg::UpdateConfig { new_config } => update_config(deps, info, new_config),
+        ExecuteMsg::SetPresalePhase { phase_id, phase } => {
+            set_presale_phase(deps, info, phase_id, phase)
+        }
+        ExecuteMsg::Withdraw { denom, amount, to } => withdraw(deps, info, denom, amount, to),
+        ExecuteMsg::Pause { operations } => set_paused(deps, info, operations, true),
+        ExecuteMsg::Unpause { operations } => set_paused(deps, info, operations, false),
+        ExecuteMsg::SetGuardian { guardian } => set_guardian(deps, info, guardian),
         ExecuteMsg::UpdateOwnership(action) => update_ownership(deps, env, info, action),
     }
 }
 
+//The CW20 contract calling is the payment denom and the sender of the tokens is the one minting
+pub fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let payment = Payment::Cw20(coin(wrapper.amount.u128(), info.sender.as_str()));
+    let info = MessageInfo {
+        sender: deps.api.addr_validate(&wrapper.sender)?,
+        funds: vec![],
+    };
+
+    match from_binary(&wrapper.msg)? {
+        ReceiveMsg::Mint { token_info } => {
+            let receiver = info.sender.to_string();
+            mint(deps, info, env, payment, token_info, receiver, None)
+        }
+        ReceiveMsg::MintTo {
+            token_info,
+            receiver,
+        } => mint(deps, info, env, payment, token_info, receiver, None),
+        ReceiveMsg::MintWithProof {
+            phase_id,
+            proof,
+            token_info,
+            receiver,
+        } => {
+            let receiver = receiver.unwrap_or(info.sender.to_string());
+            let presale = Some((phase_id, proof));
+            mint(deps, info, env, payment, token_info, receiver, presale)
+        }
+        ReceiveMsg::MintBundle {
+            bundle_id,
+            receiver,
+        } => mint_bundle(deps, info, env, payment, bundle_id, receiver),
+        ReceiveMsg::OpenLootbox {
+            lootbox_id,
+            receiver,
+            commitment,
+        } => open_lootbox(deps, info, env, payment, lootbox_id, receiver, commitment),
+    }
+}
+
 pub fn mint(
     deps: DepsMut,
     info: MessageInfo,
+    env: Env,
+    payment: Payment,
     token_info: Extension,
     receiver: String,
+    presale: Option<(u32, Vec<String>)>,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Mint)?;
     deps.api.addr_validate(&receiver)?;
-    let funds_sent = one_coin(&info)?;
+    let funds_sent = payment.coin().clone();
 
     //We check if the trait is mintable
     let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
-    let traits_response: TraitsResp = deps
-        .querier
-        .query_wasm_smart(mintables_collection_address, &QueryMsg::Traits {})?;
-
-    let new_trait = traits_response.traits.iter().find(|t| {
-        t.trait_type == token_info.trait_type
-            && t.trait_value == token_info.trait_value
-            && t.trait_rarity == token_info.trait_rarity
-    });
-
-    if new_trait.is_none() {
-        return Err(ContractError::InvalidTrait {});
-    }
+    let new_trait = find_mintable_trait(deps.as_ref(), &mintables_collection_address, &token_info)?
+        .ok_or(ContractError::InvalidTrait {})?;
+    assert_registered_trait(deps.as_ref(), &mintables_collection_address, &token_info)?;
 
     let config = CONFIG.load(deps.storage)?;
     let mut res = Response::new();
 
-    if funds_sent != new_trait.unwrap().mint_price {
+    //Traits listed without a price are sold at the price of their rarity
+    let listed_price = new_trait
+        .mint_price
+        .clone()
+        .or_else(|| config.rarity_price(&new_trait.trait_rarity))
+        .ok_or(ContractError::NoMintPrice {})?;
+    let mint_price = match presale {
+        Some((phase_id, proof)) => {
+            record_presale_mint(deps.storage, &info.sender, phase_id, &proof)?
+                .discounted_price(&listed_price)
+        }
+        None => listed_price,
+    };
+    if funds_sent != mint_price {
         return Err(ContractError::IncorrectMintFunds {});
     }
 
-    //If we are minting using CoolCat tokens we apply the burn ratio if there is one
-    if funds_sent.denom == NATIVE_DENOM && config.burn_ratio > 0 {
-        let amount_burnt = config.burn_ratio.bps_to_decimal() * funds_sent.amount;
-        let burn_msg = BankMsg::Burn {
-            amount: coins(amount_burnt.u128(), NATIVE_DENOM),
-        };
-        res.messages.push(SubMsg::new(burn_msg));
+    if !new_trait.is_on_sale(&env.block) {
+        return Err(ContractError::SaleNotActive {});
     }
-
-    //If we are minting using CoolCat we need to adjust the amount sent substracting the burnt amount
-    if funds_sent.denom == NATIVE_DENOM && config.burn_ratio != 100 {
-        let amount_sent = (100 - config.burn_ratio).bps_to_decimal() * funds_sent.amount;
-        let send_funds_msg = BankMsg::Send {
-            to_address: config.destination.unwrap().into_string(),
-            amount: coins(amount_sent.u128(), funds_sent.denom),
-        };
-        res.messages.push(SubMsg::new(send_funds_msg));
-    } else {
-        //Send full amount as nothing is burnt.
-        let send_funds_msg = BankMsg::Send {
-            to_address: config.destination.unwrap().into_string(),
-            amount: coins(funds_sent.amount.u128(), funds_sent.denom),
-        };
-        res.messages.push(SubMsg::new(send_funds_msg))
+    //The mint is recorded in mintables in the same transaction so the supply cannot be oversold
+    if new_trait.is_sold_out() {
+        return Err(ContractError::SoldOut {});
     }
+    record_item_mint(deps.storage, &TRAIT_MINTS, &info.sender, &new_trait)?;
+    res = res.add_message(record_mint_msg(
+        &mintables_collection_address,
+        MintableKind::Trait,
+        new_trait.id,
+    )?);
+
+    record_address_mint(deps.storage, &env.block, &config.mint_limits, &info.sender)?;
+    res = res.add_messages(pay_out(deps.storage, &config.revenue_split, &payment)?);
 
     let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
 
@@ -236,62 +343,53 @@ pub fn mint(
 pub fn mint_bundle(
     deps: DepsMut,
     info: MessageInfo,
+    env: Env,
+    payment: Payment,
     bundle_id: u32,
     receiver: Option<String>,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Bundle)?;
     let send_to = receiver.unwrap_or(info.sender.to_string());
     deps.api.addr_validate(&send_to)?;
 
-    let funds_sent = one_coin(&info)?;
+    let funds_sent = payment.coin().clone();
 
     //We check if the bundle is mintable
     let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
-    let bundle_response: TraitBundlesResp = deps
+    let bundle: TraitBundle = deps
         .querier
-        .query_wasm_smart(mintables_collection_address, &QueryMsg::TraitBundles {})?;
-
-    let bundle = bundle_response.bundles.iter().find(|b| b.id == bundle_id);
-
-    if bundle.is_none() {
-        return Err(ContractError::InvalidBundle {});
-    }
+        .query_wasm_smart(
+            &mintables_collection_address,
+            &QueryMsg::TraitBundle { id: bundle_id },
+        )
+        .map_err(|_| ContractError::InvalidBundle {})?;
 
     let config = CONFIG.load(deps.storage)?;
     let mut res = Response::new();
 
-    if funds_sent != bundle.unwrap().mint_price {
+    if funds_sent != bundle.mint_price {
         return Err(ContractError::IncorrectMintFunds {});
     }
 
-    //If we are minting using CoolCat tokens we apply the burn ratio if there is one
-    if funds_sent.denom == NATIVE_DENOM && config.burn_ratio > 0 {
-        let amount_burnt = config.burn_ratio.bps_to_decimal() * funds_sent.amount;
-        let burn_msg = BankMsg::Burn {
-            amount: coins(amount_burnt.u128(), NATIVE_DENOM),
-        };
-        res.messages.push(SubMsg::new(burn_msg));
+    if !bundle.is_on_sale(&env.block) {
+        return Err(ContractError::SaleNotActive {});
     }
-
-    //If we are minting using CoolCat we need to adjust the amount sent substracting the burnt amount
-    if funds_sent.denom == NATIVE_DENOM && config.burn_ratio != 100 {
-        let amount_sent = (100 - config.burn_ratio).bps_to_decimal() * funds_sent.amount;
-        let send_funds_msg = BankMsg::Send {
-            to_address: config.destination.unwrap().into_string(),
-            amount: coins(amount_sent.u128(), funds_sent.denom),
-        };
-        res.messages.push(SubMsg::new(send_funds_msg));
-    } else {
-        //Send full amount as nothing is burnt.
-        let send_funds_msg = BankMsg::Send {
-            to_address: config.destination.unwrap().into_string(),
-            amount: coins(funds_sent.amount.u128(), funds_sent.denom),
-        };
-        res.messages.push(SubMsg::new(send_funds_msg))
+    if bundle.is_sold_out() {
+        return Err(ContractError::SoldOut {});
     }
+    record_item_mint(deps.storage, &BUNDLE_MINTS, &info.sender, &bundle)?;
+    res = res.add_message(record_mint_msg(
+        &mintables_collection_address,
+        MintableKind::TraitBundle,
+        bundle_id,
+    )?);
+
+    record_address_mint(deps.storage, &env.block, &config.mint_limits, &info.sender)?;
+    res = res.add_messages(pay_out(deps.storage, &config.revenue_split, &payment)?);
 
     let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
 
-    for new_trait in bundle.unwrap().traits.clone() {
+    for new_trait in bundle.traits.clone() {
         let token_info = Extension {
             trait_type: new_trait.trait_type,
             trait_rarity: new_trait.trait_rarity,
@@ -325,91 +423,252 @@ pub fn open_lootbox(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
+    payment: Payment,
     lootbox_id: u32,
     receiver: Option<String>,
+    commitment: Option<String>,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Lootbox)?;
     let send_to = receiver.unwrap_or(info.sender.to_string());
-    deps.api.addr_validate(&send_to)?;
+    let receiver_addr = deps.api.addr_validate(&send_to)?;
 
-    let funds_sent = one_coin(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    let commitment = parse_commitment(&config.randomness_source, commitment)?;
+
+    let funds_sent = payment.coin().clone();
 
     //We check if the bundle is mintable
     let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
-    let lootbox_response: TraitLootboxesResp = deps
+    let lootbox: TraitLootbox = deps
         .querier
-        .query_wasm_smart(mintables_collection_address, &QueryMsg::TraitLootboxes {})?;
-
-    let lootbox = lootbox_response
-        .lootboxes
-        .iter()
-        .find(|lb| lb.id == lootbox_id);
+        .query_wasm_smart(
+            &mintables_collection_address,
+            &QueryMsg::TraitLootbox { id: lootbox_id },
+        )
+        .map_err(|_| ContractError::InvalidLootbox {})?;
 
-    if lootbox.is_none() {
-        return Err(ContractError::InvalidLootbox {});
-    }
-
-    let config = CONFIG.load(deps.storage)?;
     let mut res = Response::new();
 
-    if funds_sent != lootbox.unwrap().mint_price {
+    if funds_sent != lootbox.mint_price {
         return Err(ContractError::IncorrectMintFunds {});
     }
 
-    //If we are minting using CoolCat tokens we apply the burn ratio if there is one
-    if funds_sent.denom == NATIVE_DENOM && config.burn_ratio > 0 {
-        let amount_burnt = config.burn_ratio.bps_to_decimal() * funds_sent.amount;
-        let burn_msg = BankMsg::Burn {
-            amount: coins(amount_burnt.u128(), NATIVE_DENOM),
-        };
-        res.messages.push(SubMsg::new(burn_msg));
+    if !lootbox.is_on_sale(&env.block) {
+        return Err(ContractError::SaleNotActive {});
+    }
+    if lootbox.is_sold_out() {
+        return Err(ContractError::SoldOut {});
     }
+    record_item_mint(deps.storage, &LOOTBOX_MINTS, &info.sender, &lootbox)?;
+    res = res.add_message(record_mint_msg(
+        &mintables_collection_address,
+        MintableKind::TraitLootbox,
+        lootbox_id,
+    )?);
+
+    record_address_mint(deps.storage, &env.block, &config.mint_limits, &info.sender)?;
+
+    //Beacon jobs keep the payment until the beacon answers, so it can be refunded if it never does
+    let (proxy, held_payment) = match &config.randomness_source {
+        RandomnessSource::Internal => {
+            res = res.add_messages(pay_out(deps.storage, &config.revenue_split, &payment)?);
+            (None, None)
+        }
+        RandomnessSource::Beacon { proxy_addr } => (Some(proxy_addr.clone()), Some(payment)),
+    };
 
-    //If we are minting using CoolCat we need to adjust the amount sent substracting the burnt amount
-    if funds_sent.denom == NATIVE_DENOM && config.burn_ratio != 100 {
-        let amount_sent = (100 - config.burn_ratio).bps_to_decimal() * funds_sent.amount;
-        let send_funds_msg = BankMsg::Send {
-            to_address: config.destination.unwrap().into_string(),
-            amount: coins(amount_sent.u128(), funds_sent.denom),
-        };
-        res.messages.push(SubMsg::new(send_funds_msg));
-    } else {
-        //Send full amount as nothing is burnt.
-        let send_funds_msg = BankMsg::Send {
-            to_address: config.destination.unwrap().into_string(),
-            amount: coins(funds_sent.amount.u128(), funds_sent.denom),
-        };
-        res.messages.push(SubMsg::new(send_funds_msg))
+    //The lootbox is paid now but its content is only drawn once the secret is revealed in a later block
+    //or the beacon delivers the randomness for it
+    let commitment_id = increment_commitment_index(deps.storage)?;
+    PENDING_ENTROPY.save(deps.storage, &env.block.height)?;
+    LOOTBOX_COMMITMENTS.save(
+        deps.storage,
+        commitment_id,
+        &LootboxCommitment {
+            owner: info.sender.clone(),
+            receiver: receiver_addr,
+            lootbox,
+            commitment,
+            committed_at: env.block.height,
+            entropy: String::new(),
+            proxy: proxy.clone(),
+            payment: held_payment,
+        },
+    )?;
+
+    if let Some(proxy_addr) = proxy {
+        res = res.add_message(randomness_request_msg(proxy_addr, commitment_id)?);
     }
 
-    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
-    let mut current = random_number_1_to_x(
+    Ok(res
+        .add_attribute("action", "open_lootbox")
+        .add_attribute("lootbox_id", lootbox_id.to_string())
+        .add_attribute("commitment_id", commitment_id.to_string())
+        .add_attribute("sender", info.sender)
+        .add_attribute("receiver", send_to))
+}
+
+pub fn reveal_lootbox(
+    deps: DepsMut,
+    env: Env,
+    commitment_id: u64,
+    secret: String,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Lootbox)?;
+    let commitment = LOOTBOX_COMMITMENTS
+        .may_load(deps.storage, commitment_id)?
+        .ok_or(ContractError::CommitmentNotFound {})?;
+
+    let seed = reveal_seed(
+        deps.as_ref(),
         &env,
-        send_to.clone(),
-        lootbox_response.lootboxes.len().try_into().unwrap(),
-        100
-    );
-    let mut position = 0;
+        commitment_id,
+        &commitment.commitment,
+        commitment.committed_at,
+        &secret,
+        &commitment.entropy,
+    )?;
+    let position = drawn_position(&commitment.lootbox.possibilities, seed);
+
+    Ok(draw_lootbox(deps, commitment_id, commitment, position)?
+        .add_attribute("action", "reveal_lootbox"))
+}
+
+pub fn resolve_expired_lootbox(
+    deps: DepsMut,
+    env: Env,
+    commitment_id: u64,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Lootbox)?;
+    let commitment = LOOTBOX_COMMITMENTS
+        .may_load(deps.storage, commitment_id)?
+        .ok_or(ContractError::CommitmentNotFound {})?;
 
-    //Find which item of the lootbox we get according to possibilities
-    for possibility in lootbox.unwrap().possibilities.clone() {
-        if current <= possibility {
-            break;
-        } else {
-            position += 1;
-            current -= possibility;
+    if env.block.height <= commitment.committed_at + REVEAL_TIMEOUT {
+        return Err(ContractError::CommitmentNotExpired {});
+    }
+
+    //Beacon jobs that never get an answer are refunded, nobody could know their result
+    if let (None, Some(payment)) = (&commitment.commitment, &commitment.payment) {
+        LOOTBOX_COMMITMENTS.remove(deps.storage, commitment_id);
+        release_item_mint(
+            deps.storage,
+            &LOOTBOX_MINTS,
+            &commitment.owner,
+            commitment.lootbox.id,
+        )?;
+        release_address_mint(
+            deps.storage,
+            &env.block,
+            commitment.committed_at,
+            &commitment.owner,
+        )?;
+        let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
+
+        let mut res = Response::new().add_message(release_mint_msg(
+            &mintables_collection_address,
+            MintableKind::TraitLootbox,
+            commitment.lootbox.id,
+        )?);
+        if !payment.coin().amount.is_zero() {
+            res = res.add_message(payment.send_msg(&commitment.owner, payment.coin().amount)?);
         }
+
+        return Ok(res
+            .add_attribute("action", "refund_lootbox")
+            .add_attribute("commitment_id", commitment_id.to_string())
+            .add_attribute("owner", commitment.owner));
     }
 
+    //Lootboxes that are never revealed are forfeited, so walking away from a known result is worse than any draw
+    LOOTBOX_COMMITMENTS.remove(deps.storage, commitment_id);
+    let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
+
+    Ok(Response::new()
+        .add_message(release_mint_msg(
+            &mintables_collection_address,
+            MintableKind::TraitLootbox,
+            commitment.lootbox.id,
+        )?)
+        .add_attribute("action", "forfeit_lootbox")
+        .add_attribute("commitment_id", commitment_id.to_string())
+        .add_attribute("owner", commitment.owner))
+}
+
+pub fn receive_randomness(
+    deps: DepsMut,
+    info: MessageInfo,
+    job_id: String,
+    randomness: HexBinary,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    //Job ids are the ids of the lootbox and craft commitments waiting for beacon randomness
+    let commitment_id: u64 = job_id.parse().map_err(|_| ContractError::JobNotFound {})?;
+    let seed = format!("{}{}", randomness.to_hex(), commitment_id);
+
+    if let Some(commitment) = CRAFT_COMMITMENTS.may_load(deps.storage, commitment_id)? {
+        assert_not_paused(deps.storage, Operation::Craft)?;
+        if commitment.proxy.as_ref() != Some(&info.sender) {
+            return Err(ContractError::UnauthorizedRandomnessSource {});
+        }
+        return Ok(draw_craft(deps, commitment_id, commitment, seed)?
+            .add_attribute("action", "receive_randomness"));
+    }
+
+    assert_not_paused(deps.storage, Operation::Lootbox)?;
+    let commitment = LOOTBOX_COMMITMENTS
+        .may_load(deps.storage, commitment_id)?
+        .ok_or(ContractError::JobNotFound {})?;
+
+    //A job is answered by the proxy it was sent to, even if the randomness source changed since.
+    //Jobs stored without their proxy can only be answered by the configured one
+    let proxy = match (&commitment.proxy, &config.randomness_source) {
+        (Some(proxy), _) => Some(proxy),
+        (None, RandomnessSource::Beacon { proxy_addr }) if commitment.commitment.is_none() => {
+            Some(proxy_addr)
+        }
+        _ => None,
+    };
+    if proxy != Some(&info.sender) {
+        return Err(ContractError::UnauthorizedRandomnessSource {});
+    }
+
+    //The payment held for the job is paid out now that the lootbox is drawn
+    let payout_msgs = match &commitment.payment {
+        Some(payment) => pay_out(deps.storage, &config.revenue_split, payment)?,
+        None => vec![],
+    };
+
+    let position = drawn_position(&commitment.lootbox.possibilities, seed);
+
+    Ok(draw_lootbox(deps, commitment_id, commitment, position)?
+        .add_messages(payout_msgs)
+        .add_attribute("action", "receive_randomness"))
+}
+
+fn draw_lootbox(
+    deps: DepsMut,
+    commitment_id: u64,
+    commitment: LootboxCommitment,
+    position: usize,
+) -> Result<Response, ContractError> {
+    LOOTBOX_COMMITMENTS.remove(deps.storage, commitment_id);
+
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+    let lootbox = commitment.lootbox;
+
     let token_info = Extension {
-        trait_type: lootbox.unwrap().traits[position].clone().trait_type,
-        trait_rarity: lootbox.unwrap().traits[position].clone().trait_rarity,
-        trait_value: lootbox.unwrap().traits[position].clone().trait_value,
+        trait_type: lootbox.traits[position].clone().trait_type,
+        trait_rarity: lootbox.traits[position].clone().trait_rarity,
+        trait_value: lootbox.traits[position].clone().trait_value,
     };
 
     // Create mint msgs
     let mint_msg = cw721_trait_onchain::ExecuteMsg::<Extension, Empty>::Mint {
         token_id: increment_token_index(deps.storage)?.to_string(),
-        owner: send_to.clone(),
+        owner: commitment.receiver.to_string(),
         token_uri: None,
         extension: token_info,
     };
@@ -420,77 +679,887 @@ pub fn open_lootbox(
         funds: vec![],
     });
 
-    res = res.add_message(msg);
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("lootbox_id", lootbox.id.to_string())
+        .add_attribute("commitment_id", commitment_id.to_string())
+        .add_attribute("won_element", position.to_string())
+        .add_attribute("receiver", commitment.receiver))
+}
+
+//Only internal randomness needs the user to commit to a secret
+fn parse_commitment(
+    randomness_source: &RandomnessSource,
+    commitment: Option<String>,
+) -> Result<Option<String>, ContractError> {
+    match randomness_source {
+        RandomnessSource::Internal => {
+            let commitment = commitment
+                .ok_or(ContractError::InvalidCommitment {})?
+                .to_lowercase();
+            if commitment.len() != 64 || !commitment.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(ContractError::InvalidCommitment {});
+            }
+            Ok(Some(commitment))
+        }
+        RandomnessSource::Beacon { .. } => Ok(None),
+    }
+}
+
+//Checks the secret of a commitment and returns the seed of its draw
+fn reveal_seed(
+    deps: Deps,
+    env: &Env,
+    commitment_id: u64,
+    commitment: &Option<String>,
+    committed_at: u64,
+    secret: &str,
+    legacy_entropy: &str,
+) -> Result<String, ContractError> {
+    let commitment_hash = commitment
+        .as_ref()
+        .ok_or(ContractError::AwaitingRandomness {})?;
+
+    if env.block.height <= committed_at {
+        return Err(ContractError::RevealTooEarly {});
+    }
+
+    if env.block.height > committed_at + REVEAL_TIMEOUT {
+        return Err(ContractError::CommitmentExpired {});
+    }
+
+    if format!("{:x}", Sha256::digest(secret.as_bytes())) != *commitment_hash {
+        return Err(ContractError::InvalidSecret {});
+    }
+
+    //The secret is mixed with the entropy of the first block after the commitment one, which nobody knew
+    //when committing. It is recorded before any message of that block, so the reveal block does not matter
+    let entropy = BLOCK_ENTROPY
+        .may_load(deps.storage, committed_at)?
+        .unwrap_or_else(|| legacy_entropy.to_string());
+    Ok(format!("{}{}{}", secret, commitment_id, entropy))
+}
+
+fn randomness_request_msg(proxy_addr: Addr, commitment_id: u64) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: proxy_addr.into_string(),
+        msg: to_binary(&RandomnessProxyExecuteMsg::GetNextRandomness {
+            job_id: commitment_id.to_string(),
+        })?,
+        funds: vec![],
+    }))
+}
+
+pub fn craft(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    trait_ids: Vec<String>,
+    recipe_id: u32,
+    commitment: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Craft)?;
+
+    let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
+    let recipe: Recipe = deps
+        .querier
+        .query_wasm_smart(
+            &mintables_collection_address,
+            &QueryMsg::Recipe { id: recipe_id },
+        )
+        .map_err(|_| ContractError::InvalidRecipe {})?;
+
+    let mut unique_ids = trait_ids.clone();
+    unique_ids.sort();
+    unique_ids.dedup();
+    if unique_ids.len() != trait_ids.len() || trait_ids.len() as u32 != recipe.input_count() {
+        return Err(ContractError::RecipeMismatch {});
+    }
+
+    //Inputs asking for a specific value are filled first, so they are not left out by inputs accepting any value
+    let mut inputs = recipe.inputs.clone();
+    inputs.sort_by_key(|input| input.trait_value.is_none());
+
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+    for trait_id in trait_ids.iter() {
+        let trait_info: AllNftInfoResponse<Extension> = deps.querier.query_wasm_smart(
+            &collection_address,
+            &Cw721QueryMsg::AllNftInfo {
+                token_id: trait_id.clone(),
+                include_expired: None,
+            },
+        )?;
+        if trait_info.access.owner != info.sender {
+            return Err(ContractError::NotTraitOwner {});
+        }
+
+        let input = inputs
+            .iter_mut()
+            .find(|input| input.amount > 0 && input_matches(input, &trait_info.info.extension))
+            .ok_or(ContractError::RecipeMismatch {})?;
+        input.amount -= 1;
+    }
+
+    let (trait_type, trait_rarity) = match recipe.output {
+        RecipeOutput::Trait { id } => {
+            let output: Trait = deps
+                .querier
+                .query_wasm_smart(&mintables_collection_address, &QueryMsg::Trait { id })
+                .map_err(|_| ContractError::InvalidTrait {})?;
+            if output.is_sold_out() {
+                return Err(ContractError::SoldOut {});
+            }
+            return Ok(mint_crafted(
+                deps.storage,
+                &collection_address,
+                &mintables_collection_address,
+                trait_ids,
+                &info.sender,
+                output,
+            )?
+            .add_attribute("action", "craft")
+            .add_attribute("recipe_id", recipe_id.to_string())
+            .add_attribute("sender", info.sender));
+        }
+        RecipeOutput::Random {
+            trait_type,
+            trait_rarity,
+        } => (trait_type, trait_rarity),
+    };
+
+    let candidates = craftable_traits(
+        deps.as_ref(),
+        &mintables_collection_address,
+        trait_type.clone(),
+        trait_rarity.clone(),
+    )?;
+    if candidates.is_empty() {
+        return Err(ContractError::SoldOut {});
+    }
+
+    //A random output is only drawn once the secret is revealed in a later block or the beacon delivers
+    //the randomness for it. The inputs are held in escrow until then, so they cannot be moved away
+    let config = CONFIG.load(deps.storage)?;
+    let commitment = parse_commitment(&config.randomness_source, commitment)?;
+    let proxy = match config.randomness_source {
+        RandomnessSource::Internal => None,
+        RandomnessSource::Beacon { proxy_addr } => Some(proxy_addr),
+    };
+
+    let commitment_id = increment_commitment_index(deps.storage)?;
+    PENDING_ENTROPY.save(deps.storage, &env.block.height)?;
+    CRAFT_COMMITMENTS.save(
+        deps.storage,
+        commitment_id,
+        &CraftCommitment {
+            owner: info.sender.clone(),
+            recipe_id,
+            trait_ids: trait_ids.clone(),
+            trait_type,
+            trait_rarity,
+            commitment,
+            committed_at: env.block.height,
+            proxy: proxy.clone(),
+        },
+    )?;
+
+    let escrow_msg = cw721_trait_onchain::ExecuteMsg::<Extension, Empty>::Escrow {
+        token_ids: trait_ids.clone(),
+    };
+    let mut res = Response::new().add_message(WasmMsg::Execute {
+        contract_addr: collection_address.to_string(),
+        msg: to_binary(&escrow_msg)?,
+        funds: vec![],
+    });
+    if let Some(proxy_addr) = proxy {
+        res = res.add_message(randomness_request_msg(proxy_addr, commitment_id)?);
+    }
 
     Ok(res
-        .add_attribute("action", "open_lootbox")
-        .add_attribute("lootbox_id", lootbox_id.to_string())
-        .add_attribute("won_element", position.to_string())
-        .add_attribute("sender", info.sender)
-        .add_attribute("receiver", send_to))
+        .add_attribute("action", "craft")
+        .add_attribute("recipe_id", recipe_id.to_string())
+        .add_attribute("commitment_id", commitment_id.to_string())
+        .add_attribute("escrowed", trait_ids.join(","))
+        .add_attribute("sender", info.sender))
 }
 
-pub fn update_ownership(
+pub fn reveal_craft(
+    deps: DepsMut,
+    env: Env,
+    commitment_id: u64,
+    secret: String,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Craft)?;
+    let commitment = CRAFT_COMMITMENTS
+        .may_load(deps.storage, commitment_id)?
+        .ok_or(ContractError::CommitmentNotFound {})?;
+
+    let seed = reveal_seed(
+        deps.as_ref(),
+        &env,
+        commitment_id,
+        &commitment.commitment,
+        commitment.committed_at,
+        &secret,
+        "",
+    )?;
+
+    Ok(draw_craft(deps, commitment_id, commitment, seed)?.add_attribute("action", "reveal_craft"))
+}
+
+pub fn resolve_expired_craft(
     deps: DepsMut,
     env: Env,
+    commitment_id: u64,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Craft)?;
+    let commitment = CRAFT_COMMITMENTS
+        .may_load(deps.storage, commitment_id)?
+        .ok_or(ContractError::CommitmentNotFound {})?;
+
+    if env.block.height <= commitment.committed_at + REVEAL_TIMEOUT {
+        return Err(ContractError::CommitmentNotExpired {});
+    }
+    CRAFT_COMMITMENTS.remove(deps.storage, commitment_id);
+
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+
+    //Beacon jobs that never get an answer give the inputs back, nobody could know their result.
+    //Crafts that are never revealed are forfeited, so walking away from a known result is worse than any draw
+    let (action, msg) = match commitment.commitment {
+        None => (
+            "refund_craft",
+            cw721_trait_onchain::ExecuteMsg::<Extension, Empty>::ReleaseEscrow {
+                token_ids: commitment.trait_ids.clone(),
+                recipient: Some(commitment.owner.to_string()),
+            },
+        ),
+        Some(_) => (
+            "forfeit_craft",
+            cw721_trait_onchain::ExecuteMsg::<Extension, Empty>::BurnMultiple {
+                token_ids: commitment.trait_ids.clone(),
+            },
+        ),
+    };
+
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: collection_address.to_string(),
+            msg: to_binary(&msg)?,
+            funds: vec![],
+        })
+        .add_attribute("action", action)
+        .add_attribute("commitment_id", commitment_id.to_string())
+        .add_attribute("traits", commitment.trait_ids.join(","))
+        .add_attribute("owner", commitment.owner))
+}
+
+fn draw_craft(
+    deps: DepsMut,
+    commitment_id: u64,
+    commitment: CraftCommitment,
+    seed: String,
+) -> Result<Response, ContractError> {
+    CRAFT_COMMITMENTS.remove(deps.storage, commitment_id);
+
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+    let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
+    let mut candidates = craftable_traits(
+        deps.as_ref(),
+        &mintables_collection_address,
+        commitment.trait_type,
+        commitment.trait_rarity,
+    )?;
+
+    //The candidates can sell out while the craft waits for its randomness, the inputs are then given back
+    if candidates.is_empty() {
+        let release_msg = cw721_trait_onchain::ExecuteMsg::<Extension, Empty>::ReleaseEscrow {
+            token_ids: commitment.trait_ids.clone(),
+            recipient: Some(commitment.owner.to_string()),
+        };
+        return Ok(Response::new()
+            .add_message(WasmMsg::Execute {
+                contract_addr: collection_address.to_string(),
+                msg: to_binary(&release_msg)?,
+                funds: vec![],
+            })
+            .add_attribute("recipe_id", commitment.recipe_id.to_string())
+            .add_attribute("commitment_id", commitment_id.to_string())
+            .add_attribute("released", commitment.trait_ids.join(","))
+            .add_attribute("owner", commitment.owner));
+    }
+
+    let position = random_number_1_to_x(seed, candidates.len() as u32) - 1;
+    let output = candidates.swap_remove(position as usize);
+
+    Ok(mint_crafted(
+        deps.storage,
+        &collection_address,
+        &mintables_collection_address,
+        commitment.trait_ids,
+        &commitment.owner,
+        output,
+    )?
+    .add_attribute("recipe_id", commitment.recipe_id.to_string())
+    .add_attribute("commitment_id", commitment_id.to_string())
+    .add_attribute("owner", commitment.owner))
+}
+
+//Burns the inputs of a craft and mints its output to the owner
+fn mint_crafted(
+    storage: &mut dyn Storage,
+    collection_address: &Addr,
+    mintables_collection_address: &Addr,
+    trait_ids: Vec<String>,
+    owner: &Addr,
+    output: Trait,
+) -> Result<Response, ContractError> {
+    let burn_msg = cw721_trait_onchain::ExecuteMsg::<Extension, Empty>::BurnMultiple {
+        token_ids: trait_ids.clone(),
+    };
+    let token_id = increment_token_index(storage)?.to_string();
+    let mint_msg = cw721_trait_onchain::ExecuteMsg::<Extension, Empty>::Mint {
+        token_id: token_id.clone(),
+        owner: owner.to_string(),
+        token_uri: None,
+        extension: Extension {
+            trait_type: output.trait_type,
+            trait_value: output.trait_value,
+            trait_rarity: output.trait_rarity,
+        },
+    };
+
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: collection_address.to_string(),
+            msg: to_binary(&burn_msg)?,
+            funds: vec![],
+        })
+        .add_message(WasmMsg::Execute {
+            contract_addr: collection_address.to_string(),
+            msg: to_binary(&mint_msg)?,
+            funds: vec![],
+        })
+        .add_message(record_mint_msg(
+            mintables_collection_address,
+            MintableKind::Trait,
+            output.id,
+        )?)
+        .add_attribute("burnt", trait_ids.join(","))
+        .add_attribute("token_id", token_id))
+}
+
+fn input_matches(input: &RecipeInput, token_info: &Extension) -> bool {
+    input.trait_type == token_info.trait_type
+        && input.trait_rarity == token_info.trait_rarity
+        && input
+            .trait_value
+            .iter()
+            .all(|value| *value == token_info.trait_value)
+}
+
+//Catalog traits of the type and rarity that are not sold out, the catalog is paginated
+fn craftable_traits(
+    deps: Deps,
+    mintables_collection_address: &Addr,
+    trait_type: String,
+    trait_rarity: String,
+) -> StdResult<Vec<Trait>> {
+    let mut craftable = vec![];
+    let mut start_after = None;
+    loop {
+        let traits_response: TraitsResp = deps.querier.query_wasm_smart(
+            mintables_collection_address,
+            &QueryMsg::TraitsBy {
+                trait_type: Some(trait_type.clone()),
+                trait_value: None,
+                rarity: Some(trait_rarity.clone()),
+                denom: None,
+                start_after,
+                limit: Some(CATALOG_PAGE_LIMIT),
+            },
+        )?;
+
+        match traits_response.traits.last() {
+            Some(last) => start_after = Some(last.id),
+            None => return Ok(craftable),
+        }
+        craftable.extend(
+            traits_response
+                .traits
+                .into_iter()
+                .filter(|t| !t.is_sold_out()),
+        );
+    }
+}
+
+pub fn add_managers(
+    deps: DepsMut,
     info: MessageInfo,
-    action: cw_ownable::Action,
+    new_managers: Vec<String>,
 ) -> Result<Response, ContractError> {
-    let ownership = cw_ownable::update_ownership(deps, &env.block, &info.sender, action)?;
-    Ok(Response::new().add_attributes(ownership.into_attributes()))
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let mut managers = MANAGERS.may_load(deps.storage)?.unwrap_or_default();
+    for new_manager in new_managers {
+        let new_manager = deps.api.addr_validate(&new_manager)?;
+        if !managers.contains(&new_manager) {
+            managers.push(new_manager);
+        }
+    }
+    MANAGERS.save(deps.storage, &managers)?;
+
+    Ok(Response::new()
+        .add_message(update_escrow_agents_msg(deps.storage, &managers, &[])?)
+        .add_attribute("action", "add_managers"))
 }
 
-pub fn update_config(
+pub fn remove_managers(
     deps: DepsMut,
     info: MessageInfo,
-    new_config: UpdateTraitManagerParamsMsg,
+    manager_addresses: Vec<String>,
 ) -> Result<Response, ContractError> {
-    //Only owner can update config
     cw_ownable::assert_owner(deps.storage, &info.sender)?;
-    //If mint price is not fully burned then there must be a valid destination address to send funds to
-    if new_config.burn_ratio != 100 && new_config.destination.is_none() {
-        return Err(ContractError::NoMintDestination {});
+
+    let mut managers = MANAGERS.may_load(deps.storage)?.unwrap_or_default();
+    managers.retain(|m| !manager_addresses.contains(&m.to_string()));
+    MANAGERS.save(deps.storage, &managers)?;
+
+    Ok(Response::new()
+        .add_message(update_escrow_agents_msg(
+            deps.storage,
+            &[],
+            &manager_addresses,
+        )?)
+        .add_attribute("action", "remove_managers"))
+}
+
+//Managers equip traits on characters, so the trait collection lets them hold traits in escrow
+fn update_escrow_agents_msg(
+    storage: &dyn Storage,
+    add: &[Addr],
+    remove: &[String],
+) -> StdResult<CosmosMsg> {
+    let collection_address = COLLECTION_ADDRESS.load(storage)?;
+    let update_msg = cw721_trait_onchain::ExecuteMsg::<Extension, Empty>::UpdateEscrowAgents {
+        add: add.iter().map(|a| a.to_string()).collect(),
+        remove: remove.to_vec(),
+    };
+
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: collection_address.to_string(),
+        msg: to_binary(&update_msg)?,
+        funds: vec![],
+    }))
+}
+
+//Token ids are only given out by this manager, so other managers mint traits through it
+pub fn mint_traits(
+    deps: DepsMut,
+    info: MessageInfo,
+    receiver: String,
+    token_infos: Vec<Extension>,
+) -> Result<Response, ContractError> {
+    assert_not_paused(deps.storage, Operation::Mint)?;
+    let managers = MANAGERS.may_load(deps.storage)?.unwrap_or_default();
+    if !managers.contains(&info.sender) {
+        return Err(ContractError::UnauthorizedManager {});
     }
+    deps.api.addr_validate(&receiver)?;
 
-    if new_config.burn_ratio != 100 {
-        deps.api
-            .addr_validate(&new_config.destination.clone().unwrap().into_string())?;
+    let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
+    let collection_address = COLLECTION_ADDRESS.load(deps.storage)?;
+    let mut res = Response::new();
+    let mut token_ids = vec![];
+    for token_info in token_infos {
+        assert_registered_trait(deps.as_ref(), &mintables_collection_address, &token_info)?;
+        let token_id = increment_token_index(deps.storage)?.to_string();
+        let mint_msg = cw721_trait_onchain::ExecuteMsg::<Extension, Empty>::Mint {
+            token_id: token_id.clone(),
+            owner: receiver.clone(),
+            token_uri: None,
+            extension: token_info,
+        };
+        res = res.add_message(WasmMsg::Execute {
+            contract_addr: collection_address.to_string(),
+            msg: to_binary(&mint_msg)?,
+            funds: vec![],
+        });
+        token_ids.push(token_id);
     }
 
-    let range = 0..100;
-    if !range.contains(&new_config.burn_ratio) {
-        return Err(ContractError::InvalidBurnRatio {});
+    Ok(res
+        .add_attribute("action", "mint_traits")
+        .add_attribute("manager", info.sender)
+        .add_attribute("receiver", receiver)
+        .add_attribute("token_ids", token_ids.join(",")))
+}
+
+//Counts a purchase against the caps of the buyer across the manager and within the block
+fn record_address_mint(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    limits: &MintLimits,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let minted = ADDRESS_MINTS.may_load(storage, sender)?.unwrap_or_default();
+    if !limits.address_allows(minted) {
+        return Err(ContractError::MintLimitReached {});
     }
+    ADDRESS_MINTS.save(storage, sender, &(minted + 1))?;
 
-    //If we are selling anything using a non native denom, we need a destination address as we will not burn those non native tokens.
-    if new_config
-        .mint_prices
-        .iter()
-        .any(|coin| coin.denom != NATIVE_DENOM)
-        && new_config.destination.is_none()
-    {
-        return Err(ContractError::NoMintDestination {});
+    if limits.per_block.is_some() {
+        let in_block = block_mints(BLOCK_MINTS.may_load(storage, sender)?, block.height);
+        if !limits.block_allows(in_block) {
+            return Err(ContractError::BlockMintLimitReached {});
+        }
+        BLOCK_MINTS.save(storage, sender, &(block.height, in_block + 1))?;
+    }
+
+    if limits.block_ceiling.is_some() {
+        let block_total = block_mints(BLOCK_TOTAL_MINTS.may_load(storage)?, block.height);
+        if !limits.ceiling_allows(block_total) {
+            return Err(ContractError::BlockCeilingReached {});
+        }
+        BLOCK_TOTAL_MINTS.save(storage, &(block.height, block_total + 1))?;
+    }
+
+    Ok(())
+}
+
+//Counts a purchase against the per address cap of the catalog entry
+fn record_item_mint(
+    storage: &mut dyn Storage,
+    item_mints: &Map<(&Addr, u32), u32>,
+    sender: &Addr,
+    item: &impl Supply,
+) -> Result<(), ContractError> {
+    let minted = item_mints
+        .may_load(storage, (sender, item.id()))?
+        .unwrap_or_default();
+    if !item.address_allows(minted) {
+        return Err(ContractError::MintLimitReached {});
+    }
+    item_mints.save(storage, (sender, item.id()), &(minted + 1))?;
+    Ok(())
+}
+
+//Takes back a purchase that was refunded from the caps of the buyer, the block caps only if it is still the purchase block
+fn release_address_mint(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    purchased_at: u64,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let minted = ADDRESS_MINTS.may_load(storage, sender)?.unwrap_or_default();
+    ADDRESS_MINTS.save(storage, sender, &minted.saturating_sub(1))?;
+
+    if block.height == purchased_at {
+        if let Some((height, in_block)) = BLOCK_MINTS.may_load(storage, sender)? {
+            if height == purchased_at {
+                BLOCK_MINTS.save(storage, sender, &(height, in_block.saturating_sub(1)))?;
+            }
+        }
+        if let Some((height, block_total)) = BLOCK_TOTAL_MINTS.may_load(storage)? {
+            if height == purchased_at {
+                BLOCK_TOTAL_MINTS.save(storage, &(height, block_total.saturating_sub(1)))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+//Takes back a refunded purchase from the per address cap of the catalog entry
+fn release_item_mint(
+    storage: &mut dyn Storage,
+    item_mints: &Map<(&Addr, u32), u32>,
+    sender: &Addr,
+    id: u32,
+) -> Result<(), ContractError> {
+    let minted = item_mints
+        .may_load(storage, (sender, id))?
+        .unwrap_or_default();
+    item_mints.save(storage, (sender, id), &minted.saturating_sub(1))?;
+    Ok(())
+}
+
+//Checks the sender's allowlist proof and counts the mint against its limit in the phase
+fn record_presale_mint(
+    storage: &mut dyn Storage,
+    sender: &Addr,
+    phase_id: u32,
+    proof: &[String],
+) -> Result<PresalePhase, ContractError> {
+    let phase = PRESALE_PHASES
+        .may_load(storage, phase_id)?
+        .ok_or(ContractError::PresalePhaseNotFound {})?;
+
+    if !phase.allows(sender.as_str(), proof)? {
+        return Err(ContractError::NotAllowlisted {});
+    }
+
+    let minted = PRESALE_MINTS
+        .may_load(storage, (phase_id, sender))?
+        .unwrap_or_default();
+    if phase.remaining(minted) == 0 {
+        return Err(ContractError::PresaleLimitReached {});
     }
+    PRESALE_MINTS.save(storage, (phase_id, sender), &(minted + 1))?;
+
+    Ok(phase)
+}
+
+fn find_mintable_trait(
+    deps: Deps,
+    mintables_collection_address: &Addr,
+    token_info: &Extension,
+) -> StdResult<Option<Trait>> {
+    let traits_response: TraitsResp = deps.querier.query_wasm_smart(
+        mintables_collection_address,
+        &QueryMsg::TraitsBy {
+            trait_type: Some(token_info.trait_type.clone()),
+            trait_value: Some(token_info.trait_value.clone()),
+            rarity: Some(token_info.trait_rarity.clone()),
+            denom: None,
+            start_after: None,
+            limit: Some(1),
+        },
+    )?;
+
+    Ok(traits_response.traits.into_iter().next())
+}
+
+//The type and value must still be listed in the trait registry, catalog entries may outlive them
+fn assert_registered_trait(
+    deps: Deps,
+    mintables_collection_address: &Addr,
+    token_info: &Extension,
+) -> Result<(), ContractError> {
+    deps.querier
+        .query_wasm_smart::<TraitValueInfo>(
+            mintables_collection_address,
+            &QueryMsg::TraitValue {
+                trait_type: token_info.trait_type.clone(),
+                trait_value: token_info.trait_value.clone(),
+            },
+        )
+        .map_err(|_| ContractError::InvalidTrait {})?;
+
+    Ok(())
+}
+
+fn record_mint_msg(
+    mintables_collection_address: &Addr,
+    kind: MintableKind,
+    id: u32,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: mintables_collection_address.to_string(),
+        msg: to_binary(&MintablesExecuteMsg::RecordMint { kind, id })?,
+        funds: vec![],
+    }))
+}
+
+fn release_mint_msg(
+    mintables_collection_address: &Addr,
+    kind: MintableKind,
+    id: u32,
+) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: mintables_collection_address.to_string(),
+        msg: to_binary(&MintablesExecuteMsg::ReleaseMint { kind, id })?,
+        funds: vec![],
+    }))
+}
+
+fn validate_revenue_split(api: &dyn Api, split: &RevenueSplit) -> Result<(), ContractError> {
+    if !split.is_valid(api)? {
+        return Err(ContractError::InvalidRevenueSplit {});
+    }
+    Ok(())
+}
+
+//Records the payment in the revenue totals of its denom and pays out what is not accrued
+fn pay_out(
+    storage: &mut dyn Storage,
+    split: &RevenueSplit,
+    payment: &Payment,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let denom = &payment.coin().denom;
+    let mut totals = REVENUE
+        .may_load(storage, denom)?
+        .unwrap_or_else(|| RevenueTotals::new(payment));
+    let msgs = split.pay_out(&mut totals, payment)?;
+    REVENUE.save(storage, denom, &totals)?;
+
+    Ok(msgs)
+}
+
+pub fn update_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    action: cw_ownable::Action,
+) -> Result<Response, ContractError> {
+    let ownership = cw_ownable::update_ownership(deps, &env.block, &info.sender, action)?;
+    Ok(Response::new().add_attributes(ownership.into_attributes()))
+}
+
+pub fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_config: UpdateTraitManagerParamsMsg,
+) -> Result<Response, ContractError> {
+    //Only owner can update config
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+    validate_revenue_split(deps.api, &new_config.revenue_split)?;
 
     //The mint prices and rarities arrays must be same length, 1-to-1 price/rarity
     if new_config.mint_prices.len() != new_config.rarities.len() {
         return Err(ContractError::NotSameLength {});
     }
 
+    //The randomness beacon proxy must be a valid address as it is the only one allowed to deliver randomness
+    if let RandomnessSource::Beacon { proxy_addr } = &new_config.randomness_source {
+        deps.api.addr_validate(proxy_addr.as_str())?;
+    }
+
     let mut config = CONFIG.load(deps.storage)?;
-    config.burn_ratio = new_config.burn_ratio;
-    config.destination = new_config.destination;
+    config.mint_prices = new_config.mint_prices;
+    config.rarities = new_config.rarities;
+    config.revenue_split = new_config.revenue_split;
+    config.mint_limits = new_config.mint_limits;
+    config.randomness_source = new_config.randomness_source;
 
     CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
+pub fn set_presale_phase(
+    deps: DepsMut,
+    info: MessageInfo,
+    phase_id: u32,
+    phase: Option<PresalePhase>,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    match phase {
+        Some(phase) => {
+            parse_hash(&phase.merkle_root)?;
+            if phase.discount_percent > 100 {
+                return Err(ContractError::InvalidDiscount {});
+            }
+            PRESALE_PHASES.save(deps.storage, phase_id, &phase)?;
+        }
+        None => PRESALE_PHASES.remove(deps.storage, phase_id),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_presale_phase")
+        .add_attribute("phase_id", phase_id.to_string()))
+}
+
+pub fn withdraw(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    amount: Uint128,
+    to: String,
+) -> Result<Response, ContractError> {
+    //Accrued funds belong to every recipient of the split, so only the owner sends them out
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    let to = deps.api.addr_validate(&to)?;
+    let mut totals = REVENUE
+        .may_load(deps.storage, &denom)?
+        .ok_or(ContractError::InvalidWithdrawAmount {})?;
+    let msg = totals
+        .withdraw(&denom, amount, &to)?
+        .ok_or(ContractError::InvalidWithdrawAmount {})?;
+    REVENUE.save(deps.storage, &denom, &totals)?;
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "withdraw")
+        .add_attribute("denom", denom)
+        .add_attribute("amount", amount)
+        .add_attribute("to", to))
+}
+
+pub fn set_paused(
+    deps: DepsMut,
+    info: MessageInfo,
+    operations: Option<Vec<Operation>>,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    if GUARDIAN.may_load(deps.storage)?.as_ref() != Some(&info.sender) {
+        cw_ownable::assert_owner(deps.storage, &info.sender)?;
+    }
+
+    let operations = operations.unwrap_or_else(Operation::all);
+    let paused_operations = Operation::update_paused(
+        PAUSED.may_load(deps.storage)?.unwrap_or_default(),
+        &operations,
+        paused,
+    );
+    PAUSED.save(deps.storage, &paused_operations)?;
+
+    Ok(Response::new()
+        .add_attribute("action", if paused { "pause" } else { "unpause" })
+        .add_attribute("sender", info.sender))
+}
+
+pub fn set_guardian(
+    deps: DepsMut,
+    info: MessageInfo,
+    guardian: Option<String>,
+) -> Result<Response, ContractError> {
+    cw_ownable::assert_owner(deps.storage, &info.sender)?;
+
+    match &guardian {
+        Some(guardian) => GUARDIAN.save(deps.storage, &deps.api.addr_validate(guardian)?)?,
+        None => GUARDIAN.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "set_guardian")
+        .add_attribute("guardian", guardian.unwrap_or_default()))
+}
+
+fn assert_not_paused(storage: &dyn Storage, operation: Operation) -> Result<(), ContractError> {
+    if PAUSED
+        .may_load(storage)?
+        .unwrap_or_default()
+        .contains(&operation)
+    {
+        return Err(ContractError::OperationPaused {});
+    }
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: TraitManagerQueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: TraitManagerQueryMsg) -> StdResult<Binary> {
     match msg {
         TraitManagerQueryMsg::Config {} => to_binary(&query_config(deps)?),
         TraitManagerQueryMsg::AllowedCollectionCodeId {} => to_binary(&query_codeid(deps)?),
+        TraitManagerQueryMsg::LootboxCommitment { commitment_id } => {
+            to_binary(&query_lootbox_commitment(deps, commitment_id)?)
+        }
+        TraitManagerQueryMsg::PresalePhase { phase_id } => {
+            to_binary(&PRESALE_PHASES.load(deps.storage, phase_id)?)
+        }
+        TraitManagerQueryMsg::PresaleMints { phase_id, address } => {
+            to_binary(&query_presale_mints(deps, phase_id, address)?)
+        }
+        TraitManagerQueryMsg::Revenue { denom } => to_binary(&query_revenue(deps, denom)?),
+        TraitManagerQueryMsg::AllRevenue {} => to_binary(&query_all_revenue(deps)?),
+        TraitManagerQueryMsg::MintAllowance { address, item } => {
+            to_binary(&query_mint_allowance(deps, env, address, item)?)
+        }
+        TraitManagerQueryMsg::PauseStatus {} => to_binary(&query_pause_status(deps)?),
+        TraitManagerQueryMsg::Managers {} => to_binary(&query_managers(deps)?),
     }
 }
 
@@ -511,6 +1580,189 @@ fn query_codeid(deps: Deps) -> StdResult<AllowedCollectionCodeIdResponse> {
     })
 }
 
+fn query_presale_mints(
+    deps: Deps,
+    phase_id: u32,
+    address: String,
+) -> StdResult<PresaleMintsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let phase = PRESALE_PHASES.load(deps.storage, phase_id)?;
+    let minted = PRESALE_MINTS
+        .may_load(deps.storage, (phase_id, &address))?
+        .unwrap_or_default();
+
+    Ok(PresaleMintsResponse {
+        minted,
+        remaining: phase.remaining(minted),
+    })
+}
+
+fn query_revenue(deps: Deps, denom: String) -> StdResult<RevenueResponse> {
+    let totals = REVENUE.may_load(deps.storage, &denom)?.unwrap_or_default();
+    Ok(totals.response(denom))
+}
+
+fn query_all_revenue(deps: Deps) -> StdResult<Vec<RevenueResponse>> {
+    REVENUE
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|entry| entry.map(|(denom, totals)| totals.response(denom)))
+        .collect()
+}
+
+fn query_mint_allowance(
+    deps: Deps,
+    env: Env,
+    address: String,
+    item: Option<SaleItem>,
+) -> StdResult<MintAllowanceResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let limits = CONFIG.load(deps.storage)?.mint_limits;
+
+    let minted = ADDRESS_MINTS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let in_block = block_mints(
+        BLOCK_MINTS.may_load(deps.storage, &address)?,
+        env.block.height,
+    );
+    let block_total = block_mints(BLOCK_TOTAL_MINTS.may_load(deps.storage)?, env.block.height);
+    let mut remaining: Vec<u32> = limits
+        .remaining(minted, in_block, block_total)
+        .into_iter()
+        .collect();
+
+    let mut item_minted = 0;
+    if let Some(item) = item {
+        let mintables_collection_address = MINTABLE_COLLECTION_ADDRESS.load(deps.storage)?;
+        let querier = deps.querier;
+        let (item_mints, id, limit) = match item {
+            SaleItem::Single { id } => {
+                let entry: Trait = querier
+                    .query_wasm_smart(&mintables_collection_address, &QueryMsg::Trait { id })?;
+                (&TRAIT_MINTS, id, entry.per_address_limit)
+            }
+            SaleItem::Bundle { id } => {
+                let entry: TraitBundle = querier.query_wasm_smart(
+                    &mintables_collection_address,
+                    &QueryMsg::TraitBundle { id },
+                )?;
+                (&BUNDLE_MINTS, id, entry.per_address_limit)
+            }
+            SaleItem::Lootbox { id } => {
+                let entry: TraitLootbox = querier.query_wasm_smart(
+                    &mintables_collection_address,
+                    &QueryMsg::TraitLootbox { id },
+                )?;
+                (&LOOTBOX_MINTS, id, entry.per_address_limit)
+            }
+        };
+        item_minted = item_mints
+            .may_load(deps.storage, (&address, id))?
+            .unwrap_or_default();
+        if let Some(limit) = limit {
+            remaining.push(limit.saturating_sub(item_minted));
+        }
+    }
+
+    Ok(MintAllowanceResponse {
+        minted,
+        item_minted,
+        remaining: remaining.into_iter().min(),
+    })
+}
+
+fn query_pause_status(deps: Deps) -> StdResult<PauseStatusResponse> {
+    Ok(PauseStatusResponse {
+        paused: PAUSED.may_load(deps.storage)?.unwrap_or_default(),
+        guardian: GUARDIAN
+            .may_load(deps.storage)?
+            .map(|guardian| guardian.into_string()),
+    })
+}
+
+fn query_managers(deps: Deps) -> StdResult<Vec<String>> {
+    Ok(MANAGERS
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|manager| manager.into_string())
+        .collect())
+}
+
+fn query_lootbox_commitment(
+    deps: Deps,
+    commitment_id: u64,
+) -> StdResult<LootboxCommitmentResponse> {
+    let commitment = LOOTBOX_COMMITMENTS.load(deps.storage, commitment_id)?;
+    Ok(LootboxCommitmentResponse {
+        owner: commitment.owner.to_string(),
+        receiver: commitment.receiver.to_string(),
+        lootbox_id: commitment.lootbox.id,
+        commitment: commitment.commitment,
+        committed_at: commitment.committed_at,
+        expires_at: commitment.committed_at + REVEAL_TIMEOUT,
+    })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let previous = ensure_from_older_version(deps.storage)?;
+    if previous < Version::new(0, 2, 0) {
+        migrate_legacy_config(deps.storage)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", previous.to_string())
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+fn ensure_from_older_version(storage: &dyn Storage) -> Result<Version, ContractError> {
+    let stored = get_contract_version(storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::WrongMigrateContract(stored.contract));
+    }
+    let previous: Version = stored.version.parse()?;
+    if previous > CONTRACT_VERSION.parse()? {
+        return Err(ContractError::WrongMigrateVersion(stored.version));
+    }
+    Ok(previous)
+}
+
+//0.1.0 burnt burn_ratio percent of the native payments and sent the rest to the destination
+fn migrate_legacy_config(storage: &mut dyn Storage) -> Result<(), ContractError> {
+    let legacy = LEGACY_CONFIG.load(storage)?;
+    let dust_recipient = match legacy.destination.clone() {
+        Some(destination) => destination,
+        None => cw_ownable::get_ownership(storage)?
+            .owner
+            .ok_or(ContractError::InvalidRevenueSplit {})?,
+    };
+    let revenue_split = RevenueSplit {
+        recipients: legacy
+            .destination
+            .map(|destination| vec![(destination, 100u64.saturating_sub(legacy.burn_ratio))])
+            .unwrap_or_default(),
+        burn_weight: legacy.burn_ratio,
+        dust_recipient,
+        accrue: false,
+    };
+
+    let config = Config {
+        collection_code_id: legacy.collection_code_id,
+        mint_prices: vec![],
+        rarities: vec![],
+        revenue_split,
+        mint_limits: MintLimits::default(),
+        randomness_source: RandomnessSource::Internal,
+        extension: legacy.extension,
+    };
+    CONFIG.save(storage, &config)?;
+    Ok(())
+}
+
 // Reply callback triggered from cw721 trait-onchain collection contract instantiation in instantiate()
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
@@ -530,47 +1782,3 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
         Err(_) => Err(ContractError::InstantiateError {}),
     }
 }
-
-//We get around using random libraries by importing the things we need from
-//https://docs.rs/rand/0.8.1/i686-unknown-linux-gnu/src/rand/rngs/xoshiro128plusplus.rs.html
-
-fn random_number_1_to_x(env: &Env, sender: String, array_len: u32, x: u32) -> u32 {
-    let sha256 = Sha256::digest(
-        format!("{}{}{}{}", sender, env.block.time.nanos(), env.block.height, array_len).into_bytes(),
-    );
-    // Cut first 16 bytes from 32 byte value
-    let randomness: [u8; 16] = sha256.to_vec()[0..16].try_into().unwrap();
-    let mut state = [0; 4];
-    read_u32_into(&randomness, &mut state);
-    let rng = get_u32(&mut state);
-    let a_number = rng.checked_rem_euclid(x).unwrap() + 1;
-
-    a_number
-}
-
-pub fn read_u32_into(src: &[u8], dst: &mut [u32]) {
-    assert!(src.len() >= 4 * dst.len());
-    for (out, chunk) in dst.iter_mut().zip(src.chunks_exact(4)) {
-        *out = u32::from_le_bytes(chunk.try_into().unwrap());
-    }
-}
-
-fn get_u32(dst: &mut [u32]) -> u32 {
-    let result_starstar = dst[0]
-        .wrapping_add(dst[3])
-        .rotate_left(7)
-        .wrapping_add(dst[0]);
-
-    let t = dst[1] << 9;
-
-    dst[2] ^= dst[0];
-    dst[3] ^= dst[1];
-    dst[1] ^= dst[2];
-    dst[0] ^= dst[3];
-
-    dst[2] ^= t;
-
-    dst[3] = dst[3].rotate_left(11);
-
-    result_starstar
-}
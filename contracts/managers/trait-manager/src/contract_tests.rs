@@ -0,0 +1,2224 @@
+#![cfg(test)]
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{
+    coin, coins, to_binary, Addr, Binary, Deps, DepsMut, Empty, Env, Event, HexBinary, MessageInfo,
+    Response, StdResult, WasmMsg,
+};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw721::{NftInfoResponse, TokensResponse};
+use cw721_base::msg::{CollectionInfo, RoyaltyInfoResponse};
+use cw721_trait_onchain::msg::{EscrowResponse, Metadata};
+use cw_multi_test::{
+    next_block, App, AppBuilder, AppResponse, Contract, ContractWrapper, Executor,
+};
+use cw_storage_plus::Item;
+use cw_utils::Scheduled;
+use mintables::msg::{MintableKind, SupplyResp};
+use mintables::state::{
+    Recipe, RecipeInput, RecipeOutput, Trait, TraitLootbox, TraitTypeInfo, TraitValueInfo,
+};
+use sha2::{Digest, Sha256};
+use utils::{
+    merkle::leaf_hash,
+    msg::{
+        CollectionParams, CreateTraitManagerMsg, TraitManagerParams, UpdateTraitManagerParamsMsg,
+    },
+    query::{
+        LootboxCommitmentResponse, MintAllowanceResponse, PauseStatusResponse,
+        PresaleMintsResponse, RevenueResponse, SaleItem, TraitManagerConfigResponse,
+        TraitManagerQueryMsg,
+    },
+    MintLimits, Operation, PresalePhase, RandomnessSource, RevenueSplit, NATIVE_DENOM,
+};
+
+use crate::contract::{migrate, REVEAL_TIMEOUT};
+use crate::msg::{ExecuteMsg, MigrateMsg, ReceiveMsg};
+use crate::state::{LegacyConfig, CONFIG, LEGACY_CONFIG};
+use crate::ContractError;
+
+const OWNER: &str = "owner";
+const USER: &str = "user";
+const TREASURY: &str = "treasury";
+
+//Mock of a randomness beacon proxy, it keeps the requested jobs until randomness is published
+#[cw_serde]
+enum MockProxyExecuteMsg {
+    GetNextRandomness { job_id: String },
+    Publish { randomness: HexBinary },
+}
+
+const MOCK_PROXY_JOBS: Item<Vec<(Addr, String)>> = Item::new("jobs");
+
+fn mock_proxy_execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: MockProxyExecuteMsg,
+) -> StdResult<Response> {
+    let mut jobs = MOCK_PROXY_JOBS.may_load(deps.storage)?.unwrap_or_default();
+    match msg {
+        MockProxyExecuteMsg::GetNextRandomness { job_id } => {
+            jobs.push((info.sender, job_id));
+            MOCK_PROXY_JOBS.save(deps.storage, &jobs)?;
+            Ok(Response::new())
+        }
+        MockProxyExecuteMsg::Publish { randomness } => {
+            MOCK_PROXY_JOBS.save(deps.storage, &vec![])?;
+            let mut res = Response::new();
+            for (requester, job_id) in jobs {
+                res = res.add_message(WasmMsg::Execute {
+                    contract_addr: requester.into_string(),
+                    msg: to_binary(&ExecuteMsg::ReceiveRandomness {
+                        job_id,
+                        randomness: randomness.clone(),
+                    })?,
+                    funds: vec![],
+                });
+            }
+            Ok(res)
+        }
+    }
+}
+
+fn mock_proxy_instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn mock_proxy_query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+    to_binary(&Empty {})
+}
+
+fn mock_proxy() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        mock_proxy_execute,
+        mock_proxy_instantiate,
+        mock_proxy_query,
+    ))
+}
+
+fn mintables() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        mintables::execute,
+        mintables::instantiate,
+        mintables::query,
+    ))
+}
+
+fn trait_collection() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        cw721_trait_onchain::entry::execute,
+        cw721_trait_onchain::entry::instantiate,
+        cw721_trait_onchain::entry::query,
+    ))
+}
+
+fn cw20_token() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        cw20_base::contract::execute,
+        cw20_base::contract::instantiate,
+        cw20_base::contract::query,
+    ))
+}
+
+fn trait_manager() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(
+            crate::contract::execute,
+            crate::contract::instantiate,
+            crate::contract::query,
+        )
+        .with_reply(crate::contract::reply),
+    )
+}
+
+fn test_trait() -> Trait {
+    Trait {
+        id: 1,
+        trait_type: "eyes".to_string(),
+        trait_value: "laser".to_string(),
+        trait_rarity: "rare".to_string(),
+        mint_price: Some(coin(100, NATIVE_DENOM)),
+        max_supply: None,
+        minted: 0,
+        per_address_limit: None,
+        sale_start: None,
+        sale_end: None,
+    }
+}
+
+fn treasury_split() -> RevenueSplit {
+    RevenueSplit {
+        recipients: vec![(Addr::unchecked(TREASURY), 1)],
+        burn_weight: 0,
+        dust_recipient: Addr::unchecked(TREASURY),
+        accrue: false,
+    }
+}
+
+//Registers every trait type and value minted by the tests
+fn register_traits(app: &mut App, mintables_addr: &Addr) {
+    let trait_types = ["eyes", "ears"]
+        .iter()
+        .map(|trait_type| TraitTypeInfo {
+            trait_type: trait_type.to_string(),
+            display_name: trait_type.to_string(),
+            image_key: trait_type.to_string(),
+        })
+        .collect();
+    let trait_values = [
+        ("eyes", "laser"),
+        ("eyes", "gold"),
+        ("eyes", "partner"),
+        ("eyes", "capped"),
+        ("eyes", "sleepy"),
+        ("ears", "pointy"),
+    ]
+    .iter()
+    .map(|(trait_type, trait_value)| TraitValueInfo {
+        trait_type: trait_type.to_string(),
+        trait_value: trait_value.to_string(),
+        display_name: trait_value.to_string(),
+        image_key: format!("{trait_type}/{trait_value}"),
+    })
+    .collect();
+
+    for msg in [
+        mintables::msg::ExecuteMsg::SetTraitTypes { trait_types },
+        mintables::msg::ExecuteMsg::SetTraitValues { trait_values },
+    ] {
+        app.execute_contract(Addr::unchecked(OWNER), mintables_addr.clone(), &msg, &[])
+            .unwrap();
+    }
+}
+
+struct Suite {
+    app: App,
+    mintables: Addr,
+    manager: Addr,
+    collection: Addr,
+    proxy: Addr,
+}
+
+fn setup(beacon: bool) -> Suite {
+    let mut app = AppBuilder::new().build(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &Addr::unchecked(USER), coins(1000, NATIVE_DENOM))
+            .unwrap();
+    });
+    let owner = Addr::unchecked(OWNER);
+
+    let mintables_code_id = app.store_code(mintables());
+    let collection_code_id = app.store_code(trait_collection());
+    let manager_code_id = app.store_code(trait_manager());
+    let proxy_code_id = app.store_code(mock_proxy());
+
+    let mintables_addr = app
+        .instantiate_contract(
+            mintables_code_id,
+            owner.clone(),
+            &mintables::msg::InstantiateMsg { slots: vec![] },
+            &[],
+            "mintables",
+            None,
+        )
+        .unwrap();
+    register_traits(&mut app, &mintables_addr);
+    app.execute_contract(
+        owner.clone(),
+        mintables_addr.clone(),
+        &mintables::msg::ExecuteMsg::AddTraits {
+            new_traits: vec![test_trait()],
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        owner.clone(),
+        mintables_addr.clone(),
+        &mintables::msg::ExecuteMsg::AddTraitLootboxes {
+            new_trait_lootboxes: vec![TraitLootbox {
+                id: 1,
+                traits: vec![test_trait()],
+                possibilities: vec![100],
+                mint_price: coin(100, NATIVE_DENOM),
+                max_supply: Some(1),
+                minted: 0,
+                per_address_limit: None,
+                sale_start: None,
+                sale_end: None,
+            }],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let proxy = app
+        .instantiate_contract(proxy_code_id, owner.clone(), &Empty {}, &[], "proxy", None)
+        .unwrap();
+
+    let randomness_source = if beacon {
+        RandomnessSource::Beacon {
+            proxy_addr: proxy.clone(),
+        }
+    } else {
+        RandomnessSource::Internal
+    };
+
+    let manager = app
+        .instantiate_contract(
+            manager_code_id,
+            owner.clone(),
+            &CreateTraitManagerMsg {
+                init_msg: None::<Empty>,
+                collection_params: CollectionParams {
+                    code_id: collection_code_id,
+                    name: "Traits".to_string(),
+                    symbol: Some("TRAIT".to_string()),
+                    info: CollectionInfo::<RoyaltyInfoResponse> {
+                        creator: "creator".to_string(),
+                        description: "description".to_string(),
+                        image: Some("https://example.com/image.png".to_string()),
+                        external_link: None,
+                        explicit_content: None,
+                        royalty_info: None,
+                    },
+                    image_template: None,
+                },
+                manager_params: TraitManagerParams {
+                    mint_prices: vec![],
+                    rarities: vec![],
+                    revenue_split: treasury_split(),
+                    mint_limits: MintLimits::default(),
+                    randomness_source,
+                    mintable_collection_addr: mintables_addr.clone(),
+                },
+            },
+            &[],
+            "trait-manager",
+            None,
+        )
+        .unwrap();
+
+    //The manager records its mints against the supply kept in mintables
+    app.execute_contract(
+        owner,
+        mintables_addr.clone(),
+        &mintables::msg::ExecuteMsg::AddManagers {
+            managers: vec![manager.to_string()],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let config: TraitManagerConfigResponse<Empty> = app
+        .wrap()
+        .query_wasm_smart(&manager, &TraitManagerQueryMsg::Config {})
+        .unwrap();
+
+    Suite {
+        app,
+        mintables: mintables_addr,
+        manager,
+        collection: Addr::unchecked(config.collection_address),
+        proxy,
+    }
+}
+
+fn user_tokens(suite: &Suite) -> Vec<String> {
+    let tokens: TokensResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.collection,
+            &cw721_trait_onchain::QueryMsg::<Empty>::Tokens {
+                owner: USER.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    tokens.tokens
+}
+
+#[test]
+fn open_lootbox_with_beacon() {
+    let mut suite = setup(true);
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::OpenLootbox {
+                lootbox_id: 1,
+                receiver: None,
+                commitment: None,
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+
+    //The lootbox is paid but nothing is minted until the beacon answers
+    let pending: LootboxCommitmentResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.manager,
+            &TraitManagerQueryMsg::LootboxCommitment { commitment_id: 1 },
+        )
+        .unwrap();
+    assert_eq!(pending.commitment, None);
+    assert!(user_tokens(&suite).is_empty());
+
+    //Only the proxy can deliver randomness
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::ReceiveRandomness {
+                job_id: "1".to_string(),
+                randomness: HexBinary::from(vec![1u8; 32]),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnauthorizedRandomnessSource {}
+    );
+
+    //Beacon jobs cannot be revealed with a secret
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::RevealLootbox {
+                commitment_id: 1,
+                secret: "secret".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AwaitingRandomness {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.proxy.clone(),
+            &MockProxyExecuteMsg::Publish {
+                randomness: HexBinary::from(vec![7u8; 32]),
+            },
+            &[],
+        )
+        .unwrap();
+
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+    assert!(suite
+        .app
+        .wrap()
+        .query_wasm_smart::<LootboxCommitmentResponse>(
+            &suite.manager,
+            &TraitManagerQueryMsg::LootboxCommitment { commitment_id: 1 },
+        )
+        .is_err());
+}
+
+#[test]
+fn open_lootbox_with_internal_randomness() {
+    let mut suite = setup(false);
+    let secret = "secret";
+    let commitment = format!("{:x}", Sha256::digest(secret.as_bytes()));
+
+    //A commitment is required when there is no beacon
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::OpenLootbox {
+                lootbox_id: 1,
+                receiver: None,
+                commitment: None,
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidCommitment {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::OpenLootbox {
+                lootbox_id: 1,
+                receiver: None,
+                commitment: Some(commitment),
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+
+    //Randomness from anyone is rejected when the manager uses internal randomness
+    let err = suite
+        .app
+        .execute_contract(
+            suite.proxy.clone(),
+            suite.manager.clone(),
+            &ExecuteMsg::ReceiveRandomness {
+                job_id: "1".to_string(),
+                randomness: HexBinary::from(vec![1u8; 32]),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnauthorizedRandomnessSource {}
+    );
+
+    let reveal = ExecuteMsg::RevealLootbox {
+        commitment_id: 1,
+        secret: secret.to_string(),
+    };
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(USER), suite.manager.clone(), &reveal, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RevealTooEarly {}
+    );
+
+    suite.app.update_block(next_block);
+    suite
+        .app
+        .execute_contract(Addr::unchecked(USER), suite.manager.clone(), &reveal, &[])
+        .unwrap();
+
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+}
+
+#[test]
+fn resolve_expired_lootbox() {
+    let mut suite = setup(false);
+    let secret = "secret";
+    let commitment = format!("{:x}", Sha256::digest(secret.as_bytes()));
+
+    let gold = Trait {
+        id: 2,
+        trait_value: "gold".to_string(),
+        ..test_trait()
+    };
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddTraitLootboxes {
+                new_trait_lootboxes: vec![TraitLootbox {
+                    id: 2,
+                    traits: vec![test_trait(), gold],
+                    possibilities: vec![1, 99],
+                    mint_price: coin(100, NATIVE_DENOM),
+                    max_supply: None,
+                    minted: 0,
+                    per_address_limit: None,
+                    sale_start: None,
+                    sale_end: None,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::OpenLootbox {
+                lootbox_id: 2,
+                receiver: None,
+                commitment: Some(commitment),
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+
+    let resolve = ExecuteMsg::ResolveExpiredLootbox { commitment_id: 1 };
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(OWNER), suite.manager.clone(), &resolve, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CommitmentNotExpired {}
+    );
+
+    //The secret can no longer be revealed once the timeout is over
+    suite
+        .app
+        .update_block(|block| block.height += REVEAL_TIMEOUT + 1);
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::RevealLootbox {
+                commitment_id: 1,
+                secret: secret.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CommitmentExpired {}
+    );
+
+    //Anyone can resolve it, but the lootbox is forfeited without a trait or a refund,
+    //so not revealing a known result is worse than any draw
+    let balance = |suite: &Suite| {
+        suite
+            .app
+            .wrap()
+            .query_balance(USER, NATIVE_DENOM)
+            .unwrap()
+            .amount
+    };
+    let paid = balance(&suite);
+    let res = suite
+        .app
+        .execute_contract(Addr::unchecked(OWNER), suite.manager.clone(), &resolve, &[])
+        .unwrap();
+    assert!(res.has_event(&Event::new("wasm").add_attribute("action", "forfeit_lootbox")));
+    assert!(user_tokens(&suite).is_empty());
+    assert_eq!(balance(&suite), paid);
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(OWNER), suite.manager.clone(), &resolve, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CommitmentNotFound {}
+    );
+
+    //The forfeited lootbox is not counted as sold, while revealing always gives one of its entries
+    let supply: SupplyResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.mintables,
+            &mintables::msg::QueryMsg::Supply {
+                kind: MintableKind::TraitLootbox,
+                id: 2,
+            },
+        )
+        .unwrap();
+    assert_eq!(supply.minted, 0);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::OpenLootbox {
+                lootbox_id: 2,
+                receiver: None,
+                commitment: Some(format!("{:x}", Sha256::digest(secret.as_bytes()))),
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+    suite.app.update_block(next_block);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::RevealLootbox {
+                commitment_id: 2,
+                secret: secret.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+}
+
+#[test]
+fn beacon_jobs_after_source_change_and_timeout() {
+    let mut suite = setup(true);
+    let open = |suite: &mut Suite, lootbox_id: u32| {
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                suite.manager.clone(),
+                &ExecuteMsg::OpenLootbox {
+                    lootbox_id,
+                    receiver: None,
+                    commitment: None,
+                },
+                &coins(100, NATIVE_DENOM),
+            )
+            .unwrap();
+    };
+    let update_source = |randomness_source| ExecuteMsg::UpdateConfig {
+        new_config: UpdateTraitManagerParamsMsg {
+            mint_prices: vec![],
+            rarities: vec![],
+            revenue_split: treasury_split(),
+            mint_limits: MintLimits::default(),
+            randomness_source,
+        },
+    };
+    let balance = |suite: &Suite, address: &str| {
+        suite
+            .app
+            .wrap()
+            .query_balance(address, NATIVE_DENOM)
+            .unwrap()
+            .amount
+            .u128()
+    };
+
+    //The payment is held until the job is answered
+    open(&mut suite, 1);
+    assert_eq!(balance(&suite, USER), 900);
+    assert_eq!(balance(&suite, TREASURY), 0);
+
+    //Randomness is not taken while lootboxes are paused
+    let publish = MockProxyExecuteMsg::Publish {
+        randomness: HexBinary::from(vec![7u8; 32]),
+    };
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::Pause {
+                operations: Some(vec![Operation::Lootbox]),
+            },
+            &[],
+        )
+        .unwrap();
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(OWNER), suite.proxy.clone(), &publish, &[])
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "This operation is paused");
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::Unpause { operations: None },
+            &[],
+        )
+        .unwrap();
+
+    //Pending jobs are still answered by the proxy they were sent to after the source changes
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &update_source(RandomnessSource::Internal),
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.proxy.clone(),
+            &MockProxyExecuteMsg::Publish {
+                randomness: HexBinary::from(vec![7u8; 32]),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+    assert_eq!(balance(&suite, TREASURY), 100);
+
+    //Jobs the beacon never answers are refunded and their supply is given back
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddTraitLootboxes {
+                new_trait_lootboxes: vec![TraitLootbox {
+                    id: 2,
+                    traits: vec![test_trait()],
+                    possibilities: vec![100],
+                    mint_price: coin(100, NATIVE_DENOM),
+                    max_supply: Some(1),
+                    minted: 0,
+                    per_address_limit: None,
+                    sale_start: None,
+                    sale_end: None,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &update_source(RandomnessSource::Beacon {
+                proxy_addr: suite.proxy.clone(),
+            }),
+            &[],
+        )
+        .unwrap();
+    open(&mut suite, 2);
+    assert_eq!(balance(&suite, USER), 800);
+
+    suite
+        .app
+        .update_block(|block| block.height += REVEAL_TIMEOUT + 1);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::ResolveExpiredLootbox { commitment_id: 2 },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(balance(&suite, USER), 900);
+    assert_eq!(balance(&suite, TREASURY), 100);
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+    let supply: SupplyResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.mintables,
+            &mintables::msg::QueryMsg::Supply {
+                kind: MintableKind::TraitLootbox,
+                id: 2,
+            },
+        )
+        .unwrap();
+    assert_eq!(supply.minted, 0);
+
+    //The refunded lootbox is no longer counted against the caps of the buyer
+    let allowance: MintAllowanceResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.manager,
+            &TraitManagerQueryMsg::MintAllowance {
+                address: USER.to_string(),
+                item: Some(SaleItem::Lootbox { id: 2 }),
+            },
+        )
+        .unwrap();
+    assert_eq!(allowance.minted, 1);
+    assert_eq!(allowance.item_minted, 0);
+}
+
+#[test]
+fn lootbox_sold_out() {
+    let mut suite = setup(true);
+    let open = ExecuteMsg::OpenLootbox {
+        lootbox_id: 1,
+        receiver: None,
+        commitment: None,
+    };
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &open,
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+
+    let supply: SupplyResp = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.mintables,
+            &mintables::msg::QueryMsg::Supply {
+                kind: MintableKind::TraitLootbox,
+                id: 1,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        supply,
+        SupplyResp {
+            max_supply: Some(1),
+            minted: 1,
+            remaining: Some(0),
+        }
+    );
+
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &open,
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::SoldOut {}
+    );
+
+    //Only managers can record mints
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::RecordMint {
+                kind: MintableKind::TraitLootbox,
+                id: 1,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<mintables::error::ContractError>().unwrap(),
+        mintables::error::ContractError::Unauthorized {}
+    );
+}
+
+#[test]
+fn mint_trait_from_catalog() {
+    let mut suite = setup(true);
+    let test_trait = test_trait();
+    let token_info = Metadata {
+        trait_type: test_trait.trait_type,
+        trait_value: test_trait.trait_value,
+        trait_rarity: test_trait.trait_rarity,
+    };
+
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::Mint {
+                token_info: Metadata {
+                    trait_value: "unknown".to_string(),
+                    ..token_info.clone()
+                },
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidTrait {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::Mint { token_info },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+}
+
+#[test]
+fn mint_within_sale_window() {
+    let mut suite = setup(false);
+    let height = suite.app.block_info().height;
+    let drop = Trait {
+        id: 2,
+        trait_value: "gold".to_string(),
+        sale_start: Some(Scheduled::AtHeight(height + 2)),
+        sale_end: Some(Scheduled::AtHeight(height + 4)),
+        ..test_trait()
+    };
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddTraits {
+                new_traits: vec![drop.clone()],
+            },
+            &[],
+        )
+        .unwrap();
+
+    let mint = ExecuteMsg::Mint {
+        token_info: Metadata {
+            trait_type: drop.trait_type,
+            trait_value: drop.trait_value,
+            trait_rarity: drop.trait_rarity,
+        },
+    };
+    let user = Addr::unchecked(USER);
+
+    //Before the sale starts
+    let err = suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.manager.clone(),
+            &mint,
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::SaleNotActive {}
+    );
+
+    suite.app.update_block(|block| block.height += 2);
+    suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.manager.clone(),
+            &mint,
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+
+    //After the sale ends
+    suite.app.update_block(|block| block.height += 2);
+    let err = suite
+        .app
+        .execute_contract(
+            user,
+            suite.manager.clone(),
+            &mint,
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::SaleNotActive {}
+    );
+}
+
+#[test]
+fn presale_mint_with_proof() {
+    let mut suite = setup(false);
+
+    //Allowlist of two addresses, the proof of each one is the leaf of the other
+    let user_leaf = leaf_hash(USER);
+    let other_leaf = leaf_hash("other");
+    let (first, second) = if user_leaf <= other_leaf {
+        (user_leaf, other_leaf)
+    } else {
+        (other_leaf, user_leaf)
+    };
+    let root = Sha256::new()
+        .chain_update(first)
+        .chain_update(second)
+        .finalize();
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::SetPresalePhase {
+                phase_id: 1,
+                phase: Some(PresalePhase {
+                    merkle_root: format!("{:x}", root),
+                    per_address_limit: 1,
+                    discount_percent: 40,
+                }),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let presale_trait = Trait {
+        id: 2,
+        trait_value: "gold".to_string(),
+        mint_price: Some(coin(500, NATIVE_DENOM)),
+        ..test_trait()
+    };
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddTraits {
+                new_traits: vec![presale_trait.clone()],
+            },
+            &[],
+        )
+        .unwrap();
+
+    let mint = |proof: Vec<String>| ExecuteMsg::MintWithProof {
+        phase_id: 1,
+        proof,
+        token_info: Metadata {
+            trait_type: presale_trait.trait_type.clone(),
+            trait_value: presale_trait.trait_value.clone(),
+            trait_rarity: presale_trait.trait_rarity.clone(),
+        },
+        receiver: None,
+    };
+    let user = Addr::unchecked(USER);
+    let proof = vec![HexBinary::from(other_leaf.to_vec()).to_hex()];
+
+    let err = suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.manager.clone(),
+            &mint(vec![]),
+            &coins(300, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NotAllowlisted {}
+    );
+
+    //The listed price is not accepted in the presale
+    let err = suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.manager.clone(),
+            &mint(proof.clone()),
+            &coins(500, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::IncorrectMintFunds {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.manager.clone(),
+            &mint(proof.clone()),
+            &coins(300, NATIVE_DENOM),
+        )
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+
+    let mints: PresaleMintsResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.manager,
+            &TraitManagerQueryMsg::PresaleMints {
+                phase_id: 1,
+                address: USER.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        mints,
+        PresaleMintsResponse {
+            minted: 1,
+            remaining: 0
+        }
+    );
+
+    let err = suite
+        .app
+        .execute_contract(
+            user,
+            suite.manager.clone(),
+            &mint(proof),
+            &coins(300, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::PresaleLimitReached {}
+    );
+}
+
+#[test]
+fn mint_at_rarity_price() {
+    let mut suite = setup(false);
+    let epic_trait = Trait {
+        id: 3,
+        trait_rarity: "epic".to_string(),
+        mint_price: None,
+        ..test_trait()
+    };
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddTraits {
+                new_traits: vec![epic_trait.clone()],
+            },
+            &[],
+        )
+        .unwrap();
+
+    let mint = ExecuteMsg::Mint {
+        token_info: Metadata {
+            trait_type: epic_trait.trait_type,
+            trait_value: epic_trait.trait_value,
+            trait_rarity: epic_trait.trait_rarity,
+        },
+    };
+    let user = Addr::unchecked(USER);
+
+    let err = suite
+        .app
+        .execute_contract(
+            user.clone(),
+            suite.manager.clone(),
+            &mint,
+            &coins(200, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoMintPrice {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::UpdateConfig {
+                new_config: UpdateTraitManagerParamsMsg {
+                    mint_prices: vec![coin(200, NATIVE_DENOM)],
+                    rarities: vec!["epic".to_string()],
+                    revenue_split: treasury_split(),
+                    mint_limits: MintLimits::default(),
+                    randomness_source: RandomnessSource::Internal,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+    let config: TraitManagerConfigResponse<Empty> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&suite.manager, &TraitManagerQueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.config.mint_prices, vec![coin(200, NATIVE_DENOM)]);
+    assert_eq!(config.config.rarities, vec!["epic".to_string()]);
+
+    suite
+        .app
+        .execute_contract(
+            user,
+            suite.manager.clone(),
+            &mint,
+            &coins(200, NATIVE_DENOM),
+        )
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+}
+
+#[test]
+fn mint_paid_with_cw20() {
+    let mut suite = setup(false);
+    let token_code_id = suite.app.store_code(cw20_token());
+    let token = suite
+        .app
+        .instantiate_contract(
+            token_code_id,
+            Addr::unchecked(OWNER),
+            &cw20_base::msg::InstantiateMsg {
+                name: "Partner".to_string(),
+                symbol: "PART".to_string(),
+                decimals: 6,
+                initial_balances: vec![Cw20Coin {
+                    address: USER.to_string(),
+                    amount: 1000u128.into(),
+                }],
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            "partner-token",
+            None,
+        )
+        .unwrap();
+
+    //The price names the token contract instead of a native denom
+    let partner_trait = Trait {
+        id: 4,
+        trait_value: "partner".to_string(),
+        mint_price: Some(coin(250, token.as_str())),
+        ..test_trait()
+    };
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddTraits {
+                new_traits: vec![partner_trait.clone()],
+            },
+            &[],
+        )
+        .unwrap();
+
+    let token_info = Metadata {
+        trait_type: partner_trait.trait_type,
+        trait_value: partner_trait.trait_value,
+        trait_rarity: partner_trait.trait_rarity,
+    };
+    let send = |amount: u128| Cw20ExecuteMsg::Send {
+        contract: suite.manager.to_string(),
+        amount: amount.into(),
+        msg: to_binary(&ReceiveMsg::Mint {
+            token_info: token_info.clone(),
+        })
+        .unwrap(),
+    };
+
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(USER), token.clone(), &send(100), &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::IncorrectMintFunds {}
+    );
+
+    suite
+        .app
+        .execute_contract(Addr::unchecked(USER), token.clone(), &send(250), &[])
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+
+    //Tokens other than the native denom are not burnt and go to the recipients in full
+    let balance: BalanceResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &token,
+            &Cw20QueryMsg::Balance {
+                address: TREASURY.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(balance.balance.u128(), 250);
+}
+
+#[test]
+fn mint_with_revenue_split() {
+    let mut suite = setup(false);
+    let update_config = |revenue_split| ExecuteMsg::UpdateConfig {
+        new_config: UpdateTraitManagerParamsMsg {
+            mint_prices: vec![],
+            rarities: vec![],
+            revenue_split,
+            mint_limits: MintLimits::default(),
+            randomness_source: RandomnessSource::Internal,
+        },
+    };
+
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &update_config(RevenueSplit {
+                recipients: vec![(Addr::unchecked(TREASURY), 0)],
+                burn_weight: 0,
+                dust_recipient: Addr::unchecked(TREASURY),
+                accrue: false,
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidRevenueSplit {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &update_config(RevenueSplit {
+                recipients: vec![
+                    (Addr::unchecked("artist"), 2),
+                    (Addr::unchecked(TREASURY), 1),
+                ],
+                burn_weight: 1,
+                dust_recipient: Addr::unchecked(TREASURY),
+                accrue: false,
+            }),
+            &[],
+        )
+        .unwrap();
+
+    let test_trait = test_trait();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::Mint {
+                token_info: Metadata {
+                    trait_type: test_trait.trait_type,
+                    trait_value: test_trait.trait_value,
+                    trait_rarity: test_trait.trait_rarity,
+                },
+            },
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+
+    let balance = |address: &str| {
+        suite
+            .app
+            .wrap()
+            .query_balance(address, NATIVE_DENOM)
+            .unwrap()
+            .amount
+            .u128()
+    };
+    assert_eq!(balance("artist"), 50);
+    assert_eq!(balance(TREASURY), 25);
+    assert_eq!(balance(suite.manager.as_str()), 0);
+    //The remaining quarter is burnt
+    assert_eq!(balance(USER), 900);
+}
+
+#[test]
+fn accrue_and_withdraw_revenue() {
+    let mut suite = setup(false);
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::UpdateConfig {
+                new_config: UpdateTraitManagerParamsMsg {
+                    mint_prices: vec![],
+                    rarities: vec![],
+                    revenue_split: RevenueSplit {
+                        burn_weight: 1,
+                        accrue: true,
+                        ..treasury_split()
+                    },
+                    mint_limits: MintLimits::default(),
+                    randomness_source: RandomnessSource::Internal,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+    let test_trait = test_trait();
+    let mint = ExecuteMsg::Mint {
+        token_info: Metadata {
+            trait_type: test_trait.trait_type,
+            trait_value: test_trait.trait_value,
+            trait_rarity: test_trait.trait_rarity,
+        },
+    };
+    for _ in 0..2 {
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                suite.manager.clone(),
+                &mint,
+                &coins(100, NATIVE_DENOM),
+            )
+            .unwrap();
+    }
+
+    //Half of each mint is burnt and the rest stays in the manager
+    let revenue: RevenueResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.manager,
+            &TraitManagerQueryMsg::Revenue {
+                denom: NATIVE_DENOM.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(revenue.lifetime.u128(), 200);
+    assert_eq!(revenue.burned.u128(), 100);
+    assert_eq!(revenue.accrued.u128(), 100);
+    let balance = |suite: &Suite, address: &str| {
+        suite
+            .app
+            .wrap()
+            .query_balance(address, NATIVE_DENOM)
+            .unwrap()
+            .amount
+            .u128()
+    };
+    assert_eq!(balance(&suite, suite.manager.as_str()), 100);
+    assert_eq!(balance(&suite, TREASURY), 0);
+
+    let withdraw = |amount: u128| ExecuteMsg::Withdraw {
+        denom: NATIVE_DENOM.to_string(),
+        amount: amount.into(),
+        to: "vault".to_string(),
+    };
+    //A recipient of the split cannot take out the shares of the others
+    for sender in [USER, TREASURY] {
+        let err = suite
+            .app
+            .execute_contract(
+                Addr::unchecked(sender),
+                suite.manager.clone(),
+                &withdraw(60),
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Ownership(cw_ownable::OwnershipError::NotOwner)
+        );
+    }
+
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &withdraw(101),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidWithdrawAmount {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &withdraw(60),
+            &[],
+        )
+        .unwrap();
+    assert_eq!(balance(&suite, "vault"), 60);
+
+    let revenue: Vec<RevenueResponse> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&suite.manager, &TraitManagerQueryMsg::AllRevenue {})
+        .unwrap();
+    assert_eq!(revenue.len(), 1);
+    assert_eq!(revenue[0].accrued.u128(), 40);
+    assert_eq!(revenue[0].lifetime.u128(), 200);
+}
+
+#[test]
+fn mint_limits_per_address() {
+    let mut suite = setup(false);
+    let capped_trait = Trait {
+        id: 5,
+        trait_value: "capped".to_string(),
+        per_address_limit: Some(1),
+        ..test_trait()
+    };
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddTraits {
+                new_traits: vec![capped_trait.clone()],
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::UpdateConfig {
+                new_config: UpdateTraitManagerParamsMsg {
+                    mint_prices: vec![],
+                    rarities: vec![],
+                    revenue_split: treasury_split(),
+                    mint_limits: MintLimits {
+                        per_address: Some(3),
+                        per_block: Some(2),
+                        block_ceiling: None,
+                    },
+                    randomness_source: RandomnessSource::Internal,
+                },
+            },
+            &[],
+        )
+        .unwrap();
+
+    let mint = |mint_trait: Trait| ExecuteMsg::Mint {
+        token_info: Metadata {
+            trait_type: mint_trait.trait_type,
+            trait_value: mint_trait.trait_value,
+            trait_rarity: mint_trait.trait_rarity,
+        },
+    };
+    let execute = |suite: &mut Suite, msg: &ExecuteMsg| {
+        suite.app.execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            msg,
+            &coins(100, NATIVE_DENOM),
+        )
+    };
+
+    execute(&mut suite, &mint(test_trait())).unwrap();
+    execute(&mut suite, &mint(test_trait())).unwrap();
+    let err = execute(&mut suite, &mint(test_trait())).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BlockMintLimitReached {}
+    );
+    suite.app.update_block(next_block);
+
+    let allowance = |suite: &Suite, item: Option<SaleItem>| -> MintAllowanceResponse {
+        suite
+            .app
+            .wrap()
+            .query_wasm_smart(
+                &suite.manager,
+                &TraitManagerQueryMsg::MintAllowance {
+                    address: USER.to_string(),
+                    item,
+                },
+            )
+            .unwrap()
+    };
+    assert_eq!(
+        allowance(&suite, Some(SaleItem::Single { id: 5 })),
+        MintAllowanceResponse {
+            minted: 2,
+            item_minted: 0,
+            remaining: Some(1),
+        }
+    );
+
+    execute(&mut suite, &mint(capped_trait.clone())).unwrap();
+    suite.app.update_block(next_block);
+    let err = execute(&mut suite, &mint(capped_trait)).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MintLimitReached {}
+    );
+    let err = execute(&mut suite, &mint(test_trait())).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MintLimitReached {}
+    );
+    assert_eq!(allowance(&suite, None).remaining, Some(0));
+}
+
+#[test]
+fn pause_operations() {
+    let mut suite = setup(false);
+    let pause = ExecuteMsg::Pause {
+        operations: Some(vec![Operation::Mint, Operation::Lootbox]),
+    };
+
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(USER), suite.manager.clone(), &pause, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Ownership(cw_ownable::OwnershipError::NotOwner)
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::SetGuardian {
+                guardian: Some("guardian".to_string()),
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked("guardian"),
+            suite.manager.clone(),
+            &pause,
+            &[],
+        )
+        .unwrap();
+
+    let status: PauseStatusResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&suite.manager, &TraitManagerQueryMsg::PauseStatus {})
+        .unwrap();
+    assert_eq!(
+        status,
+        PauseStatusResponse {
+            paused: vec![Operation::Mint, Operation::Lootbox],
+            guardian: Some("guardian".to_string()),
+        }
+    );
+
+    let test_trait = test_trait();
+    let mint = ExecuteMsg::Mint {
+        token_info: Metadata {
+            trait_type: test_trait.trait_type,
+            trait_value: test_trait.trait_value,
+            trait_rarity: test_trait.trait_rarity,
+        },
+    };
+    let open = ExecuteMsg::OpenLootbox {
+        lootbox_id: 1,
+        receiver: None,
+        commitment: Some(format!("{:x}", Sha256::digest("secret".as_bytes()))),
+    };
+    for msg in [&mint, &open] {
+        let err = suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                suite.manager.clone(),
+                msg,
+                &coins(100, NATIVE_DENOM),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::OperationPaused {}
+        );
+    }
+
+    //Only the given operations are unpaused
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::Unpause {
+                operations: Some(vec![Operation::Mint]),
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &mint,
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["1".to_string()]);
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &open,
+            &coins(100, NATIVE_DENOM),
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::OperationPaused {}
+    );
+}
+
+//Sets up a recipe turning two common traits into a random rare one, with three common traits for the user
+fn setup_craft(beacon: bool) -> Suite {
+    let mut suite = setup(beacon);
+    let common_trait = Trait {
+        id: 6,
+        trait_value: "sleepy".to_string(),
+        trait_rarity: "common".to_string(),
+        ..test_trait()
+    };
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddTraits {
+                new_traits: vec![common_trait.clone()],
+            },
+            &[],
+        )
+        .unwrap();
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.mintables.clone(),
+            &mintables::msg::ExecuteMsg::AddRecipes {
+                new_recipes: vec![Recipe {
+                    id: 1,
+                    inputs: vec![RecipeInput {
+                        trait_type: "eyes".to_string(),
+                        trait_value: None,
+                        trait_rarity: "common".to_string(),
+                        amount: 2,
+                    }],
+                    output: RecipeOutput::Random {
+                        trait_type: "eyes".to_string(),
+                        trait_rarity: "rare".to_string(),
+                    },
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+
+    for _ in 0..3 {
+        suite
+            .app
+            .execute_contract(
+                Addr::unchecked(USER),
+                suite.manager.clone(),
+                &ExecuteMsg::Mint {
+                    token_info: Metadata {
+                        trait_type: common_trait.trait_type.clone(),
+                        trait_value: common_trait.trait_value.clone(),
+                        trait_rarity: common_trait.trait_rarity.clone(),
+                    },
+                },
+                &coins(100, NATIVE_DENOM),
+            )
+            .unwrap();
+    }
+    suite
+}
+
+fn craft(
+    suite: &mut Suite,
+    trait_ids: Vec<&str>,
+    commitment: Option<String>,
+) -> Result<AppResponse, ContractError> {
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::Craft {
+                trait_ids: trait_ids.into_iter().map(String::from).collect(),
+                recipe_id: 1,
+                commitment,
+            },
+            &[],
+        )
+        .map_err(|err| err.downcast().unwrap())
+}
+
+fn approve_manager(suite: &mut Suite) {
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.collection.clone(),
+            &cw721_trait_onchain::ExecuteMsg::<Metadata, Empty>::ApproveAll {
+                operator: suite.manager.to_string(),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+}
+
+fn escrow_holder(suite: &Suite, token_id: &str) -> Option<String> {
+    let escrow: EscrowResponse = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.collection,
+            &cw721_trait_onchain::QueryMsg::<Empty>::Escrow {
+                token_id: token_id.to_string(),
+            },
+        )
+        .unwrap();
+    escrow.holder
+}
+
+#[test]
+fn craft_traits() {
+    let mut suite = setup_craft(false);
+    let secret = "secret";
+    let commitment = format!("{:x}", Sha256::digest(secret.as_bytes()));
+
+    assert_eq!(
+        craft(&mut suite, vec!["1"], Some(commitment.clone())).unwrap_err(),
+        ContractError::RecipeMismatch {}
+    );
+    assert_eq!(
+        craft(&mut suite, vec!["1", "1"], Some(commitment.clone())).unwrap_err(),
+        ContractError::RecipeMismatch {}
+    );
+
+    //A random output needs a commitment when there is no beacon
+    approve_manager(&mut suite);
+    assert_eq!(
+        craft(&mut suite, vec!["1", "2"], None).unwrap_err(),
+        ContractError::InvalidCommitment {}
+    );
+
+    //The inputs are held by the manager until the output is drawn
+    let res = craft(&mut suite, vec!["1", "2"], Some(commitment)).unwrap();
+    assert!(res.has_event(&Event::new("wasm").add_attribute("commitment_id", "1")));
+    assert_eq!(escrow_holder(&suite, "1"), Some(suite.manager.to_string()));
+    assert_eq!(escrow_holder(&suite, "2"), Some(suite.manager.to_string()));
+    assert_eq!(user_tokens(&suite).len(), 3);
+
+    let reveal = ExecuteMsg::RevealCraft {
+        commitment_id: 1,
+        secret: secret.to_string(),
+    };
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(USER), suite.manager.clone(), &reveal, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::RevealTooEarly {}
+    );
+
+    suite.app.update_block(next_block);
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::RevealCraft {
+                commitment_id: 1,
+                secret: "other".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidSecret {}
+    );
+
+    //The manager burns the inputs on behalf of the user
+    suite
+        .app
+        .execute_contract(Addr::unchecked(USER), suite.manager.clone(), &reveal, &[])
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["3".to_string(), "4".to_string()]);
+
+    let crafted: NftInfoResponse<Metadata> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.collection,
+            &cw721_trait_onchain::QueryMsg::<Empty>::NftInfo {
+                token_id: "4".to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(crafted.extension.trait_value, test_trait().trait_value);
+    assert_eq!(crafted.extension.trait_rarity, "rare".to_string());
+}
+
+#[test]
+fn craft_with_beacon() {
+    let mut suite = setup_craft(true);
+    approve_manager(&mut suite);
+
+    craft(&mut suite, vec!["1", "2"], None).unwrap();
+    assert_eq!(escrow_holder(&suite, "1"), Some(suite.manager.to_string()));
+
+    //Beacon jobs cannot be revealed with a secret and only the proxy can answer them
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::RevealCraft {
+                commitment_id: 1,
+                secret: "secret".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AwaitingRandomness {}
+    );
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::ReceiveRandomness {
+                job_id: "1".to_string(),
+                randomness: HexBinary::from(vec![1u8; 32]),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnauthorizedRandomnessSource {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.proxy.clone(),
+            &MockProxyExecuteMsg::Publish {
+                randomness: HexBinary::from(vec![7u8; 32]),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["3".to_string(), "4".to_string()]);
+}
+
+#[test]
+fn resolve_expired_crafts() {
+    //Beacon jobs that are never answered give the inputs back
+    let mut suite = setup_craft(true);
+    approve_manager(&mut suite);
+    craft(&mut suite, vec!["1", "2"], None).unwrap();
+
+    let resolve = ExecuteMsg::ResolveExpiredCraft { commitment_id: 1 };
+    let err = suite
+        .app
+        .execute_contract(Addr::unchecked(OWNER), suite.manager.clone(), &resolve, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CommitmentNotExpired {}
+    );
+
+    suite
+        .app
+        .update_block(|block| block.height += REVEAL_TIMEOUT + 1);
+    suite
+        .app
+        .execute_contract(Addr::unchecked(OWNER), suite.manager.clone(), &resolve, &[])
+        .unwrap();
+    assert_eq!(user_tokens(&suite).len(), 3);
+    assert_eq!(escrow_holder(&suite, "1"), None);
+    assert_eq!(escrow_holder(&suite, "2"), None);
+
+    //Unrevealed secrets forfeit the inputs
+    let mut suite = setup_craft(false);
+    approve_manager(&mut suite);
+    let commitment = format!("{:x}", Sha256::digest("secret".as_bytes()));
+    craft(&mut suite, vec!["1", "2"], Some(commitment)).unwrap();
+
+    suite
+        .app
+        .update_block(|block| block.height += REVEAL_TIMEOUT + 1);
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked(USER),
+            suite.manager.clone(),
+            &ExecuteMsg::RevealCraft {
+                commitment_id: 1,
+                secret: "secret".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::CommitmentExpired {}
+    );
+    suite
+        .app
+        .execute_contract(Addr::unchecked(OWNER), suite.manager.clone(), &resolve, &[])
+        .unwrap();
+    assert_eq!(user_tokens(&suite), vec!["3".to_string()]);
+}
+
+#[test]
+fn mint_traits_for_managers() {
+    let mut suite = setup(false);
+    let token_info = Metadata {
+        trait_type: "ears".to_string(),
+        trait_value: "pointy".to_string(),
+        trait_rarity: "common".to_string(),
+    };
+    let mint_traits = |suite: &mut Suite, sender: &str| {
+        suite.app.execute_contract(
+            Addr::unchecked(sender),
+            suite.manager.clone(),
+            &ExecuteMsg::MintTraits {
+                receiver: USER.to_string(),
+                token_infos: vec![token_info.clone(), token_info.clone()],
+            },
+            &[],
+        )
+    };
+
+    let err = mint_traits(&mut suite, "character_manager").unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnauthorizedManager {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::AddManagers {
+                managers: vec!["character_manager".to_string()],
+            },
+            &[],
+        )
+        .unwrap();
+    let managers: Vec<String> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(&suite.manager, &TraitManagerQueryMsg::Managers {})
+        .unwrap();
+    assert_eq!(managers, vec!["character_manager".to_string()]);
+    //Managers can also hold traits in escrow while they are equipped
+    let agents: Vec<String> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.collection,
+            &cw721_trait_onchain::QueryMsg::<Empty>::EscrowAgents {},
+        )
+        .unwrap();
+    assert_eq!(agents, vec!["character_manager".to_string()]);
+
+    mint_traits(&mut suite, "character_manager").unwrap();
+    assert_eq!(user_tokens(&suite), vec!["1".to_string(), "2".to_string()]);
+
+    //Values missing from the trait registry cannot be minted by managers either
+    let err = suite
+        .app
+        .execute_contract(
+            Addr::unchecked("character_manager"),
+            suite.manager.clone(),
+            &ExecuteMsg::MintTraits {
+                receiver: USER.to_string(),
+                token_infos: vec![Metadata {
+                    trait_value: "floppy".to_string(),
+                    ..token_info.clone()
+                }],
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidTrait {}
+    );
+
+    suite
+        .app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            suite.manager.clone(),
+            &ExecuteMsg::RemoveManagers {
+                managers: vec!["character_manager".to_string()],
+            },
+            &[],
+        )
+        .unwrap();
+    let agents: Vec<String> = suite
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &suite.collection,
+            &cw721_trait_onchain::QueryMsg::<Empty>::EscrowAgents {},
+        )
+        .unwrap();
+    assert!(agents.is_empty());
+    let err = mint_traits(&mut suite, "character_manager").unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::UnauthorizedManager {}
+    );
+}
+
+#[test]
+fn migrate_legacy_config() {
+    let mut deps = mock_dependencies();
+    cw2::set_contract_version(deps.as_mut().storage, "trait-manager", "0.1.0").unwrap();
+    cw_ownable::initialize_owner(&mut deps.storage, &deps.api, Some(OWNER)).unwrap();
+    //Config stored by 0.1.0
+    LEGACY_CONFIG
+        .save(
+            deps.as_mut().storage,
+            &LegacyConfig {
+                collection_code_id: 7,
+                burn_ratio: 25,
+                destination: Some(Addr::unchecked(TREASURY)),
+                extension: Empty {},
+            },
+        )
+        .unwrap();
+
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    let config = CONFIG.load(&deps.storage).unwrap();
+    assert_eq!(config.collection_code_id, 7);
+    assert_eq!(
+        config.revenue_split,
+        RevenueSplit {
+            recipients: vec![(Addr::unchecked(TREASURY), 75)],
+            burn_weight: 25,
+            dust_recipient: Addr::unchecked(TREASURY),
+            accrue: false,
+        }
+    );
+    assert_eq!(config.randomness_source, RandomnessSource::Internal);
+    assert_eq!(config.mint_limits, MintLimits::default());
+
+    //Migrating again at the same version keeps the state
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert_eq!(CONFIG.load(&deps.storage).unwrap(), config);
+
+    cw2::set_contract_version(deps.as_mut().storage, "trait-manager", "1.0.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+    assert_eq!(err, ContractError::WrongMigrateVersion("1.0.0".to_string()));
+}
@@ -23,15 +23,30 @@ pub enum ContractError {
     #[error("Mint prices array must be the same length as rarities array")]
     NotSameLength {},
 
-    #[error("Burn ratio must be between 0 and 100")]
-    InvalidBurnRatio {},
+    #[error("Revenue split must have a positive total weight")]
+    InvalidRevenueSplit {},
+
+    #[error("Only the managers added by the owner can mint traits through this manager")]
+    UnauthorizedManager {},
+
+    #[error("Withdraw amount must be positive and at most the accrued revenue")]
+    InvalidWithdrawAmount {},
+
+    #[error("Mint limit of this address reached")]
+    MintLimitReached {},
+
+    #[error("Mint limit of this address for the current block reached")]
+    BlockMintLimitReached {},
+
+    #[error("Mint ceiling of the current block reached, try again in a later block")]
+    BlockCeilingReached {},
+
+    #[error("This operation is paused")]
+    OperationPaused {},
 
     #[error("Incorrect mind funds sent")]
     IncorrectMintFunds {},
 
-    #[error("If mint price is not fully burned or is not the native currency, destination address of mint funds cannot be empty")]
-    NoMintDestination {},
-
     #[error("Trait does not exist in mintables contract")]
     InvalidTrait {},
 
@@ -40,4 +55,76 @@ pub enum ContractError {
 
     #[error("Lootbox does not exist in mintables contract")]
     InvalidLootbox {},
+
+    #[error("Recipe does not exist in mintables contract")]
+    InvalidRecipe {},
+
+    #[error("Traits do not match the recipe inputs")]
+    RecipeMismatch {},
+
+    #[error("Sender does not own the trait")]
+    NotTraitOwner {},
+
+    #[error("Commitment must be a hex encoded sha256 hash")]
+    InvalidCommitment {},
+
+    #[error("Commitment not found")]
+    CommitmentNotFound {},
+
+    #[error("Secret does not match the commitment")]
+    InvalidSecret {},
+
+    #[error("Commitment can only be revealed in a later block")]
+    RevealTooEarly {},
+
+    #[error("Commitment has expired and can only be resolved")]
+    CommitmentExpired {},
+
+    #[error("Commitment has not expired yet")]
+    CommitmentNotExpired {},
+
+    #[error("Commitment is waiting for randomness from the beacon")]
+    AwaitingRandomness {},
+
+    #[error("Randomness job not found")]
+    JobNotFound {},
+
+    #[error("Only the configured randomness beacon can deliver randomness")]
+    UnauthorizedRandomnessSource {},
+
+    #[error("Sold out")]
+    SoldOut {},
+
+    #[error("Not on sale at this time")]
+    SaleNotActive {},
+
+    #[error("No mint price listed for the entry or its rarity")]
+    NoMintPrice {},
+
+    #[error("Presale phase does not exist")]
+    PresalePhaseNotFound {},
+
+    #[error("Sender is not in the presale allowlist")]
+    NotAllowlisted {},
+
+    #[error("Presale mint limit reached")]
+    PresaleLimitReached {},
+
+    #[error("Presale discount cannot be over 100 percent")]
+    InvalidDiscount {},
+
+    #[error("found version ({0}) which is newer than the code being migrated to")]
+    WrongMigrateVersion(String),
+
+    #[error("found contract ({0}) while attempting to migrate")]
+    WrongMigrateContract(String),
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
 }
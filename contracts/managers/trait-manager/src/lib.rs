@@ -3,3 +3,6 @@ mod error;
 pub mod msg;
 pub mod state;
 pub use crate::error::ContractError;
+
+#[cfg(test)]
+mod contract_tests;
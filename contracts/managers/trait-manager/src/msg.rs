@@ -1,7 +1,12 @@
 use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{HexBinary, Uint128};
+use cw20::Cw20ReceiveMsg;
 use cw721_trait_onchain::msg::Metadata;
 use cw_ownable::cw_ownable_execute;
-use utils::msg::UpdateTraitManagerParamsMsg;
+use utils::{msg::UpdateTraitManagerParamsMsg, Operation, PresalePhase};
+
+#[cw_serde]
+pub struct MigrateMsg {}
 
 #[cw_ownable_execute]
 #[cw_serde]
@@ -13,15 +18,116 @@ pub enum ExecuteMsg {
         token_info: Metadata,
         receiver: String,
     },
+    //Presale mint at the phase's discounted price, only for addresses in its allowlist
+    MintWithProof {
+        phase_id: u32,
+        //Hex encoded sibling hashes from the sender's leaf up to the root
+        proof: Vec<String>,
+        token_info: Metadata,
+        receiver: Option<String>,
+    },
     MintBundle{
         bundle_id: u32,
         receiver: Option<String>,
     },
-    OpenLootbox{
+    OpenLootbox {
         lootbox_id: u32,
         receiver: Option<String>,
+        //Hex encoded sha256 hash of a secret that will be revealed in a later block.
+        //Only used with internal randomness, ignored when a beacon is configured
+        commitment: Option<String>,
+    },
+    RevealLootbox {
+        commitment_id: u64,
+        secret: String,
+    },
+    ResolveExpiredLootbox {
+        commitment_id: u64,
+    },
+    //Burns traits matching the inputs of a mintables recipe to mint its output.
+    //The manager must be approved to burn the traits
+    Craft {
+        trait_ids: Vec<String>,
+        recipe_id: u32,
+        //Hex encoded sha256 hash of a secret that will be revealed in a later block.
+        //Only used by random outputs with internal randomness
+        commitment: Option<String>,
+    },
+    RevealCraft {
+        commitment_id: u64,
+        secret: String,
+    },
+    ResolveExpiredCraft {
+        commitment_id: u64,
     },
+    //Owner only, managers allowed to send `MintTraits`
+    AddManagers {
+        managers: Vec<String>,
+    },
+    RemoveManagers {
+        managers: Vec<String>,
+    },
+    //Sent by the added managers, mints the traits for free without recording them in mintables
+    MintTraits {
+        receiver: String,
+        token_infos: Vec<Metadata>,
+    },
+    //Callback from the randomness beacon proxy, the job id is the lootbox or craft commitment id
+    ReceiveRandomness {
+        job_id: String,
+        randomness: HexBinary,
+    },
+    //Mints paid with a CW20 token, the message is a `ReceiveMsg`
+    Receive(Cw20ReceiveMsg),
     UpdateConfig {
         new_config: UpdateTraitManagerParamsMsg,
     },
+    //Sets or, when no phase is given, removes a presale phase
+    SetPresalePhase {
+        phase_id: u32,
+        phase: Option<PresalePhase>,
+    },
+    //Owner only, sends out revenue accrued by the manager
+    Withdraw {
+        denom: String,
+        amount: Uint128,
+        to: String,
+    },
+    //Owner or guardian, without operations every operation is paused or unpaused
+    Pause {
+        operations: Option<Vec<Operation>>,
+    },
+    Unpause {
+        operations: Option<Vec<Operation>>,
+    },
+    SetGuardian {
+        guardian: Option<String>,
+    },
+}
+
+/// Mints that can be paid by sending CW20 tokens to the manager
+#[cw_serde]
+pub enum ReceiveMsg {
+    Mint {
+        token_info: Metadata,
+    },
+    MintTo {
+        token_info: Metadata,
+        receiver: String,
+    },
+    MintWithProof {
+        phase_id: u32,
+        proof: Vec<String>,
+        token_info: Metadata,
+        receiver: Option<String>,
+    },
+    MintBundle {
+        bundle_id: u32,
+        receiver: Option<String>,
+    },
+    OpenLootbox {
+        lootbox_id: u32,
+        receiver: Option<String>,
+        commitment: Option<String>,
+    },
 }
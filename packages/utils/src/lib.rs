@@ -1,8 +1,14 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Coin, Decimal, Uint128};
+use cosmwasm_std::{to_binary, Addr, BankMsg, Coin, CosmosMsg, StdResult, Uint128, WasmMsg};
+use cw20::Cw20ExecuteMsg;
 
+pub mod merkle;
 pub mod msg;
 pub mod query;
+pub mod random;
+pub mod revenue;
+
+pub use revenue::{RevenueSplit, RevenueTotals};
 
 pub type CodeId = u64;
 pub const NATIVE_DENOM: &str = "uccat";
@@ -11,8 +17,15 @@ pub const NATIVE_DENOM: &str = "uccat";
 #[cw_serde]
 pub struct TraitManagerConfig<T> {
     pub collection_code_id: u64,
-    pub burn_ratio: u64,
-    pub destination: Option<Addr>,
+    //Price of each rarity, used for the traits listed without a mint price
+    #[serde(default)]
+    pub mint_prices: Vec<Coin>,
+    #[serde(default)]
+    pub rarities: Vec<String>,
+    pub revenue_split: RevenueSplit,
+    #[serde(default)]
+    pub mint_limits: MintLimits,
+    pub randomness_source: RandomnessSource,
     pub extension: T,
 }
 
@@ -20,19 +33,308 @@ pub struct TraitManagerConfig<T> {
 pub struct CharacterManagerConfig<T> {
     pub collection_code_id: u64,
     pub empty_character_mint_price: Coin,
-    //This ratio will be burnt
-    pub burn_ratio: u64,
-    //Rest sent here
-    pub destination: Option<Addr>,
+    //Price of each rarity, used for the characters listed without a mint price
+    #[serde(default)]
+    pub character_mint_prices: Vec<Coin>,
+    #[serde(default)]
+    pub character_rarities: Vec<String>,
+    pub revenue_split: RevenueSplit,
+    #[serde(default)]
+    pub mint_limits: MintLimits,
+    pub randomness_source: RandomnessSource,
+    #[serde(default)]
+    pub fusion: FusionConfig,
+    #[serde(default)]
+    pub dismantle: DismantleConfig,
+    #[serde(default)]
+    pub names: NameConfig,
     pub extension: T,
 }
 
-pub trait U64Ext {
-    fn bps_to_decimal(self) -> Decimal;
+impl<T> TraitManagerConfig<T> {
+    pub fn rarity_price(&self, rarity: &str) -> Option<Coin> {
+        rarity_price(&self.mint_prices, &self.rarities, rarity)
+    }
+}
+
+impl<T> CharacterManagerConfig<T> {
+    pub fn rarity_price(&self, rarity: &str) -> Option<Coin> {
+        rarity_price(
+            &self.character_mint_prices,
+            &self.character_rarities,
+            rarity,
+        )
+    }
+}
+
+fn rarity_price(prices: &[Coin], rarities: &[String], rarity: &str) -> Option<Coin> {
+    rarities
+        .iter()
+        .position(|r| r == rarity)
+        .and_then(|i| prices.get(i).cloned())
+}
+
+/// Caps on the purchases of a single address and of a whole block, caps per item are set on the mintables entries
+#[cw_serde]
+#[derive(Default)]
+pub struct MintLimits {
+    //Purchases across every item sold by the manager
+    pub per_address: Option<u32>,
+    //Purchases of an address within a single block
+    pub per_block: Option<u32>,
+    //Purchases of every address together within a single block
+    #[serde(default)]
+    pub block_ceiling: Option<u32>,
+}
+
+impl MintLimits {
+    pub fn address_allows(&self, minted: u32) -> bool {
+        self.per_address.map_or(true, |limit| minted < limit)
+    }
+
+    pub fn block_allows(&self, in_block: u32) -> bool {
+        self.per_block.map_or(true, |limit| in_block < limit)
+    }
+
+    pub fn ceiling_allows(&self, block_total: u32) -> bool {
+        self.block_ceiling.map_or(true, |limit| block_total < limit)
+    }
+
+    /// Purchases left to an address under every cap, None when nothing is capped
+    pub fn remaining(&self, minted: u32, in_block: u32, block_total: u32) -> Option<u32> {
+        [
+            self.per_address.map(|limit| limit.saturating_sub(minted)),
+            self.per_block.map(|limit| limit.saturating_sub(in_block)),
+            self.block_ceiling
+                .map(|limit| limit.saturating_sub(block_total)),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+}
+
+/// Purchases made in the block at `height`, out of the height and count saved by the last purchase
+pub fn block_mints(last: Option<(u64, u32)>, height: u64) -> u32 {
+    match last {
+        Some((last_height, minted)) if last_height == height => minted,
+        _ => 0,
+    }
+}
+
+/// How the character manager fuses two characters into a new one
+#[cw_serde]
+pub struct FusionConfig {
+    //Odds of each trait slot being inherited from each parent are its weight over the sum of both
+    pub parent_a_weight: u32,
+    pub parent_b_weight: u32,
+    //Chance, in percent, of a slot taking a random mintables trait of its type instead
+    pub mutation_percent: u64,
+    //The parents are burnt when true, otherwise they are locked and stay with their owner
+    pub burn_parents: bool,
+}
+
+impl Default for FusionConfig {
+    fn default() -> Self {
+        FusionConfig {
+            parent_a_weight: 1,
+            parent_b_weight: 1,
+            mutation_percent: 0,
+            burn_parents: true,
+        }
+    }
 }
 
-impl U64Ext for u64 {
-    fn bps_to_decimal(self) -> Decimal {
-        Decimal::percent(self) / Uint128::from(100u128)
+/// How the character manager turns characters back into traits
+#[cw_serde]
+#[derive(Default)]
+pub struct DismantleConfig {
+    //Manager minting the traits, it has to list the character manager. Dismantling is disabled when empty
+    pub trait_manager: Option<Addr>,
+    //Chance, in percent, of losing a trait of each rarity. Rarities not listed are never lost
+    pub loss_percents: Vec<(String, u64)>,
+}
+
+impl DismantleConfig {
+    pub fn loss_percent(&self, rarity: &str) -> u64 {
+        self.loss_percents
+            .iter()
+            .find(|(r, _)| r == rarity)
+            .map(|(_, percent)| *percent)
+            .unwrap_or_default()
+    }
+}
+
+/// Rules and fees for the names given to characters
+#[cw_serde]
+pub struct NameConfig {
+    //Bounds on the number of characters of a name, both included
+    pub min_length: u32,
+    pub max_length: u32,
+    //Characters a name can be made of, any printable character is allowed when empty
+    pub allowed_chars: Option<String>,
+    //Names that cannot be taken, compared without case
+    pub reserved_words: Vec<String>,
+    //Paid on every rename through the revenue split, renames are free when empty
+    pub rename_fee: Option<Coin>,
+    //Seconds a character has to wait between renames
+    pub rename_cooldown: Option<u64>,
+}
+
+impl Default for NameConfig {
+    fn default() -> Self {
+        NameConfig {
+            min_length: 1,
+            max_length: 64,
+            allowed_chars: None,
+            reserved_words: vec![],
+            rename_fee: None,
+            rename_cooldown: None,
+        }
+    }
+}
+
+impl NameConfig {
+    pub fn is_allowed(&self, name: &str) -> bool {
+        let length = name.chars().count() as u64;
+        if length < self.min_length as u64 || length > self.max_length as u64 {
+            return false;
+        }
+        //Surrounding spaces would let two names look the same
+        if name.trim() != name {
+            return false;
+        }
+        match &self.allowed_chars {
+            Some(allowed) => name.chars().all(|c| allowed.contains(c)),
+            None => name.chars().all(|c| !c.is_control()),
+        }
+    }
+
+    pub fn is_reserved(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.reserved_words
+            .iter()
+            .any(|word| word.to_lowercase() == name)
+    }
+}
+
+/// Manager operations that can be paused in an emergency
+#[cw_serde]
+pub enum Operation {
+    Mint,
+    Bundle,
+    Lootbox,
+    Modify,
+    Lock,
+    Rename,
+    //Transfers of the character collection
+    Transfer,
+    Craft,
+    Fuse,
+    Dismantle,
+}
+
+impl Operation {
+    pub fn all() -> Vec<Operation> {
+        vec![
+            Operation::Mint,
+            Operation::Bundle,
+            Operation::Lootbox,
+            Operation::Modify,
+            Operation::Lock,
+            Operation::Rename,
+            Operation::Transfer,
+            Operation::Craft,
+            Operation::Fuse,
+            Operation::Dismantle,
+        ]
+    }
+
+    /// Paused operations once `operations` are paused or unpaused
+    pub fn update_paused(
+        mut paused_operations: Vec<Operation>,
+        operations: &[Operation],
+        paused: bool,
+    ) -> Vec<Operation> {
+        paused_operations.retain(|operation| !operations.contains(operation));
+        if paused {
+            paused_operations.extend(operations.iter().cloned());
+        }
+        paused_operations
+    }
+}
+
+/// Where the managers get the randomness used to draw lootbox contents
+#[cw_serde]
+pub enum RandomnessSource {
+    /// Commit-reveal between the user's secret and a later block
+    Internal,
+    /// External beacon proxy that calls back `ReceiveRandomness` on the manager
+    Beacon { proxy_addr: Addr },
+}
+
+/// Allowlisted presale set by the owner of a manager
+#[cw_serde]
+pub struct PresalePhase {
+    //Hex encoded sha256 root of the allowlist, see `merkle::leaf_hash` for the leaves
+    pub merkle_root: String,
+    pub per_address_limit: u32,
+    //Percentage taken off the mint price
+    pub discount_percent: u64,
+}
+
+impl PresalePhase {
+    pub fn discounted_price(&self, price: &Coin) -> Coin {
+        Coin {
+            denom: price.denom.clone(),
+            amount: price
+                .amount
+                .multiply_ratio(100 - self.discount_percent, 100u128),
+        }
+    }
+
+    /// Whether `address` is in the allowlist, the proof being the sibling hashes from its leaf up to the root
+    pub fn allows(&self, address: &str, proof: &[String]) -> StdResult<bool> {
+        merkle::verify_proof(&self.merkle_root, merkle::leaf_hash(address), proof)
+    }
+
+    /// Presale mints left to an address that already minted `minted` times in the phase
+    pub fn remaining(&self, minted: u32) -> u32 {
+        self.per_address_limit.saturating_sub(minted)
+    }
+}
+
+/// Funds paid for a mint. Prices in a CW20 token use the token contract address as denom
+#[cw_serde]
+pub enum Payment {
+    Native(Coin),
+    Cw20(Coin),
+}
+
+impl Payment {
+    pub fn coin(&self) -> &Coin {
+        match self {
+            Payment::Native(coin) | Payment::Cw20(coin) => coin,
+        }
+    }
+
+    /// Message sending part of the payment to `to`, a bank send or a CW20 transfer
+    pub fn send_msg(&self, to: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+        Ok(match self {
+            Payment::Native(coin) => BankMsg::Send {
+                to_address: to.to_string(),
+                amount: vec![Coin::new(amount.u128(), &coin.denom)],
+            }
+            .into(),
+            Payment::Cw20(coin) => WasmMsg::Execute {
+                contract_addr: coin.denom.clone(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: to.to_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        })
     }
 }
@@ -0,0 +1,67 @@
+use cosmwasm_std::{HexBinary, StdError, StdResult};
+use sha2::{Digest, Sha256};
+
+/// Leaf of an allowlist tree, the sha256 hash of the address
+pub fn leaf_hash(address: &str) -> [u8; 32] {
+    Sha256::digest(address.as_bytes()).into()
+}
+
+/// Checks a proof against a hex encoded sha256 root.
+/// Pairs are sorted before hashing, so the proof does not need to say on which side each sibling is
+pub fn verify_proof(root: &str, leaf: [u8; 32], proof: &[String]) -> StdResult<bool> {
+    let root = parse_hash(root)?;
+
+    let computed = proof.iter().try_fold(leaf, |hash, sibling| {
+        let sibling = parse_hash(sibling)?;
+        let (first, second) = if hash <= sibling {
+            (hash, sibling)
+        } else {
+            (sibling, hash)
+        };
+        let mut hasher = Sha256::new();
+        hasher.update(first);
+        hasher.update(second);
+        Ok::<[u8; 32], StdError>(hasher.finalize().into())
+    })?;
+
+    Ok(computed == root)
+}
+
+/// Parses a hex encoded sha256 hash
+pub fn parse_hash(hash: &str) -> StdResult<[u8; 32]> {
+    HexBinary::from_hex(hash)?
+        .to_array()
+        .map_err(|_| StdError::generic_err("Hash must be 32 bytes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair_hash(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        let (first, second) = if a <= b { (a, b) } else { (b, a) };
+        let mut hasher = Sha256::new();
+        hasher.update(first);
+        hasher.update(second);
+        hasher.finalize().into()
+    }
+
+    #[test]
+    fn verify_allowlist_proofs() {
+        let leaves: Vec<[u8; 32]> = ["alice", "bob", "carol"].map(leaf_hash).to_vec();
+        let ab = pair_hash(leaves[0], leaves[1]);
+        let root = HexBinary::from(pair_hash(ab, leaves[2]).to_vec()).to_hex();
+
+        let proof = vec![
+            HexBinary::from(leaves[1].to_vec()).to_hex(),
+            HexBinary::from(leaves[2].to_vec()).to_hex(),
+        ];
+        assert!(verify_proof(&root, leaf_hash("alice"), &proof).unwrap());
+        assert!(!verify_proof(&root, leaf_hash("mallory"), &proof).unwrap());
+
+        let proof = vec![HexBinary::from(ab.to_vec()).to_hex()];
+        assert!(verify_proof(&root, leaf_hash("carol"), &proof).unwrap());
+
+        assert!(verify_proof(&root, leaf_hash("alice"), &["zz".to_string()]).is_err());
+    }
+}
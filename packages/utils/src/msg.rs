@@ -2,6 +2,10 @@ use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Addr, Coin};
 use cw721_base::msg::{CollectionInfo, RoyaltyInfoResponse};
 
+use crate::{
+    DismantleConfig, FusionConfig, MintLimits, NameConfig, RandomnessSource, RevenueSplit,
+};
+
 pub type BaseTraitManagerCreateMsg<T> = CreateTraitManagerMsg<T>;
 pub type BaseCharacterManagerCreateMsg<T> = CreateCharacterManagerMsg<T>;
 
@@ -12,6 +16,9 @@ pub struct CollectionParams {
     pub name: String,
     pub symbol: Option<String>,
     pub info: CollectionInfo<RoyaltyInfoResponse>,
+    /// Url template of the token images, see the collection README for the placeholders
+    #[serde(default)]
+    pub image_template: Option<String>,
 }
 
 #[cw_serde]
@@ -25,10 +32,10 @@ pub struct CreateTraitManagerMsg<T> {
 pub struct TraitManagerParams {
     pub mint_prices: Vec<Coin>,
     pub rarities: Vec<String>,
-    //This ratio will be burnt
-    pub burn_ratio: u64,
-    //Rest sent here
-    pub destination: Option<Addr>,
+    pub revenue_split: RevenueSplit,
+    #[serde(default)]
+    pub mint_limits: MintLimits,
+    pub randomness_source: RandomnessSource,
     pub mintable_collection_addr: Addr,
 }
 
@@ -36,10 +43,10 @@ pub struct TraitManagerParams {
 pub struct UpdateTraitManagerParamsMsg {
     pub mint_prices: Vec<Coin>,
     pub rarities: Vec<String>,
-    //This ratio will be burnt
-    pub burn_ratio: u64,
-    //Rest sent here
-    pub destination: Option<Addr>,
+    pub revenue_split: RevenueSplit,
+    #[serde(default)]
+    pub mint_limits: MintLimits,
+    pub randomness_source: RandomnessSource,
 }
 
 #[cw_serde]
@@ -54,10 +61,16 @@ pub struct CharacterManagerParams {
     pub empty_character_mint_price: Coin,
     pub character_mint_prices: Vec<Coin>,
     pub character_rarities: Vec<String>,
-    //This ratio will be burnt
-    pub burn_ratio: u64,
-    //Rest sent here
-    pub destination: Option<Addr>,
+    pub revenue_split: RevenueSplit,
+    #[serde(default)]
+    pub mint_limits: MintLimits,
+    pub randomness_source: RandomnessSource,
+    #[serde(default)]
+    pub fusion: FusionConfig,
+    #[serde(default)]
+    pub dismantle: DismantleConfig,
+    #[serde(default)]
+    pub names: NameConfig,
     pub trait_collection_addr: Addr,
     pub mintable_collection_addr: Addr,
 }
@@ -67,8 +80,29 @@ pub struct UpdateCharacterManagerParamsMsg {
     pub empty_character_mint_price: Coin,
     pub character_mint_prices: Vec<Coin>,
     pub character_rarities: Vec<String>,
-    //This ratio will be burnt
-    pub burn_ratio: u64,
-    //Rest sent here
-    pub destination: Option<Addr>,
+    pub revenue_split: RevenueSplit,
+    #[serde(default)]
+    pub mint_limits: MintLimits,
+    pub randomness_source: RandomnessSource,
+    #[serde(default)]
+    pub fusion: FusionConfig,
+    #[serde(default)]
+    pub dismantle: DismantleConfig,
+    #[serde(default)]
+    pub names: NameConfig,
+}
+
+/// Message the managers send to the randomness beacon proxy when a lootbox is opened
+#[cw_serde]
+pub enum RandomnessProxyExecuteMsg {
+    GetNextRandomness { job_id: String },
+}
+
+/// Message the character manager sends to the trait manager to mint the traits of a dismantled character
+#[cw_serde]
+pub enum TraitManagerMintMsg<T> {
+    MintTraits {
+        receiver: String,
+        token_infos: Vec<T>,
+    },
 }
@@ -1,9 +1,11 @@
 use cosmwasm_schema::QueryResponses;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::Empty;
+use cosmwasm_std::{Empty, Uint128};
 
 use crate::CharacterManagerConfig;
 use crate::CodeId;
+use crate::Operation;
+use crate::PresalePhase;
 use crate::TraitManagerConfig;
 
 #[cw_serde]
@@ -13,6 +15,36 @@ pub enum CharacterManagerQueryMsg {
     Config {},
     #[returns(AllowedCollectionCodeIdResponse)]
     AllowedCollectionCodeId {},
+    #[returns(LootboxCommitmentResponse)]
+    LootboxCommitment { commitment_id: u64 },
+    #[returns(PresalePhase)]
+    PresalePhase { phase_id: u32 },
+    #[returns(PresaleMintsResponse)]
+    PresaleMints { phase_id: u32, address: String },
+    #[returns(RevenueResponse)]
+    Revenue { denom: String },
+    #[returns(Vec<RevenueResponse>)]
+    AllRevenue {},
+    //Purchases left to an address under the manager caps and, when given, the caps of the item
+    #[returns(MintAllowanceResponse)]
+    MintAllowance {
+        address: String,
+        item: Option<SaleItem>,
+    },
+    #[returns(PauseStatusResponse)]
+    PauseStatus {},
+    //Parents of a character minted by fusion
+    #[returns(LineageResponse)]
+    Lineage { token_id: String },
+    //Character holding a name, names are matched without case
+    #[returns(ResolveNameResponse)]
+    ResolveName { name: String },
+    //Addresses allowed to reset names
+    #[returns(Vec<String>)]
+    Moderators {},
+    //Name resets of a character, oldest first
+    #[returns(Vec<ModerationResponse>)]
+    ModerationHistory { token_id: String },
 }
 
 #[cw_serde]
@@ -22,6 +54,27 @@ pub enum TraitManagerQueryMsg {
     Config {},
     #[returns(AllowedCollectionCodeIdResponse)]
     AllowedCollectionCodeId {},
+    #[returns(LootboxCommitmentResponse)]
+    LootboxCommitment { commitment_id: u64 },
+    #[returns(PresalePhase)]
+    PresalePhase { phase_id: u32 },
+    #[returns(PresaleMintsResponse)]
+    PresaleMints { phase_id: u32, address: String },
+    #[returns(RevenueResponse)]
+    Revenue { denom: String },
+    #[returns(Vec<RevenueResponse>)]
+    AllRevenue {},
+    //Purchases left to an address under the manager caps and, when given, the caps of the item
+    #[returns(MintAllowanceResponse)]
+    MintAllowance {
+        address: String,
+        item: Option<SaleItem>,
+    },
+    #[returns(PauseStatusResponse)]
+    PauseStatus {},
+    //Managers allowed to mint traits through this manager
+    #[returns(Vec<String>)]
+    Managers {},
 }
 
 #[cw_serde]
@@ -40,3 +93,73 @@ pub struct CharacterManagerConfigResponse<T> {
 pub struct AllowedCollectionCodeIdResponse {
     pub code_id: CodeId,
 }
+
+#[cw_serde]
+pub struct LootboxCommitmentResponse {
+    pub owner: String,
+    pub receiver: String,
+    pub lootbox_id: u32,
+    //Empty while the lootbox waits for randomness from the beacon
+    pub commitment: Option<String>,
+    pub committed_at: u64,
+    //Last block height in which the secret can be revealed, after it anyone can resolve the lootbox or refund the beacon job
+    pub expires_at: u64,
+}
+
+#[cw_serde]
+pub struct PresaleMintsResponse {
+    pub minted: u32,
+    pub remaining: u32,
+}
+
+#[cw_serde]
+pub struct RevenueResponse {
+    pub denom: String,
+    pub accrued: Uint128,
+    pub burned: Uint128,
+    pub lifetime: Uint128,
+}
+
+/// Catalog entry sold by a manager, by its id in mintables
+#[cw_serde]
+pub enum SaleItem {
+    //A trait or a character, depending on the manager
+    Single { id: u32 },
+    Bundle { id: u32 },
+    Lootbox { id: u32 },
+}
+
+#[cw_serde]
+pub struct MintAllowanceResponse {
+    pub minted: u32,
+    pub item_minted: u32,
+    //Empty when no cap applies
+    pub remaining: Option<u32>,
+}
+
+#[cw_serde]
+pub struct PauseStatusResponse {
+    pub paused: Vec<Operation>,
+    pub guardian: Option<String>,
+}
+
+#[cw_serde]
+pub struct LineageResponse {
+    //Empty when the character was not fused
+    pub parents: Vec<String>,
+    //Zero for characters that were not fused, otherwise one more than the older parent
+    pub generation: u32,
+}
+
+#[cw_serde]
+pub struct ResolveNameResponse {
+    pub token_id: Option<String>,
+}
+
+#[cw_serde]
+pub struct ModerationResponse {
+    pub moderator: String,
+    pub previous_name: Option<String>,
+    pub reason: String,
+    pub height: u64,
+}
@@ -0,0 +1,96 @@
+use cosmwasm_std::BlockInfo;
+use sha2::{Digest, Sha256};
+
+/// Entropy of a block, mixed into the draws committed in an earlier block so that
+/// nobody knows their result when committing
+pub fn block_entropy(block: &BlockInfo) -> String {
+    format!(
+        "{:x}",
+        Sha256::digest(
+            format!("{}{}{}", block.chain_id, block.height, block.time.nanos()).as_bytes()
+        )
+    )
+}
+
+/// Position drawn from a list of possibilities.
+/// Possibilities are weights, so the draw is made over their total
+pub fn drawn_position(possibilities: &[u32], seed: String) -> usize {
+    let total: u32 = possibilities.iter().sum();
+    let mut current = random_number_1_to_x(seed, total);
+    let mut position = 0;
+
+    //Find which item of the lootbox we get according to possibilities
+    for possibility in possibilities {
+        if current <= *possibility {
+            break;
+        } else {
+            position += 1;
+            current -= possibility;
+        }
+    }
+    position
+}
+
+//We get around using random libraries by importing the things we need from
+//https://docs.rs/rand/0.8.1/i686-unknown-linux-gnu/src/rand/rngs/xoshiro128plusplus.rs.html
+
+pub fn random_number_1_to_x(seed: String, x: u32) -> u32 {
+    let sha256 = Sha256::digest(seed.into_bytes());
+    // Cut first 16 bytes from 32 byte value
+    let randomness: [u8; 16] = sha256.to_vec()[0..16].try_into().unwrap();
+    let mut state = [0; 4];
+    read_u32_into(&randomness, &mut state);
+    let rng = get_u32(&mut state);
+    rng.checked_rem_euclid(x).unwrap() + 1
+}
+
+pub fn read_u32_into(src: &[u8], dst: &mut [u32]) {
+    assert!(src.len() >= 4 * dst.len());
+    for (out, chunk) in dst.iter_mut().zip(src.chunks_exact(4)) {
+        *out = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+}
+
+pub fn get_u32(dst: &mut [u32]) -> u32 {
+    let result_starstar = dst[0]
+        .wrapping_add(dst[3])
+        .rotate_left(7)
+        .wrapping_add(dst[0]);
+
+    let t = dst[1] << 9;
+
+    dst[2] ^= dst[0];
+    dst[3] ^= dst[1];
+    dst[1] ^= dst[2];
+    dst[0] ^= dst[3];
+
+    dst[2] ^= t;
+
+    dst[3] = dst[3].rotate_left(11);
+
+    result_starstar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_are_in_range() {
+        for i in 0..100 {
+            let number = random_number_1_to_x(format!("seed{}", i), 7);
+            assert!((1..=7).contains(&number));
+        }
+        assert_eq!(
+            random_number_1_to_x("seed".to_string(), 7),
+            random_number_1_to_x("seed".to_string(), 7)
+        );
+    }
+
+    #[test]
+    fn zero_weights_are_never_drawn() {
+        for i in 0..100 {
+            assert_eq!(drawn_position(&[0, 5, 0], format!("seed{}", i)), 1);
+        }
+    }
+}
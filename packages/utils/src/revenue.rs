@@ -0,0 +1,310 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{coins, Addr, Api, BankMsg, Coin, CosmosMsg, StdResult, Uint128};
+
+use crate::query::RevenueResponse;
+use crate::{Payment, NATIVE_DENOM};
+
+/// How the managers divide the funds paid for every mint
+#[cw_serde]
+pub struct RevenueSplit {
+    //Each recipient gets its weight over the total weight of the split
+    pub recipients: Vec<(Addr, u64)>,
+    //Share of the native denom that is burnt, other denoms are divided between the recipients only
+    pub burn_weight: u64,
+    //Gets what is left after rounding down every share
+    pub dust_recipient: Addr,
+    //Keeps the recipients' shares in the manager until they are withdrawn, only the burn is paid out
+    #[serde(default)]
+    pub accrue: bool,
+}
+
+impl RevenueSplit {
+    pub fn recipients_weight(&self) -> u64 {
+        self.recipients.iter().map(|(_, weight)| weight).sum()
+    }
+
+    pub fn total_weight(&self) -> u64 {
+        self.recipients_weight() + self.burn_weight
+    }
+
+    /// Checks the addresses of the split, false when nothing could be paid out as every weight is zero
+    pub fn is_valid(&self, api: &dyn Api) -> StdResult<bool> {
+        for (recipient, _) in &self.recipients {
+            api.addr_validate(recipient.as_str())?;
+        }
+        api.addr_validate(self.dust_recipient.as_str())?;
+
+        Ok(self.total_weight() > 0)
+    }
+
+    fn burnable(payment: &Payment) -> bool {
+        matches!(payment, Payment::Native(coin) if coin.denom == NATIVE_DENOM)
+    }
+
+    fn payment_weight(&self, payment: &Payment) -> u64 {
+        if Self::burnable(payment) {
+            self.total_weight()
+        } else {
+            self.recipients_weight()
+        }
+    }
+
+    pub fn burn_amount(&self, payment: &Payment) -> Uint128 {
+        let total_weight = self.payment_weight(payment);
+        if !Self::burnable(payment) || total_weight == 0 {
+            return Uint128::zero();
+        }
+        payment
+            .coin()
+            .amount
+            .multiply_ratio(self.burn_weight, total_weight)
+    }
+
+    /// Messages burning and sending every share of the payment. When accruing only the burn is sent
+    pub fn payout_msgs(&self, payment: &Payment) -> StdResult<Vec<CosmosMsg>> {
+        let paid = payment.coin();
+        let total_weight = self.payment_weight(payment);
+
+        let mut msgs = vec![];
+        let burnt = self.burn_amount(payment);
+        if !burnt.is_zero() {
+            msgs.push(
+                BankMsg::Burn {
+                    amount: coins(burnt.u128(), &paid.denom),
+                }
+                .into(),
+            );
+        }
+        if self.accrue {
+            return Ok(msgs);
+        }
+
+        let mut remaining = paid.amount - burnt;
+        if total_weight > 0 {
+            for (recipient, weight) in &self.recipients {
+                let share = paid.amount.multiply_ratio(*weight, total_weight);
+                if !share.is_zero() {
+                    msgs.push(payment.send_msg(recipient, share)?);
+                    remaining -= share;
+                }
+            }
+        }
+
+        if remaining > Uint128::zero() {
+            msgs.push(payment.send_msg(&self.dust_recipient, remaining)?);
+        }
+
+        Ok(msgs)
+    }
+
+    /// Counts the payment in the totals of its denom and returns the messages paying it out
+    pub fn pay_out(
+        &self,
+        totals: &mut RevenueTotals,
+        payment: &Payment,
+    ) -> StdResult<Vec<CosmosMsg>> {
+        totals.record(self, payment);
+        self.payout_msgs(payment)
+    }
+}
+
+/// Revenue accounting of a manager for one denom
+#[cw_serde]
+#[derive(Default)]
+pub struct RevenueTotals {
+    //CW20 denoms are the token contract address
+    pub cw20: bool,
+    //Held by the manager and available to withdraw
+    pub accrued: Uint128,
+    pub burned: Uint128,
+    //Everything paid in this denom, including what was burnt
+    pub lifetime: Uint128,
+}
+
+impl RevenueTotals {
+    pub fn new(payment: &Payment) -> Self {
+        RevenueTotals {
+            cw20: matches!(payment, Payment::Cw20(_)),
+            accrued: Uint128::zero(),
+            burned: Uint128::zero(),
+            lifetime: Uint128::zero(),
+        }
+    }
+
+    pub fn record(&mut self, split: &RevenueSplit, payment: &Payment) {
+        let paid = payment.coin().amount;
+        let burned = split.burn_amount(payment);
+        self.lifetime += paid;
+        self.burned += burned;
+        if split.accrue {
+            self.accrued += paid - burned;
+        }
+    }
+
+    /// Payment in this denom, used to send out what was accrued
+    pub fn payment(&self, denom: &str) -> Payment {
+        let coin = Coin::new(0, denom);
+        if self.cw20 {
+            Payment::Cw20(coin)
+        } else {
+            Payment::Native(coin)
+        }
+    }
+
+    /// Takes `amount` out of the accrued funds and returns the message sending it to `to`.
+    /// None when the amount is zero or more than what was accrued
+    pub fn withdraw(
+        &mut self,
+        denom: &str,
+        amount: Uint128,
+        to: &Addr,
+    ) -> StdResult<Option<CosmosMsg>> {
+        if amount.is_zero() || amount > self.accrued {
+            return Ok(None);
+        }
+        self.accrued -= amount;
+        self.payment(denom).send_msg(to, amount).map(Some)
+    }
+
+    pub fn response(&self, denom: String) -> RevenueResponse {
+        RevenueResponse {
+            denom,
+            accrued: self.accrued,
+            burned: self.burned,
+            lifetime: self.lifetime,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::mock_dependencies;
+    use cosmwasm_std::{coin, to_binary, WasmMsg};
+    use cw20::Cw20ExecuteMsg;
+
+    fn split() -> RevenueSplit {
+        RevenueSplit {
+            recipients: vec![
+                (Addr::unchecked("artist"), 2),
+                (Addr::unchecked("treasury"), 1),
+            ],
+            burn_weight: 1,
+            dust_recipient: Addr::unchecked("treasury"),
+            accrue: false,
+        }
+    }
+
+    fn send(to: &str, amount: u128, denom: &str) -> CosmosMsg {
+        BankMsg::Send {
+            to_address: to.to_string(),
+            amount: coins(amount, denom),
+        }
+        .into()
+    }
+
+    #[test]
+    fn validate_split() {
+        let deps = mock_dependencies();
+        assert!(split().is_valid(&deps.api).unwrap());
+
+        let unweighted = RevenueSplit {
+            recipients: vec![(Addr::unchecked("artist"), 0)],
+            burn_weight: 0,
+            ..split()
+        };
+        assert!(!unweighted.is_valid(&deps.api).unwrap());
+
+        let invalid = RevenueSplit {
+            dust_recipient: Addr::unchecked(""),
+            ..split()
+        };
+        invalid.is_valid(&deps.api).unwrap_err();
+    }
+
+    #[test]
+    fn split_native_payment() {
+        let msgs = split()
+            .payout_msgs(&Payment::Native(coin(101, NATIVE_DENOM)))
+            .unwrap();
+        assert_eq!(
+            msgs,
+            vec![
+                BankMsg::Burn {
+                    amount: coins(25, NATIVE_DENOM)
+                }
+                .into(),
+                send("artist", 50, NATIVE_DENOM),
+                send("treasury", 25, NATIVE_DENOM),
+                send("treasury", 1, NATIVE_DENOM),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_without_burning_other_denoms() {
+        let msgs = split()
+            .payout_msgs(&Payment::Native(coin(90, "uatom")))
+            .unwrap();
+        assert_eq!(
+            msgs,
+            vec![send("artist", 60, "uatom"), send("treasury", 30, "uatom")]
+        );
+
+        let msgs = split()
+            .payout_msgs(&Payment::Cw20(coin(3, "token")))
+            .unwrap();
+        assert_eq!(
+            msgs[0],
+            WasmMsg::Execute {
+                contract_addr: "token".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "artist".to_string(),
+                    amount: 2u128.into(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }
+            .into()
+        );
+        assert_eq!(msgs.len(), 2);
+    }
+
+    #[test]
+    fn accrue_native_payment() {
+        let split = RevenueSplit {
+            accrue: true,
+            ..split()
+        };
+        let payment = Payment::Native(coin(101, NATIVE_DENOM));
+        assert_eq!(
+            split.payout_msgs(&payment).unwrap(),
+            vec![BankMsg::Burn {
+                amount: coins(25, NATIVE_DENOM)
+            }
+            .into()]
+        );
+
+        let mut totals = RevenueTotals::new(&payment);
+        totals.record(&split, &payment);
+        split.pay_out(&mut totals, &payment).unwrap();
+        assert_eq!(totals.lifetime.u128(), 202);
+        assert_eq!(totals.burned.u128(), 50);
+        assert_eq!(totals.accrued.u128(), 152);
+
+        let vault = Addr::unchecked("vault");
+        assert_eq!(
+            totals
+                .withdraw(NATIVE_DENOM, Uint128::new(153), &vault)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            totals
+                .withdraw(NATIVE_DENOM, Uint128::new(52), &vault)
+                .unwrap(),
+            Some(send("vault", 52, NATIVE_DENOM))
+        );
+        assert_eq!(totals.accrued.u128(), 100);
+    }
+}